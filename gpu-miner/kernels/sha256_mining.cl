@@ -0,0 +1,219 @@
+/**
+ * Kernel OpenCL pour Mining SHA256
+ *
+ * Même préimage que kernels/sha256_mining.cu et pow::compute_hash:
+ * challenge (32) || miner_pubkey (32) || nonce (16, u128 LE) || block_number (8, LE).
+ * Seuls les 64 bits bas du nonce sont parcourus.
+ */
+
+// SHA256 Constants
+__constant uint K[64] = {
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5,
+    0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3,
+    0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc,
+    0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7,
+    0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13,
+    0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3,
+    0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5,
+    0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208,
+    0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2
+};
+
+// SHA256 helper functions
+#define ROTR(x, n) (((x) >> (n)) | ((x) << (32 - (n))))
+#define CH(x, y, z) (((x) & (y)) ^ (~(x) & (z)))
+#define MAJ(x, y, z) (((x) & (y)) ^ ((x) & (z)) ^ ((y) & (z)))
+#define EP0(x) (ROTR(x, 2) ^ ROTR(x, 13) ^ ROTR(x, 22))
+#define EP1(x) (ROTR(x, 6) ^ ROTR(x, 11) ^ ROTR(x, 25))
+#define SIG0(x) (ROTR(x, 7) ^ ROTR(x, 18) ^ ((x) >> 3))
+#define SIG1(x) (ROTR(x, 17) ^ ROTR(x, 19) ^ ((x) >> 10))
+
+// Preimage length in bytes, and in bits for the padding
+#define PREIMAGE_LEN 88
+
+void sha256_init(uint* state) {
+    state[0] = 0x6a09e667;
+    state[1] = 0xbb67ae85;
+    state[2] = 0x3c6ef372;
+    state[3] = 0xa54ff53a;
+    state[4] = 0x510e527f;
+    state[5] = 0x9b05688c;
+    state[6] = 0x1f83d9ab;
+    state[7] = 0x5be0cd19;
+}
+
+void sha256_transform(uint* state, const uint* data) {
+    uint a, b, c, d, e, f, g, h;
+    uint w[64];
+    uint t1, t2;
+
+    // Prepare message schedule
+    for (int i = 0; i < 16; i++) {
+        w[i] = data[i];
+    }
+    for (int i = 16; i < 64; i++) {
+        w[i] = SIG1(w[i-2]) + w[i-7] + SIG0(w[i-15]) + w[i-16];
+    }
+
+    // Initialize working variables
+    a = state[0];
+    b = state[1];
+    c = state[2];
+    d = state[3];
+    e = state[4];
+    f = state[5];
+    g = state[6];
+    h = state[7];
+
+    // Main loop
+    for (int i = 0; i < 64; i++) {
+        t1 = h + EP1(e) + CH(e, f, g) + K[i] + w[i];
+        t2 = EP0(a) + MAJ(a, b, c);
+        h = g;
+        g = f;
+        f = e;
+        e = d + t1;
+        d = c;
+        c = b;
+        b = a;
+        a = t1 + t2;
+    }
+
+    // Add to state
+    state[0] += a;
+    state[1] += b;
+    state[2] += c;
+    state[3] += d;
+    state[4] += e;
+    state[5] += f;
+    state[6] += g;
+    state[7] += h;
+}
+
+// Big-endian word from 4 bytes
+uint load_be(const uchar* p) {
+    return ((uint)p[0] << 24) | ((uint)p[1] << 16) | ((uint)p[2] << 8) | (uint)p[3];
+}
+
+// SHA256 of the 88-byte preimage: two blocks, the second one padded
+void sha256_preimage(const uchar* data, uint* state) {
+    uint block[16];
+
+    sha256_init(state);
+
+    for (int i = 0; i < 16; i++) {
+        block[i] = load_be(data + i * 4);
+    }
+    sha256_transform(state, block);
+
+    // Remaining 24 bytes, then 0x80, zeros and the length in bits
+    for (int i = 0; i < 6; i++) {
+        block[i] = load_be(data + 64 + i * 4);
+    }
+    block[6] = 0x80000000;
+    for (int i = 7; i < 15; i++) {
+        block[i] = 0;
+    }
+    block[15] = PREIMAGE_LEN * 8;
+    sha256_transform(state, block);
+}
+
+// SHA256 over the 32-byte digest held in `state` (second pass of double SHA256)
+void sha256_digest(uint* state) {
+    uint block[16];
+
+    for (int i = 0; i < 8; i++) {
+        block[i] = state[i];
+    }
+    block[8] = 0x80000000;
+    for (int i = 9; i < 15; i++) {
+        block[i] = 0;
+    }
+    block[15] = 256;
+
+    sha256_init(state);
+    sha256_transform(state, block);
+}
+
+// Compare the first 16 bytes of the digest as a little-endian u128 (same as pow::is_valid_hash)
+bool is_valid_hash(const uint* state, __global const uchar* target) {
+    for (int i = 15; i >= 0; i--) {
+        uchar byte = (state[i / 4] >> (24 - 8 * (i % 4))) & 0xff;
+        if (byte < target[i]) return true;
+        if (byte > target[i]) return false;
+    }
+    return false;
+}
+
+__kernel void mine(
+    __global const uchar* challenge,     // 32 bytes
+    __global const uchar* miner_pubkey,  // 32 bytes
+    ulong block_number,
+    ulong start_nonce,
+    ulong nonce_count,                   // Nonces to test from start_nonce
+    __global const uchar* target,        // 16 bytes (little-endian u128)
+    __global ulong* result_nonce,        // Output: valid nonce
+    volatile __global uint* found,       // Output: 1 if found
+    uint double_sha,                     // 1: SHA256(SHA256(preimage))
+    uint nonces_per_thread,              // Nonces tested by each work-item
+    uint early_exit,                     // 1: stop once a sibling has found a nonce
+    __global uint* tested,               // Output: nonces hashed, one slot per work-item
+    ulong work_items                     // Work-items launched by the host
+) {
+    ulong gid = get_global_id(0);
+    // The runtime may round the global size up: extra work-items must not write
+    if (gid >= work_items) return;
+    ulong stride = work_items;
+    uint count = 0;
+
+    // challenge || miner_pubkey || nonce (u128 LE, high 64 bits zero) || block_number (LE)
+    uchar data[PREIMAGE_LEN];
+    for (int i = 0; i < 32; i++) {
+        data[i] = challenge[i];
+        data[32 + i] = miner_pubkey[i];
+    }
+    for (int i = 0; i < 8; i++) {
+        data[72 + i] = 0;
+        data[80 + i] = (block_number >> (i * 8)) & 0xff;
+    }
+
+    for (uint iter = 0; iter < nonces_per_thread; iter++) {
+        // Re-read the flag (volatile) before each nonce: a sibling may have found one
+        // since this work-item started, the rest of the batch is wasted work
+        if (early_exit && *found) break;
+
+        // Stride by the work-item count so a batch covers a contiguous nonce range
+        ulong idx = gid + (ulong)iter * stride;
+        if (idx >= nonce_count) break;
+        ulong nonce = start_nonce + idx;
+
+        for (int i = 0; i < 8; i++) {
+            data[64 + i] = (nonce >> (i * 8)) & 0xff;
+        }
+
+        uint state[8];
+        sha256_preimage(data, state);
+
+        // Optional second pass over the 32-byte first digest
+        if (double_sha) {
+            sha256_digest(state);
+        }
+        count++;
+
+        if (is_valid_hash(state, target)) {
+            if (atomic_cmpxchg(found, 0, 1) == 0) {
+                *result_nonce = nonce;
+            }
+            break;
+        }
+    }
+
+    tested[gid] = count;
+}
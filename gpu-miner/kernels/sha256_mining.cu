@@ -1,7 +1,9 @@
 /**
  * CUDA Kernel pour Mining SHA256
  * 
- * Ce kernel calcule SHA256(challenge || nonce) pour trouver un hash < target
+ * Ce kernel calcule SHA256(challenge || miner_pubkey || nonce || block_number) pour trouver
+ * un hash < target. Le nonce est encodé sur 16 bytes (u128 LE) comme pow::compute_hash;
+ * seuls les 64 bits bas sont parcourus.
  */
 
 #include <stdint.h>
@@ -164,9 +166,10 @@ extern "C" __global__ void mine_block(
     uint64_t block_number,         // Block number
     uint64_t start_nonce,          // Starting nonce
     uint64_t nonce_count,          // Number of nonces to test
-    const uint8_t* target,         // 32 bytes target (big-endian)
+    const uint8_t* target,         // 32 bytes target (little-endian u128 in bytes 0..16)
     uint64_t* result_nonce,        // Output: found nonce
-    int* found                     // Output: 1 if found, 0 otherwise
+    int* found,                    // Output: 1 if found, 0 otherwise
+    int double_sha                 // 1: SHA256(SHA256(data)) (Bitcoin-style)
 ) {
     // Calculate global thread ID
     uint64_t idx = blockIdx.x * blockDim.x + threadIdx.x;
@@ -221,6 +224,15 @@ extern "C" __global__ void mine_block(
     uint8_t hash[32];
     sha256_hash(data, 88, hash);
 
+    // Optional second pass over the 32-byte first digest
+    if (double_sha) {
+        uint8_t first[32];
+        for (int i = 0; i < 32; i++) {
+            first[i] = hash[i];
+        }
+        sha256_hash(first, 32, hash);
+    }
+
     // Compare first 16 bytes as little-endian u128 (same as Rust side)
     // Convert hash[0..16] to u128 (little-endian)
     unsigned long long hash_low = 0;
@@ -252,62 +264,3 @@ extern "C" __global__ void mine_block(
         atomicExch((unsigned long long*)result_nonce, nonce);
     }
 }
-
-// ============================================================================
-// Batch Mining Kernel (optimized for multiple attempts)
-// ============================================================================
-
-extern "C" __global__ void mine_block_batch(
-    const uint8_t* challenges,     // N × 32 bytes challenges
-    uint64_t start_nonce,
-    uint64_t nonce_count,
-    const uint8_t* target,
-    uint64_t* result_nonces,       // N outputs
-    int* found_flags,              // N outputs
-    int num_challenges
-) {
-    uint64_t idx = blockIdx.x * blockDim.x + threadIdx.x;
-    
-    if (idx >= nonce_count) return;
-    
-    uint64_t nonce = start_nonce + idx;
-    
-    // Process each challenge
-    for (int ch = 0; ch < num_challenges; ch++) {
-        if (found_flags[ch]) continue;  // Already found
-        
-        const uint8_t* challenge = challenges + (ch * 32);
-        
-        uint8_t data[40];
-        for (int i = 0; i < 32; i++) {
-            data[i] = challenge[i];
-        }
-        
-        // Add nonce
-        data[32] = nonce & 0xff;
-        data[33] = (nonce >> 8) & 0xff;
-        data[34] = (nonce >> 16) & 0xff;
-        data[35] = (nonce >> 24) & 0xff;
-        data[36] = (nonce >> 32) & 0xff;
-        data[37] = (nonce >> 40) & 0xff;
-        data[38] = (nonce >> 48) & 0xff;
-        data[39] = (nonce >> 56) & 0xff;
-        
-        uint8_t hash[32];
-        sha256_hash(data, 40, hash);
-        
-        bool is_valid = true;
-        for (int i = 0; i < 32; i++) {
-            if (hash[i] < target[i]) break;
-            else if (hash[i] > target[i]) {
-                is_valid = false;
-                break;
-            }
-        }
-        
-        if (is_valid) {
-            atomicExch(&found_flags[ch], 1);
-            atomicExch((unsigned long long*)&result_nonces[ch], nonce);
-        }
-    }
-}
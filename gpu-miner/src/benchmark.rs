@@ -1,94 +1,182 @@
 //! Benchmark pour comparer les performances CPU vs GPU
 
-use std::time::Instant;
+// Les modules sont partagés avec le binaire miner, qui en utilise davantage
+#![allow(dead_code)]
+
+use clap::Parser;
+use std::time::{Duration, Instant};
 
 mod config;
+mod logging;
 mod miner;
+mod numa;
 mod pow;
+mod power;
+mod report;
+mod solution_log;
+mod tasks;
 
 #[cfg(feature = "cuda")]
 mod cuda_miner;
 
-use miner::MinerBackend;
+#[cfg(feature = "opencl")]
+mod opencl_miner;
 
-fn main() {
-    env_logger::init();
-    
-    println!("╔══════════════════════════════════════════════════════════════╗");
-    println!("║            POW MINER - BENCHMARK COMPLET                     ║");
-    println!("╚══════════════════════════════════════════════════════════════╝\n");
-    
-    let challenge = [0u8; 32];
-    let miner_pubkey = [1u8; 32]; // Dummy miner pubkey for benchmark
-    let difficulties = vec![
-        ("Très facile", 1_000),
-        ("Facile", 10_000),
-        ("Moyen", 100_000),
-        ("Difficile", 1_000_000),
-        ("Très difficile", 10_000_000),
-    ];
+use miner::{MinerBackend, MiningControl};
+use report::{BenchJob, OutputFormat, Reporter};
 
-    // Benchmark CPU
-    println!("📊 CPU Mining (multi-threaded)\n");
-    println!("Threads: {}\n", num_cpus::get());
+#[derive(Parser)]
+#[command(name = "benchmark")]
+#[command(about = "Compare CPU and GPU mining performance", long_about = None)]
+struct Args {
+    /// Format de sortie des résultats
+    #[arg(long, value_enum, default_value = "text")]
+    output: OutputFormat,
 
-    let cpu_miner = miner::CpuMiner::new(num_cpus::get());
+    /// Durée du warm-up exclu de la mesure, par backend (ms, 0 pour désactiver)
+    #[arg(long, default_value = "200")]
+    warmup_ms: u64,
 
-    let block_number = 0; // Numéro de bloc fictif pour le benchmark
+    /// Puissance du GPU en watts pour calculer l'efficacité (MH/J), sinon lue via NVML si disponible
+    #[arg(long)]
+    gpu_watts: Option<f64>,
 
-    for (name, diff) in &difficulties {
-        let target = u128::MAX / diff;
+    /// N'affiche que les erreurs
+    #[arg(short, long, conflicts_with = "verbose")]
+    quiet: bool,
 
-        print!("  {} (diff: {})... ", name, diff);
+    /// Couleurs, emoji et cadres: auto (terminal sans NO_COLOR), always ou never
+    #[arg(long, value_enum, default_value = "auto")]
+    color: logging::ColorMode,
 
-        let start = Instant::now();
-        match cpu_miner.mine(&challenge, &miner_pubkey, block_number, target, u128::MAX) {
-            Some(nonce) => {
-                let elapsed = start.elapsed();
-                let hashrate = (nonce as f64) / elapsed.as_secs_f64();
-                println!("✓ {:?} ({:.2} MH/s)", elapsed, hashrate / 1_000_000.0);
-            }
-            None => {
-                println!("✗ Not found");
-            }
-        }
+    /// Augmente la verbosité (-v info, -vv debug, -vvv trace); RUST_LOG reste prioritaire
+    #[arg(short, long, action = clap::ArgAction::Count)]
+    verbose: u8,
+}
+
+const DIFFICULTIES: [(&str, u128); 5] = [
+    ("Très facile", 1_000),
+    ("Facile", 10_000),
+    ("Moyen", 100_000),
+    ("Difficile", 1_000_000),
+    ("Très difficile", 10_000_000),
+];
+
+/// Découpages de grille CUDA comparés au lancement unique
+#[cfg(feature = "cuda")]
+const CUDA_SUB_LAUNCHES: [usize; 2] = [4, 16];
+
+fn main() {
+    let args = Args::parse();
+    logging::init(args.quiet, args.verbose, args.color);
+    let reporter = report::reporter(args.output);
+    // Les en-têtes décoratifs ne doivent pas polluer la sortie JSON ou CSV
+    let text = args.output == OutputFormat::Text;
+
+    if text {
+        println!("{}", logging::decorate("╔══════════════════════════════════════════════════════════════╗"));
+        println!("{}", logging::decorate("║            POW MINER - BENCHMARK COMPLET                     ║"));
+        println!("{}", logging::decorate("╚══════════════════════════════════════════════════════════════╝\n"));
+    }
+
+    // Benchmark CPU
+    if text {
+        println!("{}", logging::decorate("📊 CPU Mining (multi-threaded)\n"));
+        println!("Threads: {}\n", num_cpus::get());
     }
-    
+
+    let cpu_miner = miner::CpuMiner::new(num_cpus::get());
+    bench_backend(&cpu_miner, reporter.as_ref(), None, Duration::from_millis(args.warmup_ms));
+
     // Benchmark CUDA
     #[cfg(feature = "cuda")]
     {
-        println!("\n📊 CUDA Mining\n");
+        if text {
+            println!("{}", logging::decorate("\n📊 CUDA Mining\n"));
+        }
 
         match cuda_miner::CudaMiner::new(0) {
             Ok(cuda_miner) => {
-                for (name, diff) in &difficulties {
-                    let target = u128::MAX / diff;
-
-                    print!("  {} (diff: {})... ", name, diff);
-
-                    let start = Instant::now();
-                    match cuda_miner.mine(&challenge, &miner_pubkey, block_number, target, u128::MAX) {
-                        Some(nonce) => {
-                            let elapsed = start.elapsed();
-                            let hashrate = (nonce as f64) / elapsed.as_secs_f64();
-                            println!("✓ {:?} ({:.2} MH/s)", elapsed, hashrate / 1_000_000.0);
-                        }
-                        None => {
-                            println!("✗ Not found");
-                        }
+                let power = power::PowerSource::detect(args.gpu_watts, 0);
+                bench_backend(&cuda_miner, reporter.as_ref(), power.as_ref(), Duration::from_millis(args.warmup_ms));
+
+                // Compromis latence / débit du découpage des grilles
+                for sub_launches in CUDA_SUB_LAUNCHES {
+                    if text {
+                        println!("{}", logging::decorate(&format!("\n📊 CUDA Mining, {} sub-launches\n", sub_launches)));
+                    }
+                    let split = cuda_miner::CudaMiner::new(0).map(|m| m.with_sub_launches(sub_launches));
+                    match split {
+                        Ok(miner) => bench_backend(&miner, reporter.as_ref(), power.as_ref(), Duration::ZERO),
+                        Err(e) => reporter.on_error("CUDA", &format!("CUDA not available: {}", e)),
                     }
                 }
             }
-            Err(e) => {
-                println!("⚠️  CUDA not available: {}", e);
-            }
+            Err(e) => reporter.on_error("CUDA", &format!("CUDA not available: {}", e)),
         }
     }
-    
+
     #[cfg(not(feature = "cuda"))]
     {
-        println!("\n⚠️  CUDA not compiled (use --features cuda)");
+        if text {
+            println!("{}", logging::decorate("\n⚠️  CUDA not compiled (use --features cuda)"));
+        }
+    }
+
+    // Benchmark OpenCL
+    #[cfg(feature = "opencl")]
+    {
+        if text {
+            println!("{}", logging::decorate("\n📊 OpenCL Mining\n"));
+        }
+
+        match opencl_miner::OpenClMiner::new(0) {
+            Ok(opencl_miner) => {
+                let power = power::PowerSource::detect(args.gpu_watts, 0);
+                bench_backend(&opencl_miner, reporter.as_ref(), power.as_ref(), Duration::from_millis(args.warmup_ms))
+            }
+            Err(e) => reporter.on_error("OpenCL", &format!("OpenCL not available: {}", e)),
+        }
+    }
+
+    #[cfg(not(feature = "opencl"))]
+    {
+        if text {
+            println!("{}", logging::decorate("\n⚠️  OpenCL not compiled (use --features opencl)"));
+        }
+    }
+
+    if text {
+        println!("{}", logging::decorate("\n✅ Benchmark terminé!\n"));
+    }
+}
+
+/// Mine chaque difficulté de référence avec `miner` et rapporte les résultats
+fn bench_backend(miner: &dyn MinerBackend, reporter: &dyn Reporter, power: Option<&power::PowerSource>, warmup: Duration) {
+    let backend = report::BackendInfo::of(miner);
+
+    // Warm-up hors chrono: le hashrate « à froid » est rapporté séparément
+    if !warmup.is_zero() {
+        let (hashes, elapsed) = miner::warm_up(miner, warmup);
+        report::report_warmup(reporter, &backend, hashes, elapsed);
+    }
+
+    for (label, difficulty) in DIFFICULTIES {
+        let job = BenchJob {
+            label: Some(label.to_string()),
+            challenge: [0u8; 32],
+            miner_pubkey: [1u8; 32], // Dummy miner pubkey for benchmark
+            block_number: 0,         // Numéro de bloc fictif pour le benchmark
+            difficulty,
+            target: None,
+            hash_algo: pow::HashAlgo::Sha256,
+            compare_mode: pow::CompareMode::Low128,
+        };
+
+        let control = MiningControl::new();
+        let start = Instant::now();
+        let nonce = miner.mine_with_control(&job.challenge, &job.miner_pubkey, job.block_number, job.target(), 0..u128::MAX, &control);
+
+        report::report_result(reporter, &backend, &job, nonce, start.elapsed(), &control, power);
     }
-    
-    println!("\n✅ Benchmark terminé!\n");
 }
@@ -0,0 +1,48 @@
+//! Disjoncteur des soumissions: évite de payer des frais en boucle sur des preuves toujours rejetées
+
+/// Compte les échecs de soumission consécutifs et s'ouvre au `threshold`-ième
+pub struct CircuitBreaker {
+    threshold: u32,
+    failures: u32,
+}
+
+impl CircuitBreaker {
+    pub fn new(threshold: u32) -> Self {
+        Self { threshold: threshold.max(1), failures: 0 }
+    }
+
+    /// Referme le disjoncteur (soumission acceptée ou fin du cooldown)
+    pub fn reset(&mut self) {
+        self.failures = 0;
+    }
+
+    /// Enregistre un échec; retourne true si le disjoncteur est ouvert
+    pub fn record_failure(&mut self) -> bool {
+        self.failures += 1;
+        self.failures >= self.threshold
+    }
+
+    pub fn failures(&self) -> u32 {
+        self.failures
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_k_failures_trip_the_breaker() {
+        let mut breaker = CircuitBreaker::new(3);
+        assert!(!breaker.record_failure());
+        assert!(!breaker.record_failure());
+        assert!(breaker.record_failure());
+        assert_eq!(breaker.failures(), 3);
+
+        // Seuls les échecs consécutifs comptent
+        breaker.reset();
+        assert!(!breaker.record_failure());
+        assert!(!breaker.record_failure());
+        assert!(breaker.record_failure());
+    }
+}
@@ -2,21 +2,109 @@
 // CHAIN CLIENT - Interaction avec Solana
 // =============================================================================
 
-use anyhow::{Context, Result, anyhow};
-use log::{info, debug};
-use solana_client::rpc_client::RpcClient;
-use solana_sdk::{
+// Les appels passent des closures retournant ClientError (solana-client), un type volumineux
+#![allow(clippy::result_large_err)]
+
+use anchor_client::solana_client::client_error::{ClientError, ClientErrorKind};
+use anchor_client::solana_client::rpc_request::{RpcError, RpcResponseErrorData};
+use anchor_client::solana_sdk::{
+    account::Account,
+    account_utils::StateMut,
     commitment_config::CommitmentConfig,
-    instruction::{AccountMeta, Instruction},
+    hash::Hash,
+    native_token::LAMPORTS_PER_SOL,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    message::Message,
+    packet::PACKET_DATA_SIZE,
     pubkey::Pubkey,
-    signature::{Keypair, Signer, read_keypair_file},
-    transaction::Transaction,
-    system_program,
+    signature::{Keypair, Signature, read_keypair_file},
+    sysvar,
+    transaction::{Transaction, TransactionError},
 };
-use spl_token_2022;
+use solana_nonce::{state::State as NonceState, versions::Versions as NonceVersions};
+use anchor_lang::system_program;
+use spl_token_2022::extension::StateWithExtensions;
+use spl_token_2022::state::Mint;
+use anyhow::{Context, Result, anyhow};
+use log::{debug, info, warn};
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::fmt;
+use std::future::Future;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use crate::config::{MinerConfig, PdaSeeds};
+use crate::jito::JitoClient;
+use crate::pow;
+use crate::rpc_pool::{self, RpcPool};
+use crate::signer::{self, LocalSigner, TxSigner};
+use crate::tasks;
+
+// Pools: le mineur standard soumet dans le pool normal
+const POOL_NORMAL: u8 = 0;
+const POOL_SEEKER: u8 = 1;
+
+// Tentatives des appels de `ensure_token_account` sur erreur RPC transitoire
+const ATA_ATTEMPTS: u32 = 3;
+const ATA_RETRY_DELAY: Duration = Duration::from_millis(500);
+
+// Taille du compte miner_stats: discriminator (8) + miner (32) + blocks_mined (8)
+// + total_rewards (8) + last_submission_ts (8) + bump (1). Layout supposé (IDL du
+// programme absent du dépôt), à réaligner si le programme change.
+const MINER_STATS_ACCOUNT_SIZE: usize = 8 + 32 + 8 + 8 + 8 + 1;
+
+// Taille du compte PowConfig (IDL pow_protocol 0.1.0): discriminator (8) + champs lus par
+// `parse_pow_config` (218) + bump (1) + block_timestamps (80) + leur index et compte (2)
+// + attestation_authority (32) + pool_id (1)
+#[cfg(test)]
+const POW_CONFIG_V1_LEN: usize = 8 + 218 + 1 + 80 + 2 + 32 + 1;
+
+// Bytes lus par `parse_pow_config`: discriminator (8) + champs jusqu'à is_paused (218). Un
+// compte Anchor peut être plus grand (padding, espace réservé à l'allocation)
+const POW_CONFIG_MIN_LEN: usize = 8 + 218;
+
+// Frais de signature d'une transaction à un signataire (lamports)
+const TX_FEE_LAMPORTS: u64 = 5_000;
+
+// Taille maximale du memo (bytes): la transaction submit_proof doit tenir dans un paquet
+const MAX_MEMO_BYTES: usize = 256;
+
+// Envois d'une même transaction à durable nonce avant abandon
+const DURABLE_NONCE_ATTEMPTS: u32 = 3;
+
+// Lectures du blockhash au plus avec --fresh-blockhash, tant qu'il est déjà expiré
+const FRESH_BLOCKHASH_ATTEMPTS: u32 = 3;
 
-use crate::config::MinerConfig;
+// Confirmation d'une transaction: un statut lu toutes les 500 ms, abandon après 90 s
+// (un blockhash expire après 150 slots, soit 60 à 90 s)
+const CONFIRM_POLL_INTERVAL: Duration = Duration::from_millis(500);
+const CONFIRM_TIMEOUT: Duration = Duration::from_secs(90);
+
+// Discriminator Anchor de submit_proof (sha256("global:submit_proof")[..8])
+const SUBMIT_PROOF_DISCRIMINATOR: [u8; 8] = [54, 241, 46, 84, 4, 212, 46, 94];
+
+// Discriminator Anchor du compte PowConfig (sha256("account:PowConfig")[..8])
+const POW_CONFIG_DISCRIMINATOR: [u8; 8] = [12, 63, 174, 43, 190, 116, 166, 15];
+
+// Comptes de submit_proof dans l'ordre de l'IDL (nom, writable, signer), résolus par
+// `submit_proof_account`. Le programme lit l'horloge via `Clock::get()`: aucun sysvar
+// (Clock, Rent) n'est attendu. Un compte ajouté au programme = une ligne ici.
+const SUBMIT_PROOF_ACCOUNTS: &[(&str, bool, bool)] = &[
+    ("miner", true, true),
+    ("pow_config", true, false),
+    ("other_pool", false, false),
+    ("mint_authority", false, false),
+    ("mint", true, false),
+    ("miner_token_account", true, false),
+    ("miner_stats", true, false),
+    ("fee_collector", true, false),
+    // Optionnel et absent: Anchor attend le program ID, en lecture seule
+    ("attestation", false, false),
+    ("token_program", false, false),
+    ("system_program", false, false),
+];
 
 // =============================================================================
 // STRUCTS
@@ -24,6 +112,7 @@ use crate::config::MinerConfig;
 
 /// État du protocole PoW
 #[derive(Debug, Clone)]
+#[allow(dead_code)] // Parsé en entier, tous les champs ne sont pas encore affichés
 pub struct PowState {
     pub authority: Pubkey,
     pub mint: Pubkey,
@@ -34,182 +123,998 @@ pub struct PowState {
     pub challenge: [u8; 32],
     pub pending_reward_tokens: u64,
     pub fee_sol: u64,
+    pub fees: FeeCounters,
     pub launch_ts: i64,
     pub is_initialized: bool,
     pub is_paused: bool,
 }
 
+/// Compteurs de fees SOL du PowConfig (lamports), cumulés depuis le lancement
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FeeCounters {
+    /// Fees collectées
+    pub total_fees_collected: u64,
+    /// Part envoyée à la team
+    pub total_team_fees: u64,
+    /// Part utilisée pour le buyback
+    pub total_buyback_sol: u64,
+    /// Part ajoutée à la LP
+    pub total_lp_sol: u64,
+}
+
+impl PowState {
+    /// Secondes écoulées depuis le dernier bloc à l'instant `now` (timestamp unix)
+    pub fn seconds_since_last_block(&self, now: i64) -> i64 {
+        // Les horloges du cluster et du dernier bloc peuvent légèrement diverger
+        (now - self.last_block_ts).max(0)
+    }
+
+    /// Résumé lisible, les montants de tokens selon les `decimals` du mint
+    ///
+    /// `{}` tient sur une ligne (logs), `{:#}` aligne un champ par ligne (`status`).
+    pub fn summary(&self, decimals: u8) -> PowStateSummary<'_> {
+        PowStateSummary { state: self, decimals }
+    }
+}
+
+/// Affichage de `PowState::summary`
+pub struct PowStateSummary<'a> {
+    state: &'a PowState,
+    decimals: u8,
+}
+
+impl PowStateSummary<'_> {
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        let state = self.state;
+        let tokens = |raw: u64| format!("{} tokens", format_tokens(raw, self.decimals));
+        vec![
+            ("Challenge", format!("{}…", hex::encode(&state.challenge[..8]))),
+            ("Difficulty", format!("{:.3e}", state.difficulty as f64)),
+            ("Blocks mined", format_thousands(state.blocks_mined)),
+            ("Total supply mined", tokens(state.total_supply_mined)),
+            ("Pending reward", tokens(state.pending_reward_tokens)),
+            ("Fee", format_sol(state.fee_sol)),
+            ("Fees collected", format_sol(state.fees.total_fees_collected)),
+            ("  to team", format_sol(state.fees.total_team_fees)),
+            ("  to buyback", format_sol(state.fees.total_buyback_sol)),
+            ("  to LP", format_sol(state.fees.total_lp_sol)),
+            ("Last block at", format_timestamp(state.last_block_ts)),
+            ("Launched", format_timestamp(state.launch_ts)),
+            ("Paused", state.is_paused.to_string()),
+        ]
+    }
+}
+
+impl fmt::Display for PowStateSummary<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let fields = self.fields();
+        if f.alternate() {
+            for (label, value) in fields {
+                writeln!(f, "{:<22}{}", format!("{}:", label), value)?;
+            }
+            return Ok(());
+        }
+        // Une ligne: les sous-totaux de fees alourdiraient les logs
+        let line: Vec<String> = fields.iter().filter(|(label, _)| !label.starts_with(' ')).map(|(label, value)| format!("{}: {}", label.to_lowercase(), value)).collect();
+        write!(f, "{}", line.join(", "))
+    }
+}
+
+/// PDAs du programme communs à tous les mineurs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProgramPdas {
+    pub pow_config: Pubkey,
+    pub other_pool: Pubkey,
+    pub mint_authority: Pubkey,
+    pub fee_vault: Pubkey,
+}
+
+impl PdaSeeds {
+    pub fn program_pdas(&self, program_id: &Pubkey) -> ProgramPdas {
+        let pda = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, program_id).0;
+        ProgramPdas {
+            pow_config: pda(&[self.pow_config.as_bytes(), &[POOL_NORMAL]]),
+            other_pool: pda(&[self.pow_config.as_bytes(), &[POOL_SEEKER]]),
+            mint_authority: pda(&[self.mint_authority.as_bytes()]),
+            fee_vault: pda(&[self.fee_vault.as_bytes()]),
+        }
+    }
+
+    /// PDA miner_stats de `miner` dans le pool normal
+    pub fn miner_stats(&self, program_id: &Pubkey, miner: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[self.miner_stats.as_bytes(), &[POOL_NORMAL], miner.as_ref()], program_id).0
+    }
+}
+
+/// Signataire d'un mineur et ses comptes dérivés
+///
+/// Le pubkey fait partie du préimage: un nonce n'est valide que pour le signataire
+/// avec lequel il a été miné, et doit être soumis par ce même signataire.
+pub struct MinerAccount {
+    signer: Box<dyn TxSigner>,
+    miner_stats_pda: Pubkey,
+    miner_token_account: Pubkey,
+}
+
+impl MinerAccount {
+    pub fn new(signer: Box<dyn TxSigner>, program_id: &Pubkey, mint: &Pubkey, seeds: &PdaSeeds) -> Self {
+        let miner_stats_pda = seeds.miner_stats(program_id, &signer.pubkey());
+
+        // Token account du miner
+        let miner_token_account = associated_token_address(&signer.pubkey(), mint);
+
+        Self {
+            signer,
+            miner_stats_pda,
+            miner_token_account,
+        }
+    }
+
+    pub fn pubkey(&self) -> Pubkey {
+        self.signer.pubkey()
+    }
+}
+
+/// Accès au protocole utilisé par la boucle de mining: RPC (`ChainClient`)
+/// ou simulé en mémoire (`MockChain`, option `--mock-chain`)
+pub trait Chain {
+    /// Mineurs chargés
+    fn miners(&self) -> &[MinerAccount];
+
+    /// Mineur du prochain round
+    fn next_miner(&self) -> &MinerAccount;
+
+    /// Description de l'endpoint, pour les logs
+    fn endpoint(&self) -> &str;
+
+    /// Mint configuré (`--mint`), dont dérivent les token accounts des mineurs
+    fn mint(&self) -> Pubkey;
+
+    /// Solde SOL d'un mineur (lamports)
+    async fn get_balance(&self, miner: &MinerAccount) -> Result<u64>;
+
+    async fn get_pow_state(&self) -> Result<PowState>;
+
+    /// Solde SOL du fee vault (lamports): fees collectées pas encore distribuées
+    async fn get_fee_vault_balance(&self) -> Result<u64>;
+
+    async fn seconds_since_last_block(&self, state: &PowState) -> Result<i64>;
+
+    /// Challenge qui suivra l'acceptation de `nonce` (`--speculative`), si le programme le
+    /// dérive de façon déterministe de la preuve; None si le mineur ne peut pas le prévoir
+    fn predict_challenge(&self, _state: &PowState, _nonce: u128) -> Option<[u8; 32]> {
+        None
+    }
+
+    /// Soumet un nonce; retourne la signature de la transaction
+    async fn submit_proof(&self, miner: &MinerAccount, nonce: u128) -> Result<String>;
+
+    /// Preuves qu'une transaction peut contenir (`--batch-submit`), 1 sans soumission groupée
+    fn max_proofs_per_transaction(&self) -> usize {
+        1
+    }
+
+    /// Soumet plusieurs nonces en une transaction, chacun pour le challenge laissé par le
+    /// précédent; tout ou rien
+    async fn submit_proofs(&self, miner: &MinerAccount, nonces: &[u128]) -> Result<String> {
+        match nonces {
+            [nonce] => self.submit_proof(miner, *nonce).await,
+            _ => Err(anyhow!("{} does not accept {} proofs in one transaction", self.endpoint(), nonces.len())),
+        }
+    }
+
+    async fn ensure_token_account(&self) -> Result<()>;
+
+    /// Rent du PDA miner_stats si le premier submit_proof doit le créer, 0 s'il existe
+    async fn miner_stats_rent(&self, miner: &MinerAccount) -> Result<u64>;
+
+    /// Solde de tokens d'un mineur (unités de base)
+    async fn get_token_balance(&self, miner: &MinerAccount) -> Result<u64>;
+
+    async fn mint_decimals(&self) -> Result<u8>;
+}
+
 /// Client pour interagir avec le protocole
 pub struct ChainClient {
-    rpc: RpcClient,
-    keypair: Keypair,
+    /// Lectures d'état (polling)
+    rpc: Arc<RpcPool>,
+    /// Envoi des transactions (`--submit-rpc`), sinon `rpc`
+    submit_rpc: Option<Arc<RpcPool>>,
+    miners: Vec<MinerAccount>,
+    next_miner: AtomicUsize,
     program_id: Pubkey,
     mint: Pubkey,
     pow_config_pda: Pubkey,
-    miner_stats_pda: Pubkey,
+    other_pool_pda: Pubkey,
+    mint_authority_pda: Pubkey,
     fee_vault_pda: Pubkey,
-    miner_token_account: Pubkey,
+    /// Décimales du mint, lues au premier appel de `mint_decimals`
+    mint_decimals: OnceLock<u8>,
+    /// Memo ajouté en tête des transactions submit_proof (`--memo`)
+    memo: Option<String>,
+    /// Nonce account remplaçant le blockhash récent (`--durable-nonce`)
+    durable_nonce: Option<Pubkey>,
+    /// Envoi sans attendre la confirmation (`--no-confirm`)
+    no_confirm: bool,
+    /// Blockhash vérifié avant de signer (`--fresh-blockhash`)
+    fresh_blockhash: bool,
+    /// Soumissions `--no-confirm` confirmées en tâche de fond
+    accepted: Arc<AtomicU64>,
+    /// Envoi en bundle avec tip (`--jito-block-engine`)
+    jito: Option<JitoClient>,
+    /// Preuves par transaction sous la taille d'un paquet, calculé au premier appel
+    max_proofs: OnceLock<usize>,
 }
 
 impl ChainClient {
     /// Créer un nouveau client
     pub async fn new(config: &MinerConfig) -> Result<Self> {
-        // Client RPC
-        let rpc = RpcClient::new_with_commitment(
-            &config.rpc_url,
-            CommitmentConfig::confirmed(),
-        );
+        // Clients RPC (bascule sur l'URL suivante en cas d'erreurs répétées)
+        let pool = |urls: &[String]| -> Result<RpcPool> {
+            let rpc = RpcPool::new(urls, CommitmentConfig::confirmed())?;
+            Ok(match config.rpc_rate_limit {
+                Some(per_second) => rpc.with_rate_limit(per_second),
+                None => rpc,
+            })
+        };
+        let rpc = pool(&config.rpc_urls)?;
+        let submit_rpc = match config.submit_rpc_urls.as_slice() {
+            [] => None,
+            urls => Some(pool(urls)?),
+        };
 
-        // Charger le keypair
-        let keypair = read_keypair_file(&config.wallet_path)
-            .map_err(|e| anyhow!("Failed to load wallet: {}", e))?;
+        // Charger les keypairs
+        let signers: Vec<_> = load_keypairs(&config.wallet_paths)?
+            .into_iter()
+            .map(|keypair| Box::new(LocalSigner::new(keypair)) as Box<dyn TxSigner>)
+            .collect();
+        if signers.is_empty() {
+            return Err(anyhow!("No keypair loaded"));
+        }
 
         let program_id = Pubkey::from_str(&config.program_id)
             .context("Invalid program ID")?;
-        
+
         let mint = Pubkey::from_str(&config.mint)
             .context("Invalid mint address")?;
 
-        // Dériver les PDAs
-        let (pow_config_pda, _) = Pubkey::find_program_address(
-            &[b"pow_config"],
-            &program_id,
-        );
+        let mut client = Self::with_rpc(rpc, signers, program_id, mint, &config.seeds)?;
+        info!("   PDA pow_config: {}", client.pow_config_pda);
+        info!("   PDA fee_vault: {}", client.fee_vault_pda);
+        info!("   PDA mint_authority: {}", client.mint_authority_pda);
+        for miner in &client.miners {
+            info!("   PDA miner_stats: {} ({})", miner.miner_stats_pda, miner.pubkey());
+        }
+        if let Some(submit_rpc) = submit_rpc {
+            client = client.with_submit_rpc(submit_rpc);
+        }
+        if let Some(memo) = &config.memo {
+            client = client.with_memo(memo);
+        }
+        if let Some(account) = &config.durable_nonce {
+            client.durable_nonce = Some(Pubkey::from_str(account).context("Invalid durable nonce account")?);
+        }
+        client.no_confirm = config.no_confirm;
+        client.fresh_blockhash = config.fresh_blockhash;
+        if let Some(block_engine) = &config.jito_block_engine {
+            let jito = JitoClient::new(block_engine, config.jito_tip);
+            info!("   Jito bundles: {} (tip: {} lamports)", jito.url(), config.jito_tip);
+            client = client.with_jito(jito);
+        }
+        Ok(client)
+    }
 
-        let (miner_stats_pda, _) = Pubkey::find_program_address(
-            &[b"miner_stats", keypair.pubkey().as_ref()],
-            &program_id,
-        );
+    /// Ajoute `memo` à chaque soumission, tronqué à `MAX_MEMO_BYTES`
+    fn with_memo(mut self, memo: &str) -> Self {
+        let truncated = truncate_memo(memo);
+        if truncated.len() < memo.len() {
+            warn!("Memo truncated from {} to {} bytes", memo.len(), truncated.len());
+        }
+        self.memo = Some(truncated.to_string());
+        self
+    }
 
-        let (fee_vault_pda, _) = Pubkey::find_program_address(
-            &[b"fee_vault"],
-            &program_id,
-        );
+    /// Envoie les transactions par `submit_rpc` plutôt que par le pool de polling
+    fn with_submit_rpc(mut self, submit_rpc: RpcPool) -> Self {
+        self.submit_rpc = Some(Arc::new(submit_rpc));
+        self
+    }
 
-        // Token account du miner
-        let miner_token_account = spl_associated_token_account::get_associated_token_address_with_program_id(
-            &keypair.pubkey(),
-            &mint,
-            &spl_token_2022::id(),
-        );
+    /// Envoie les preuves en bundle Jito, tip compris, plutôt que par le RPC
+    fn with_jito(mut self, jito: JitoClient) -> Self {
+        self.jito = Some(jito);
+        self
+    }
+
+    /// Pool utilisé pour le blockhash et l'envoi des transactions
+    fn submit_pool(&self) -> &Arc<RpcPool> {
+        self.submit_rpc.as_ref().unwrap_or(&self.rpc)
+    }
+
+    /// Client sans keypair ni mint, limité à la lecture de l'état du protocole (`status`)
+    pub fn read_only(rpc_urls: &[String], program_id: &str, seeds: &PdaSeeds) -> Result<Self> {
+        let rpc = RpcPool::new(rpc_urls, CommitmentConfig::confirmed())?;
+        let program_id = Pubkey::from_str(program_id).context("Invalid program ID")?;
+
+        Self::with_rpc(rpc, Vec::new(), program_id, Pubkey::default(), seeds)
+    }
+
+    fn with_rpc(rpc: RpcPool, signers: Vec<Box<dyn TxSigner>>, program_id: Pubkey, mint: Pubkey, seeds: &PdaSeeds) -> Result<Self> {
+        // Dériver les PDAs
+        let pdas = seeds.program_pdas(&program_id);
+
+        let miners = signers
+            .into_iter()
+            .map(|signer| MinerAccount::new(signer, &program_id, &mint, seeds))
+            .collect();
 
         Ok(Self {
-            rpc,
-            keypair,
+            rpc: Arc::new(rpc),
+            submit_rpc: None,
+            miners,
+            next_miner: AtomicUsize::new(0),
             program_id,
             mint,
-            pow_config_pda,
-            miner_stats_pda,
-            fee_vault_pda,
-            miner_token_account,
+            pow_config_pda: pdas.pow_config,
+            other_pool_pda: pdas.other_pool,
+            mint_authority_pda: pdas.mint_authority,
+            fee_vault_pda: pdas.fee_vault,
+            mint_decimals: OnceLock::new(),
+            memo: None,
+            durable_nonce: None,
+            no_confirm: false,
+            fresh_blockhash: false,
+            accepted: Arc::new(AtomicU64::new(0)),
+            jito: None,
+            max_proofs: OnceLock::new(),
         })
     }
 
-    /// Récupérer le solde du miner
-    pub async fn get_balance(&self) -> Result<u64> {
-        let balance = self.rpc.get_balance(&self.keypair.pubkey())?;
+    /// Timestamp unix du cluster, lu dans le sysvar Clock
+    pub async fn cluster_unix_timestamp(&self) -> Result<i64> {
+        let account = self.rpc.call(|rpc| rpc.get_account(&sysvar::clock::ID)).await
+            .context("Failed to fetch Clock sysvar")?;
+
+        parse_clock_unix_timestamp(&account.data)
+    }
+
+    /// Instruction submit_proof signée par `miner`
+    fn submit_proof_instruction(&self, miner: &MinerAccount, nonce: u128) -> Instruction {
+        // Discriminator + nonce (u128, little-endian)
+        let mut data = Vec::with_capacity(8 + pow::NONCE_BYTES);
+        data.extend_from_slice(&SUBMIT_PROOF_DISCRIMINATOR);
+        data.extend_from_slice(&nonce.to_le_bytes());
+
+        let accounts = SUBMIT_PROOF_ACCOUNTS
+            .iter()
+            .map(|&(name, writable, signer)| {
+                let pubkey = self.submit_proof_account(miner, name);
+                if writable {
+                    AccountMeta::new(pubkey, signer)
+                } else {
+                    AccountMeta::new_readonly(pubkey, signer)
+                }
+            })
+            .collect();
+
+        Instruction { program_id: self.program_id, accounts, data }
+    }
+
+    /// Adresse du compte `name` de `SUBMIT_PROOF_ACCOUNTS` pour `miner`
+    fn submit_proof_account(&self, miner: &MinerAccount, name: &str) -> Pubkey {
+        match name {
+            "miner" => miner.pubkey(),
+            "pow_config" => self.pow_config_pda,
+            "other_pool" => self.other_pool_pda,
+            "mint_authority" => self.mint_authority_pda,
+            "mint" => self.mint,
+            "miner_token_account" => miner.miner_token_account,
+            "miner_stats" => miner.miner_stats_pda,
+            "fee_collector" => self.fee_vault_pda,
+            "attestation" => self.program_id,
+            "token_program" => spl_token_2022::id(),
+            "system_program" => system_program::ID,
+            "clock" => sysvar::clock::ID,
+            "rent" => sysvar::rent::ID,
+            _ => unreachable!("unknown submit_proof account: {}", name),
+        }
+    }
+
+    /// Instructions de la transaction de soumission: memo éventuel, un submit_proof par nonce,
+    /// puis le tip en mode bundle Jito
+    fn submit_proof_instructions(&self, miner: &MinerAccount, nonces: &[u128]) -> Vec<Instruction> {
+        let memo = self.memo.iter().map(|memo| spl_memo::build_memo(memo.as_bytes(), &[]));
+        let proofs = nonces.iter().map(|&nonce| self.submit_proof_instruction(miner, nonce));
+        let tip = self.jito.iter().map(|jito| jito.tip_instruction(&miner.pubkey()));
+        memo.chain(proofs).chain(tip).collect()
+    }
+
+    /// Taille sérialisée de la transaction de soumission de `count` preuves par `miner`
+    fn submit_transaction_size(&self, miner: &MinerAccount, count: usize) -> usize {
+        let advance = self.durable_nonce.map(|account| solana_system_interface::instruction::advance_nonce_account(&account, &miner.pubkey()));
+        let instructions: Vec<_> = advance.into_iter().chain(self.submit_proof_instructions(miner, &vec![u128::MAX; count])).collect();
+        let message = Message::new(&instructions, Some(&miner.pubkey()));
+        // Nombre de signatures (short_vec d'un octet), signatures, puis le message
+        1 + 64 * message.header.num_required_signatures as usize + message.serialize().len()
+    }
+
+    /// Transaction submit_proof à durable nonce: avance le nonce en tête, `nonce_hash` en
+    /// guise de blockhash
+    ///
+    /// Déterministe: tant que le nonce n'a pas avancé, la reconstruire redonne la même
+    /// signature, et la transaction ne peut atterrir qu'une fois.
+    fn durable_nonce_transaction(&self, miner: &MinerAccount, nonces: &[u128], nonce_account: &Pubkey, nonce_hash: Hash) -> Result<Transaction> {
+        let advance = solana_system_interface::instruction::advance_nonce_account(nonce_account, &miner.pubkey());
+        let instructions: Vec<_> = std::iter::once(advance).chain(self.submit_proof_instructions(miner, nonces)).collect();
+        signer::sign_transaction(&instructions, miner.signer.as_ref(), nonce_hash)
+    }
+
+    /// Valeur courante du nonce account, dont `miner` doit être l'autorité
+    async fn durable_nonce_hash(&self, miner: &MinerAccount, nonce_account: Pubkey) -> Result<Hash> {
+        let account = self.submit_pool().submit(move |rpc| rpc.get_account(&nonce_account)).await
+            .context("Failed to fetch durable nonce account")?;
+
+        let (hash, authority) = parse_durable_nonce(&account)?;
+        if authority != miner.pubkey() {
+            return Err(anyhow!("Durable nonce authority is {}, not the miner {}", authority, miner.pubkey()));
+        }
+        Ok(hash)
+    }
+
+    /// Soumission idempotente par durable nonce
+    ///
+    /// Après une erreur transitoire (timeout), le nonce est relu: inchangé, la même
+    /// transaction est renvoyée; avancé, la tentative précédente a été traitée et son
+    /// statut fait foi.
+    async fn submit_with_durable_nonce(&self, miner: &MinerAccount, nonces: &[u128], nonce_account: Pubkey) -> Result<String> {
+        let nonce_hash = self.durable_nonce_hash(miner, nonce_account).await?;
+        let transaction = self.durable_nonce_transaction(miner, nonces, &nonce_account, nonce_hash)?;
+        let signature = transaction.signatures[0];
+
+        let mut attempt = 1;
+        loop {
+            let transaction = transaction.clone();
+            match self.send_and_confirm(transaction).await {
+                Ok(signature) => return Ok(signature.to_string()),
+                Err(e) if attempt < DURABLE_NONCE_ATTEMPTS && rpc_pool::is_transient(&e) => {
+                    if self.durable_nonce_hash(miner, nonce_account).await? != nonce_hash {
+                        return self.landed_signature(signature).await;
+                    }
+                    warn!("Submission {} not confirmed (attempt {}/{}), resending: {:#}", signature, attempt, DURABLE_NONCE_ATTEMPTS, e);
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Issue d'une transaction dont le durable nonce a avancé
+    async fn landed_signature(&self, signature: Signature) -> Result<String> {
+        match self.submit_pool().submit(move |rpc| rpc.get_signature_status(&signature)).await? {
+            Some(Ok(())) => {
+                info!("Submission {} landed despite the send error", signature);
+                Ok(signature.to_string())
+            }
+            Some(Err(e)) => Err(anyhow!("Submission {} failed on-chain: {}", signature, e)),
+            None => Err(anyhow!("Durable nonce was advanced by another transaction, {} will not land", signature)),
+        }
+    }
+
+    /// Crée le token account (ATA) du mineur, payé et signé par lui
+    async fn create_token_account(&self, miner: &MinerAccount) -> Result<()> {
+        let instruction = spl_associated_token_account::instruction::create_associated_token_account(
+            &miner.pubkey(),
+            &miner.pubkey(),
+            &self.mint,
+            &spl_token_2022::id(),
+        );
+
+        let recent_blockhash = self.submit_pool().call(|rpc| rpc.get_latest_blockhash()).await?;
+
+        let transaction = signer::sign_transaction(&[instruction], miner.signer.as_ref(), recent_blockhash)?;
+
+        self.send_and_confirm(transaction).await?;
+        Ok(())
+    }
+
+    /// Envoie `transaction` puis attend sa confirmation (`confirm_signature`)
+    async fn send_and_confirm(&self, transaction: Transaction) -> Result<Signature> {
+        let signature = self.submit_pool().submit(move |rpc| rpc.send_transaction(&transaction)).await?;
+        confirm_signature(self.submit_pool(), signature).await?;
+        Ok(signature)
+    }
+}
+
+/// Réessaie `f` (jusqu'à `ATA_ATTEMPTS` fois) tant que l'erreur est transitoire
+async fn retry_transient<T, Fut: Future<Output = Result<T>>>(mut f: impl FnMut() -> Fut) -> Result<T> {
+    let mut attempt = 1;
+    loop {
+        match f().await {
+            Err(e) if attempt < ATA_ATTEMPTS && rpc_pool::is_transient(&e) => {
+                warn!("Transient RPC error (attempt {}/{}): {:#}", attempt, ATA_ATTEMPTS, e);
+                tokio::time::sleep(ATA_RETRY_DELAY).await;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Envoi sans confirmation: retourne dès que `send` a remis la transaction au RPC
+///
+/// `confirm` s'exécute en tâche de fond et incrémente `accepted` si la transaction aboutit;
+/// un échec est seulement journalisé, la preuve est perdue.
+async fn send_unconfirmed<C>(
+    send: impl Future<Output = Result<Signature>>,
+    confirm: impl FnOnce(Signature) -> C,
+    accepted: Arc<AtomicU64>,
+) -> Result<String>
+where
+    C: Future<Output = Result<()>> + Send + 'static,
+{
+    let signature = send.await?;
+    let confirmation = confirm(signature);
+    tasks::spawn("confirm-submission", async move {
+        match confirmation.await {
+            Ok(()) => {
+                let total = accepted.fetch_add(1, Ordering::Relaxed) + 1;
+                info!("Submission {} confirmed ({} accepted)", signature, total);
+            }
+            Err(e) => warn!("Submission {} not confirmed: {:#}", signature, e),
+        }
+    });
+    Ok(signature.to_string())
+}
+
+/// Attend la confirmation de `signature`; erreur si elle a échoué ou n'a pas abouti
+///
+/// Un `getSignatureStatuses` par lecture, via `RpcPool::call`: le polling respecte
+/// `--rpc-rate-limit`. Les erreurs restent des `ClientError` (transaction rejetée ou,
+/// après `CONFIRM_TIMEOUT`, erreur transitoire) pour `is_transient` et `is_already_exists`.
+async fn confirm_signature(rpc: &RpcPool, signature: Signature) -> Result<()> {
+    let deadline = tokio::time::Instant::now() + CONFIRM_TIMEOUT;
+    loop {
+        match rpc.call(move |rpc| rpc.get_signature_status(&signature)).await? {
+            Some(Ok(())) => return Ok(()),
+            Some(Err(e)) => return Err(ClientError::from(e).into()),
+            None if tokio::time::Instant::now() >= deadline => {
+                let message = format!("unable to confirm transaction {} within {:?}", signature, CONFIRM_TIMEOUT);
+                return Err(ClientError::from(ClientErrorKind::Custom(message)).into());
+            }
+            None => tokio::time::sleep(CONFIRM_POLL_INTERVAL).await,
+        }
+    }
+}
+
+/// Blockhash de `fetch`, relu tant que `is_valid` le déclare déjà expiré
+///
+/// Au plus `FRESH_BLOCKHASH_ATTEMPTS` lectures: la dernière est gardée sans vérification.
+fn fresh_blockhash<E>(mut fetch: impl FnMut() -> Result<Hash, E>, mut is_valid: impl FnMut(&Hash) -> Result<bool, E>) -> Result<Hash, E> {
+    let mut blockhash = fetch()?;
+    for attempt in 1..FRESH_BLOCKHASH_ATTEMPTS {
+        if is_valid(&blockhash)? {
+            break;
+        }
+        debug!("Blockhash {} already expired (read {}/{}), fetching a new one", blockhash, attempt, FRESH_BLOCKHASH_ATTEMPTS);
+        blockhash = fetch()?;
+    }
+    Ok(blockhash)
+}
+
+/// Vrai si la création a échoué parce que le compte existe déjà
+///
+/// Selon la version du programme ATA: `AccountAlreadyInUse` du system program
+/// (`Custom(0)`) ou `IllegalOwner` (compte déjà assigné au token program).
+fn is_already_exists(e: &anyhow::Error) -> bool {
+    let Some(TransactionError::InstructionError(_, error)) = e.downcast_ref::<ClientError>().and_then(|e| e.get_transaction_error()) else {
+        return false;
+    };
+    matches!(error, InstructionError::Custom(0) | InstructionError::IllegalOwner | InstructionError::AccountAlreadyInitialized)
+}
+
+/// Préfixe de `memo` d'au plus `MAX_MEMO_BYTES`, coupé sur une frontière de caractère
+/// (le programme Memo rejette l'UTF-8 invalide)
+fn truncate_memo(memo: &str) -> &str {
+    let mut end = memo.len().min(MAX_MEMO_BYTES);
+    while !memo.is_char_boundary(end) {
+        end -= 1;
+    }
+    &memo[..end]
+}
+
+/// Logs du programme renvoyés par la simulation (preflight) d'une transaction rejetée
+pub fn program_logs(e: &anyhow::Error) -> Vec<String> {
+    let Some(ClientErrorKind::RpcError(RpcError::RpcResponseError { data: RpcResponseErrorData::SendTransactionPreflightFailure(result), .. })) =
+        e.downcast_ref::<ClientError>().map(|e| e.kind())
+    else {
+        return Vec::new();
+    };
+    result.logs.clone().unwrap_or_default()
+}
+
+impl Chain for ChainClient {
+    /// Tous les mineurs chargés
+    fn miners(&self) -> &[MinerAccount] {
+        &self.miners
+    }
+
+    /// Mineur du prochain round (round-robin sur les keypairs chargés)
+    fn next_miner(&self) -> &MinerAccount {
+        let index = self.next_miner.fetch_add(1, Ordering::Relaxed);
+        &self.miners[index % self.miners.len()]
+    }
+
+    /// URL du RPC actuellement utilisé
+    fn endpoint(&self) -> &str {
+        self.rpc.active_url()
+    }
+
+    fn mint(&self) -> Pubkey {
+        self.mint
+    }
+
+    /// Récupérer le solde d'un miner
+    async fn get_balance(&self, miner: &MinerAccount) -> Result<u64> {
+        let pubkey = miner.pubkey();
+        let balance = self.rpc.call(move |rpc| rpc.get_balance(&pubkey)).await?;
         Ok(balance)
     }
 
     /// Récupérer l'état du protocole
-    pub async fn get_pow_state(&self) -> Result<PowState> {
-        let account = self.rpc.get_account(&self.pow_config_pda)
+    async fn get_pow_state(&self) -> Result<PowState> {
+        let pow_config_pda = self.pow_config_pda;
+        let account = self.rpc.call(move |rpc| rpc.get_account(&pow_config_pda)).await
             .context("Failed to fetch PoW config account")?;
 
         parse_pow_config(&account.data)
     }
 
-    /// Soumettre une preuve de travail
-    pub async fn submit_proof(&self, nonce: u64) -> Result<String> {
-        // Construire l'instruction
-        // Discriminator pour "submit_proof" dans Anchor
-        // En production, utiliser le client IDL généré
-        let mut data = Vec::with_capacity(16);
-        
-        // Discriminator (à adapter selon votre programme)
-        // C'est le hash SHA256 des 8 premiers bytes de "global:submit_proof"
-        data.extend_from_slice(&[0x4e, 0x41, 0x4a, 0x8d, 0x2c, 0x1d, 0x3e, 0x5f]); // Placeholder
-        
-        // Nonce (u64, little-endian)
-        data.extend_from_slice(&nonce.to_le_bytes());
+    /// Récupérer le solde du fee vault
+    async fn get_fee_vault_balance(&self) -> Result<u64> {
+        let fee_vault_pda = self.fee_vault_pda;
+        self.rpc.call(move |rpc| rpc.get_balance(&fee_vault_pda)).await
+            .context("Failed to fetch fee vault balance")
+    }
 
-        let instruction = Instruction {
-            program_id: self.program_id,
-            accounts: vec![
-                AccountMeta::new(self.keypair.pubkey(), true),      // miner (signer, writable)
-                AccountMeta::new(self.pow_config_pda, false),        // pow_config
-                AccountMeta::new(self.mint, false),                  // mint
-                AccountMeta::new(self.miner_token_account, false),   // miner_token_account
-                AccountMeta::new(self.miner_stats_pda, false),       // miner_stats
-                AccountMeta::new(self.fee_vault_pda, false),         // fee_collector
-                AccountMeta::new_readonly(spl_token_2022::id(), false), // token_program
-                AccountMeta::new_readonly(system_program::id(), false), // system_program
-            ],
-            data,
-        };
+    /// Secondes écoulées depuis le dernier bloc miné, selon l'horloge du cluster
+    async fn seconds_since_last_block(&self, state: &PowState) -> Result<i64> {
+        let now = self.cluster_unix_timestamp().await?;
+        Ok(state.seconds_since_last_block(now))
+    }
+
+    /// Soumettre une preuve de travail, signée par le mineur qui l'a trouvée
+    async fn submit_proof(&self, miner: &MinerAccount, nonce: u128) -> Result<String> {
+        self.submit_proofs(miner, &[nonce]).await
+    }
+
+    /// Autant de preuves que la taille d'un paquet le permet (memo, tip et durable nonce compris)
+    fn max_proofs_per_transaction(&self) -> usize {
+        *self.max_proofs.get_or_init(|| {
+            let miner = &self.miners[0];
+            (1..).take_while(|&count| self.submit_transaction_size(miner, count) <= PACKET_DATA_SIZE).last().unwrap_or(1)
+        })
+    }
+
+    /// Soumettre plusieurs preuves en une transaction (`--batch-submit`)
+    async fn submit_proofs(&self, miner: &MinerAccount, nonces: &[u128]) -> Result<String> {
+        if nonces.len() > self.max_proofs_per_transaction() {
+            return Err(anyhow!("{} proofs do not fit in one transaction (max {})", nonces.len(), self.max_proofs_per_transaction()));
+        }
+        if let Some(nonce_account) = self.durable_nonce {
+            return self.submit_with_durable_nonce(miner, nonces, nonce_account).await;
+        }
+
+        let instructions = self.submit_proof_instructions(miner, nonces);
 
         // Créer et envoyer la transaction
-        let recent_blockhash = self.rpc.get_latest_blockhash()?;
-        
-        let transaction = Transaction::new_signed_with_payer(
-            &[instruction],
-            Some(&self.keypair.pubkey()),
-            &[&self.keypair],
-            recent_blockhash,
-        );
+        // Prioritaire sur les lectures d'état face au limiteur de débit; blockhash lu à la
+        // commitment des soumissions
+        let check = self.fresh_blockhash;
+        let recent_blockhash = self
+            .submit_pool()
+            .submit(move |rpc| {
+                let commitment = rpc.commitment();
+                fresh_blockhash(
+                    || rpc.get_latest_blockhash_with_commitment(commitment).map(|(hash, _)| hash),
+                    |hash| if check { rpc.is_blockhash_valid(hash, commitment) } else { Ok(true) },
+                )
+            })
+            .await?;
+
+        let transaction = signer::sign_transaction(&instructions, miner.signer.as_ref(), recent_blockhash)?;
+
+        let pool = self.submit_pool().clone();
+        let confirm = move |signature| async move { confirm_signature(&pool, signature).await };
+
+        // Bundle Jito: le block engine ne suit pas la transaction, confirmée par le RPC
+        if let Some(jito) = &self.jito {
+            let signature = transaction.signatures[0];
+            let send = async move {
+                let bundle = jito.send_bundle(&[transaction]).await?;
+                debug!("Bundle {} sent with transaction {}", bundle, signature);
+                Ok(signature)
+            };
+            if self.no_confirm {
+                return send_unconfirmed(send, confirm, self.accepted.clone()).await;
+            }
+            let signature = send.await?;
+            confirm(signature).await.with_context(|| format!("Bundled submission {}", signature))?;
+            return Ok(signature.to_string());
+        }
+
+        if self.no_confirm {
+            let send = self.submit_pool().submit(move |rpc| rpc.send_transaction(&transaction));
+            return send_unconfirmed(send, confirm, self.accepted.clone()).await;
+        }
+
+        let signature = self.send_and_confirm(transaction).await?;
 
-        let signature = self.rpc.send_and_confirm_transaction(&transaction)?;
-        
         Ok(signature.to_string())
     }
 
-    /// Vérifier si le token account existe, sinon le créer
-    pub async fn ensure_token_account(&self) -> Result<()> {
-        let account = self.rpc.get_account(&self.miner_token_account);
-        
-        if account.is_err() {
-            info!("Creating token account...");
-            
-            let instruction = spl_associated_token_account::instruction::create_associated_token_account(
-                &self.keypair.pubkey(),
-                &self.keypair.pubkey(),
-                &self.mint,
-                &spl_token_2022::id(),
-            );
-
-            let recent_blockhash = self.rpc.get_latest_blockhash()?;
-            
-            let transaction = Transaction::new_signed_with_payer(
-                &[instruction],
-                Some(&self.keypair.pubkey()),
-                &[&self.keypair],
-                recent_blockhash,
-            );
-
-            self.rpc.send_and_confirm_transaction(&transaction)?;
-            info!("Token account created!");
+    /// Vérifier que le token account de chaque mineur existe, sinon le créer
+    ///
+    /// Idempotent: un compte créé entre-temps par un autre processus compte comme un succès.
+    async fn ensure_token_account(&self) -> Result<()> {
+        for miner in &self.miners {
+            let token_account = miner.miner_token_account;
+            let exists = retry_transient(|| async {
+                self.rpc
+                    .call(move |rpc| rpc.get_account_with_commitment(&token_account, rpc.commitment()))
+                    .await
+                    .map(|response| response.value.is_some())
+            })
+            .await?;
+
+            if exists {
+                debug!("Token account {} already exists", miner.miner_token_account);
+                continue;
+            }
+
+            info!("Creating token account for {}...", miner.pubkey());
+            match retry_transient(|| self.create_token_account(miner)).await {
+                Ok(()) => info!("Token account created!"),
+                Err(e) if is_already_exists(&e) => info!("Token account already exists (created concurrently)"),
+                Err(e) => return Err(e),
+            }
         }
 
         Ok(())
     }
 
-    /// Récupérer le solde de tokens
-    pub async fn get_token_balance(&self) -> Result<u64> {
-        let account = self.rpc.get_token_account_balance(&self.miner_token_account)?;
+    /// Rent-exempt minimum du compte miner_stats, s'il n'existe pas encore
+    async fn miner_stats_rent(&self, miner: &MinerAccount) -> Result<u64> {
+        let miner_stats_pda = miner.miner_stats_pda;
+        let exists = self.rpc
+            .call(move |rpc| rpc.get_account_with_commitment(&miner_stats_pda, rpc.commitment()))
+            .await?
+            .value
+            .is_some();
+        if exists {
+            return Ok(0);
+        }
+
+        self.rpc.call(|rpc| rpc.get_minimum_balance_for_rent_exemption(MINER_STATS_ACCOUNT_SIZE)).await
+    }
+
+    /// Récupérer le solde de tokens d'un miner
+    async fn get_token_balance(&self, miner: &MinerAccount) -> Result<u64> {
+        let token_account = miner.miner_token_account;
+        let account = self.rpc.call(move |rpc| rpc.get_token_account_balance(&token_account)).await?;
         let amount = account.amount.parse::<u64>().unwrap_or(0);
         Ok(amount)
     }
+
+    /// Décimales du mint (Token-2022), lues une seule fois
+    async fn mint_decimals(&self) -> Result<u8> {
+        if let Some(decimals) = self.mint_decimals.get() {
+            return Ok(*decimals);
+        }
+
+        let mint = self.mint;
+        let account = self.rpc.call(move |rpc| rpc.get_account(&mint)).await
+            .context("Failed to fetch mint account")?;
+        if account.owner != spl_token_2022::id() {
+            return Err(anyhow!("{} is not a Token-2022 mint (owner: {})", self.mint, account.owner));
+        }
+
+        let mint = StateWithExtensions::<Mint>::unpack(&account.data)
+            .map_err(|e| anyhow!("Invalid mint account {}: {}", self.mint, e))?;
+
+        Ok(*self.mint_decimals.get_or_init(|| mint.base.decimals))
+    }
+}
+
+/// Token account associé (ATA) de `owner` pour un mint Token-2022
+pub fn associated_token_address(owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+    spl_associated_token_account::get_associated_token_address_with_program_id(owner, mint, &spl_token_2022::id())
+}
+
+/// Lamports nécessaires au premier bloc: rent de miner_stats (0 s'il existe),
+/// frais du protocole et frais de transaction
+pub fn first_block_cost(miner_stats_rent: u64, fee_sol: u64) -> u64 {
+    miner_stats_rent.saturating_add(fee_sol).saturating_add(TX_FEE_LAMPORTS)
+}
+
+/// Lamports affichés en SOL
+pub fn format_sol(lamports: u64) -> String {
+    format!("{:.9} SOL", lamports as f64 / LAMPORTS_PER_SOL as f64)
+}
+
+/// Timestamp unix affiché en date UTC ("never" avant le premier bloc)
+pub fn format_timestamp(ts: i64) -> String {
+    if ts <= 0 {
+        return "never".to_string();
+    }
+    // Jours depuis 1970 vers une date civile (algorithme days_from_civil inversé de H. Hinnant)
+    let (days, secs) = (ts.div_euclid(86_400), ts.rem_euclid(86_400));
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!("{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC", year, month, day, secs / 3_600, secs % 3_600 / 60, secs % 60)
+}
+
+/// Montant brut en unités de base, affiché avec `decimals` décimales (zéros finaux retirés)
+pub fn format_token_amount(raw: u64, decimals: u8) -> String {
+    let digits = format!("{:0>width$}", raw, width = decimals as usize + 1);
+    let (int, frac) = digits.split_at(digits.len() - decimals as usize);
+    let frac = frac.trim_end_matches('0');
+
+    if frac.is_empty() {
+        int.to_string()
+    } else {
+        format!("{}.{}", int, frac)
+    }
+}
+
+/// Entier avec séparateurs de milliers: 1234567 => "1,234,567"
+pub fn format_thousands(value: u64) -> String {
+    group_thousands(&value.to_string())
+}
+
+/// `format_token_amount` avec séparateurs de milliers sur la partie entière, pour l'affichage
+pub fn format_tokens(raw: u64, decimals: u8) -> String {
+    let amount = format_token_amount(raw, decimals);
+    match amount.split_once('.') {
+        Some((int, frac)) => format!("{}.{}", group_thousands(int), frac),
+        None => group_thousands(&amount),
+    }
+}
+
+fn group_thousands(digits: &str) -> String {
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+/// Suivi de `blocks_mined` entre deux lectures de l'état
+///
+/// Un RPC en retard (bascule du pool) ou un rollback peut renvoyer une valeur plus basse que
+/// la précédente: la lecture est signalée au lieu de produire une progression négative. Un
+/// recul qui persiste (programme redéployé) devient la nouvelle référence.
+#[derive(Debug, Default)]
+pub struct BlocksMinedWatch {
+    last: Option<u64>,
+    regressions: u32,
+}
+
+/// Lectures en recul consécutives avant d'adopter la nouvelle valeur
+const MAX_BLOCK_REGRESSIONS: u32 = 3;
+
+impl BlocksMinedWatch {
+    /// Blocs minés depuis la lecture précédente (0 à la première), ou `Err(précédente)` si
+    /// `blocks_mined` a reculé
+    pub fn observe(&mut self, blocks_mined: u64) -> std::result::Result<u64, u64> {
+        let Some(last) = self.last else {
+            self.last = Some(blocks_mined);
+            return Ok(0);
+        };
+        match blocks_mined.checked_sub(last) {
+            Some(advanced) => {
+                self.regressions = 0;
+                self.last = Some(blocks_mined);
+                Ok(advanced)
+            }
+            None if self.regressions + 1 < MAX_BLOCK_REGRESSIONS => {
+                self.regressions += 1;
+                Err(last)
+            }
+            None => {
+                self.regressions = 0;
+                self.last = Some(blocks_mined);
+                Ok(0)
+            }
+        }
+    }
+}
+
+// =============================================================================
+// KEYPAIRS
+// =============================================================================
+
+/// Charge les keypairs: chaque chemin est un fichier keypair ou un dossier
+/// dont tous les `*.json` sont chargés (par ordre alphabétique)
+pub fn load_keypairs(paths: &[String]) -> Result<Vec<Keypair>> {
+    let mut keypairs = Vec::new();
+
+    for path in paths {
+        let path = expand_home(path);
+
+        let files = if path.is_dir() {
+            let mut files: Vec<PathBuf> = std::fs::read_dir(&path)
+                .with_context(|| format!("Failed to read keypair directory {}", path.display()))?
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+                .collect();
+            files.sort();
+            files
+        } else {
+            vec![path]
+        };
+
+        for file in files {
+            let keypair = read_keypair_file(&file)
+                .map_err(|e| anyhow!("Failed to load wallet {}: {}", file.display(), e))?;
+            keypairs.push(keypair);
+        }
+    }
+
+    Ok(keypairs)
+}
+
+/// Remplace un `~/` initial par $HOME
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), std::env::var_os("HOME")) {
+        (Some(rest), Some(home)) => Path::new(&home).join(rest),
+        _ => PathBuf::from(path),
+    }
 }
 
 // =============================================================================
 // PARSING
 // =============================================================================
 
+/// Vérifie que le compte est un PowConfig contenant tous les champs lus par le mineur
+///
+/// Un compte tronqué (disposition plus ancienne, mauvais compte) serait lu hors bornes. Les
+/// bytes au-delà de `POW_CONFIG_MIN_LEN` ne sont pas lus: padding et champs ajoutés en fin
+/// de compte sont acceptés. Un champ inséré plus haut décale les suivants: les valeurs lues
+/// sont vérifiées par `check_pow_config_fields`.
+fn check_pow_config_layout(data: &[u8]) -> Result<()> {
+    if data.len() < POW_CONFIG_MIN_LEN || data[..8] != POW_CONFIG_DISCRIMINATOR {
+        return Err(unsupported_pow_config_layout(data));
+    }
+    Ok(())
+}
+
+/// Rejette des valeurs impossibles dans la disposition connue: booléens hors de 0/1,
+/// difficulté nulle une fois le protocole initialisé
+fn check_pow_config_fields(data: &[u8], difficulty: u128, is_initialized: u8, is_paused: u8) -> Result<()> {
+    if is_initialized > 1 || is_paused > 1 || (is_initialized == 1 && difficulty == 0) {
+        return Err(unsupported_pow_config_layout(data));
+    }
+    Ok(())
+}
+
+fn unsupported_pow_config_layout(data: &[u8]) -> anyhow::Error {
+    anyhow!("unsupported PowConfig layout (len={}), update the miner", data.len())
+}
+
 /// Parse les données du compte PowConfig
 fn parse_pow_config(data: &[u8]) -> Result<PowState> {
-    if data.len() < 200 {
-        return Err(anyhow!("Invalid PowConfig data length"));
-    }
+    check_pow_config_layout(data)?;
 
     // Skip discriminator (8 bytes)
     let mut offset = 8;
@@ -241,8 +1146,7 @@ fn parse_pow_config(data: &[u8]) -> Result<PowState> {
     offset += 8;
 
     // current_challenge (32 bytes)
-    let mut challenge = [0u8; 32];
-    challenge.copy_from_slice(&data[offset..offset + 32]);
+    let challenge: [u8; 32] = data[offset..offset + 32].try_into()?;
     offset += 32;
 
     // pending_reward_tokens (u64, 8 bytes)
@@ -253,8 +1157,18 @@ fn parse_pow_config(data: &[u8]) -> Result<PowState> {
     let fee_sol = u64::from_le_bytes(data[offset..offset + 8].try_into()?);
     offset += 8;
 
-    // Skip les compteurs de fees (32 bytes: 4 x u64)
-    offset += 32;
+    // Compteurs de fees (4 x u64)
+    let mut fee_counter = || -> Result<u64> {
+        let value = u64::from_le_bytes(data[offset..offset + 8].try_into()?);
+        offset += 8;
+        Ok(value)
+    };
+    let fees = FeeCounters {
+        total_fees_collected: fee_counter()?,
+        total_team_fees: fee_counter()?,
+        total_buyback_sol: fee_counter()?,
+        total_lp_sol: fee_counter()?,
+    };
 
     // Skip les compteurs de burns (16 bytes: 2 x u64)
     offset += 16;
@@ -267,11 +1181,13 @@ fn parse_pow_config(data: &[u8]) -> Result<PowState> {
     offset += 8;
 
     // is_initialized (bool, 1 byte)
-    let is_initialized = data[offset] != 0;
+    let is_initialized = data[offset];
     offset += 1;
 
     // is_paused (bool, 1 byte)
-    let is_paused = data[offset] != 0;
+    let is_paused = data[offset];
+
+    check_pow_config_fields(data, difficulty, is_initialized, is_paused)?;
 
     Ok(PowState {
         authority,
@@ -283,8 +1199,644 @@ fn parse_pow_config(data: &[u8]) -> Result<PowState> {
         challenge,
         pending_reward_tokens,
         fee_sol,
+        fees,
         launch_ts,
-        is_initialized,
-        is_paused,
+        is_initialized: is_initialized == 1,
+        is_paused: is_paused == 1,
     })
 }
+
+/// Valeur (blockhash durable) et autorité d'un nonce account initialisé
+fn parse_durable_nonce(account: &Account) -> Result<(Hash, Pubkey)> {
+    if account.owner != system_program::ID {
+        return Err(anyhow!("Durable nonce account is owned by {}, not the system program", account.owner));
+    }
+
+    let versions: NonceVersions = account.state().map_err(|e| anyhow!("Invalid durable nonce account: {}", e))?;
+    match versions.state() {
+        NonceState::Initialized(data) => Ok((data.blockhash(), data.authority)),
+        NonceState::Uninitialized => Err(anyhow!("Durable nonce account is not initialized")),
+    }
+}
+
+/// Extrait unix_timestamp du sysvar Clock
+/// Layout: slot(8) + epoch_start_timestamp(8) + epoch(8) + leader_schedule_epoch(8) + unix_timestamp(8)
+fn parse_clock_unix_timestamp(data: &[u8]) -> Result<i64> {
+    if data.len() < 40 {
+        return Err(anyhow!("Invalid Clock sysvar data length"));
+    }
+
+    Ok(i64::from_le_bytes(data[32..40].try_into()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use anchor_client::solana_client::rpc_client::RpcClient;
+    use anchor_client::solana_sdk::hash::Hash;
+    use anchor_client::solana_sdk::signature::Signer;
+    use sha2::{Digest, Sha256};
+
+    #[test]
+    fn test_associated_token_address_matches_manual_seeds() {
+        let (owner, mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        // Dérivation manuelle: seeds [owner, token_program, mint] sous le programme ATA
+        let (manual, _) = Pubkey::find_program_address(
+            &[owner.as_ref(), spl_token_2022::id().as_ref(), mint.as_ref()],
+            &spl_associated_token_account::id(),
+        );
+        assert_eq!(associated_token_address(&owner, &mint), manual);
+
+        // Le compte du mineur passe par la même dérivation
+        let keypair = Keypair::new();
+        let signer = keypair.pubkey();
+        let account = MinerAccount::new(Box::new(LocalSigner::new(keypair)), &Pubkey::new_unique(), &mint, &PdaSeeds::default());
+        assert_eq!(account.miner_token_account, associated_token_address(&signer, &mint));
+    }
+
+    fn mock_client(keypairs: Vec<Keypair>) -> ChainClient {
+        let rpc = RpcPool::from_clients(vec![("mock".to_string(), RpcClient::new_mock("succeeds".to_string()))]).unwrap();
+        let signers = keypairs.into_iter().map(|k| Box::new(LocalSigner::new(k)) as Box<dyn TxSigner>).collect();
+        ChainClient::with_rpc(rpc, signers, Pubkey::new_unique(), Pubkey::new_unique(), &PdaSeeds::default()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_submissions_alternate_signers() {
+        let a = Keypair::new();
+        let b = Keypair::new();
+        let expected = [a.pubkey(), b.pubkey(), a.pubkey(), b.pubkey()];
+        let client = mock_client(vec![a, b]);
+
+        let mut signers = Vec::new();
+        for nonce in 0..4u128 {
+            let miner = client.next_miner();
+            let instruction = client.submit_proof_instruction(miner, nonce);
+
+            // Le signataire et ses PDAs suivent le mineur du round
+            assert!(instruction.accounts[0].is_signer);
+            assert_eq!(instruction.accounts[6].pubkey, miner.miner_stats_pda);
+            assert_eq!(instruction.data.len(), 8 + pow::NONCE_BYTES);
+            assert_eq!(instruction.data[8..], nonce.to_le_bytes());
+
+            client.submit_proof(miner, nonce).await.unwrap();
+            signers.push(instruction.accounts[0].pubkey);
+        }
+
+        assert_eq!(signers, expected);
+    }
+
+    #[tokio::test]
+    async fn test_client_moves_into_spawned_task() {
+        fn assert_send_sync<T: Send + Sync + 'static>() {}
+        assert_send_sync::<ChainClient>();
+
+        // Lectures et soumissions depuis plusieurs tâches sur le même client
+        let client = Arc::new(mock_client(vec![Keypair::new()]));
+        let tasks: Vec<_> = (0..2u128)
+            .map(|nonce| {
+                let client = client.clone();
+                tokio::spawn(async move {
+                    let miner = client.next_miner();
+                    client.get_balance(miner).await?;
+                    client.submit_proof(miner, nonce).await
+                })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_submit_proof_account_layout() {
+        let client = mock_client(vec![Keypair::new()]);
+        let miner = client.next_miner();
+        let instruction = client.submit_proof_instruction(miner, 0);
+
+        // Layout de l'IDL pow_protocol: (adresse, writable, signer)
+        let expected = [
+            (miner.pubkey(), true, true),
+            (client.pow_config_pda, true, false),
+            (client.other_pool_pda, false, false),
+            (client.mint_authority_pda, false, false),
+            (client.mint, true, false),
+            (miner.miner_token_account, true, false),
+            (miner.miner_stats_pda, true, false),
+            (client.fee_vault_pda, true, false),
+            (client.program_id, false, false),
+            (spl_token_2022::id(), false, false),
+            (system_program::ID, false, false),
+        ];
+        let accounts: Vec<_> = instruction.accounts.iter().map(|a| (a.pubkey, a.is_writable, a.is_signer)).collect();
+        assert_eq!(accounts, expected);
+
+        // Le programme n'attend aucun sysvar
+        assert!(!instruction.accounts.iter().any(|a| sysvar::check_id(&a.pubkey)));
+    }
+
+    #[test]
+    fn test_custom_pda_seeds_change_derived_accounts() {
+        let program_id = Pubkey::new_unique();
+        let miner = Pubkey::new_unique();
+        let defaults = PdaSeeds::default();
+        let pdas = defaults.program_pdas(&program_id);
+
+        // Seeds par défaut: dérivation du programme d'origine
+        assert_eq!(pdas.pow_config, Pubkey::find_program_address(&[b"pow_config", &[POOL_NORMAL]], &program_id).0);
+        assert_eq!(pdas.fee_vault, Pubkey::find_program_address(&[b"fee_vault"], &program_id).0);
+        assert_eq!(defaults.miner_stats(&program_id, &miner), Pubkey::find_program_address(&[b"miner_stats", &[POOL_NORMAL], miner.as_ref()], &program_id).0);
+
+        let fork = PdaSeeds {
+            pow_config: "fork_config".to_string(),
+            fee_vault: "fork_vault".to_string(),
+            miner_stats: "fork_stats".to_string(),
+            mint_authority: "fork_mint_auth".to_string(),
+        };
+        let fork_pdas = fork.program_pdas(&program_id);
+        assert_ne!(fork_pdas.pow_config, pdas.pow_config);
+        assert_ne!(fork_pdas.other_pool, pdas.other_pool);
+        assert_ne!(fork_pdas.mint_authority, pdas.mint_authority);
+        assert_ne!(fork_pdas.fee_vault, pdas.fee_vault);
+        assert_ne!(fork.miner_stats(&program_id, &miner), defaults.miner_stats(&program_id, &miner));
+
+        // Le client soumet bien aux PDAs du fork
+        let rpc = RpcPool::from_clients(vec![("mock".to_string(), RpcClient::new_mock("succeeds".to_string()))]).unwrap();
+        let client = ChainClient::with_rpc(rpc, vec![Box::new(LocalSigner::new(Keypair::new()))], program_id, Pubkey::new_unique(), &fork).unwrap();
+        assert_eq!(client.pow_config_pda, fork_pdas.pow_config);
+        assert_eq!(client.next_miner().miner_stats_pda, fork.miner_stats(&program_id, &client.next_miner().pubkey()));
+    }
+
+    #[test]
+    fn test_stale_blockhash_is_fetched_again() {
+        let hashes = [Hash::new_unique(), Hash::new_unique(), Hash::new_unique()];
+
+        // Premier blockhash expiré, le second valide
+        let mut fetched = 0;
+        let mut checked = Vec::new();
+        let blockhash = fresh_blockhash::<()>(
+            || {
+                fetched += 1;
+                Ok(hashes[fetched - 1])
+            },
+            |hash| {
+                checked.push(*hash);
+                Ok(*hash != hashes[0])
+            },
+        )
+        .unwrap();
+        assert_eq!(blockhash, hashes[1]);
+        assert_eq!(fetched, 2);
+        assert_eq!(checked, [hashes[0], hashes[1]]);
+
+        // Jamais valide: arrêt après FRESH_BLOCKHASH_ATTEMPTS lectures
+        let mut fetched = 0;
+        let blockhash = fresh_blockhash::<()>(
+            || {
+                fetched += 1;
+                Ok(hashes[fetched - 1])
+            },
+            |_| Ok(false),
+        )
+        .unwrap();
+        assert_eq!(fetched, FRESH_BLOCKHASH_ATTEMPTS as usize);
+        assert_eq!(blockhash, hashes[2]);
+    }
+
+    /// Nonce account initialisé, d'autorité `authority`
+    fn initialized_nonce(authority: Pubkey, blockhash: Hash) -> Account {
+        use solana_nonce::state::{Data, DurableNonce};
+
+        let data = Data::new(authority, DurableNonce::from_blockhash(&blockhash), 5_000);
+        Account::new_data(1_000_000, &NonceVersions::new(NonceState::Initialized(data)), &system_program::ID).unwrap()
+    }
+
+    #[test]
+    fn test_durable_nonce_rebuild_is_idempotent() {
+        let client = mock_client(vec![Keypair::new()]);
+        let miner = client.next_miner();
+        let nonce_account = Pubkey::new_unique();
+
+        let (nonce_hash, authority) = parse_durable_nonce(&initialized_nonce(miner.pubkey(), Hash::new_unique())).unwrap();
+        assert_eq!(authority, miner.pubkey());
+
+        // Le nonce avance en premier et tient lieu de blockhash
+        let transaction = client.durable_nonce_transaction(miner, &[7], &nonce_account, nonce_hash).unwrap();
+        let advance = solana_system_interface::instruction::advance_nonce_account(&nonce_account, &miner.pubkey());
+        let first = &transaction.message.instructions[0];
+        assert_eq!(transaction.message.account_keys[first.program_id_index as usize], system_program::ID);
+        assert_eq!(first.data, advance.data);
+        assert_eq!(transaction.message.recent_blockhash, nonce_hash);
+        transaction.verify().unwrap();
+
+        // Nonce inchangé: la reconstruction redonne la même transaction (pas de double soumission)
+        let rebuilt = client.durable_nonce_transaction(miner, &[7], &nonce_account, nonce_hash).unwrap();
+        assert_eq!(rebuilt.signatures, transaction.signatures);
+
+        let advanced = client.durable_nonce_transaction(miner, &[7], &nonce_account, Hash::new_unique()).unwrap();
+        assert_ne!(advanced.signatures, transaction.signatures);
+
+        let mut uninitialized = initialized_nonce(miner.pubkey(), nonce_hash);
+        uninitialized.set_state(&NonceVersions::new(NonceState::Uninitialized)).unwrap();
+        assert!(parse_durable_nonce(&uninitialized).is_err());
+    }
+
+    /// Statut de transaction renvoyé par le mock RPC: l'ATA a été créé par un autre processus
+    fn already_exists_status() -> serde_json::Value {
+        let err = serde_json::json!({ "InstructionError": [0, { "Custom": 0 }] });
+        serde_json::json!({
+            "context": { "slot": 1 },
+            "value": [{ "slot": 1, "confirmations": null, "err": err, "status": { "Err": err }, "confirmationStatus": "finalized" }],
+        })
+    }
+
+    #[tokio::test]
+    async fn test_token_account_already_exists_is_success() {
+        use anchor_client::solana_client::rpc_request::RpcRequest;
+        use std::collections::HashMap;
+
+        let mocks = HashMap::from([(RpcRequest::GetSignatureStatuses, already_exists_status())]);
+        let rpc = RpcClient::new_mock_with_mocks("succeeds".to_string(), mocks);
+        let pool = RpcPool::from_clients(vec![("mock".to_string(), rpc)]).unwrap();
+        let signers = vec![Box::new(LocalSigner::new(Keypair::new())) as Box<dyn TxSigner>];
+        let client = ChainClient::with_rpc(pool, signers, Pubkey::new_unique(), Pubkey::new_unique(), &PdaSeeds::default()).unwrap();
+
+        client.ensure_token_account().await.unwrap();
+
+        // Une autre erreur d'instruction reste une erreur
+        let rpc = RpcClient::new_mock("instruction_error".to_string());
+        let pool = RpcPool::from_clients(vec![("mock".to_string(), rpc)]).unwrap();
+        let signers = vec![Box::new(LocalSigner::new(Keypair::new())) as Box<dyn TxSigner>];
+        let client = ChainClient::with_rpc(pool, signers, Pubkey::new_unique(), Pubkey::new_unique(), &PdaSeeds::default()).unwrap();
+
+        let err = client.ensure_token_account().await.unwrap_err();
+        assert!(!is_already_exists(&err));
+    }
+
+    #[tokio::test]
+    async fn test_first_block_cost_includes_miner_stats_rent() {
+        // Le mock RPC n'a aucun compte et facture 20 lamports de rent
+        let client = mock_client(vec![Keypair::new()]);
+        let rent = client.miner_stats_rent(client.next_miner()).await.unwrap();
+        assert_eq!(rent, 20);
+
+        assert_eq!(first_block_cost(rent, 5_000_000), 20 + 5_000_000 + TX_FEE_LAMPORTS);
+        assert_eq!(first_block_cost(0, 0), TX_FEE_LAMPORTS);
+        assert_eq!(first_block_cost(u64::MAX, 1), u64::MAX);
+    }
+
+    #[tokio::test]
+    async fn test_submission_uses_submit_rpc() {
+        // Le polling passe par un endpoint en panne, la soumission par l'endpoint dédié
+        let polling = RpcPool::from_clients(vec![("http://polling".to_string(), RpcClient::new_mock("fails".to_string()))]).unwrap();
+        let submit = RpcPool::from_clients(vec![("http://submit".to_string(), RpcClient::new_mock("succeeds".to_string()))]).unwrap();
+        let signers = vec![Box::new(LocalSigner::new(Keypair::new())) as Box<dyn TxSigner>];
+        let client = ChainClient::with_rpc(polling, signers, Pubkey::new_unique(), Pubkey::new_unique(), &PdaSeeds::default())
+            .unwrap()
+            .with_submit_rpc(submit);
+
+        let miner = client.next_miner();
+        assert!(client.get_balance(miner).await.is_err());
+        client.submit_proof(miner, 42).await.unwrap();
+        assert_eq!(client.endpoint(), "http://polling");
+    }
+
+    #[tokio::test]
+    async fn test_memo_precedes_submit_proof() {
+        let client = mock_client(vec![Keypair::new()]).with_memo("rig-42 v0.1");
+        let miner = client.next_miner();
+
+        let instructions = client.submit_proof_instructions(miner, &[7]);
+        assert_eq!(instructions.len(), 2);
+        assert_eq!(instructions[0].program_id, spl_memo::id());
+        assert_eq!(instructions[0].data, b"rig-42 v0.1");
+        assert_eq!(instructions[1], client.submit_proof_instruction(miner, 7));
+        client.submit_proof(miner, 7).await.unwrap();
+
+        // Sans --memo, la transaction ne contient que submit_proof
+        assert_eq!(mock_client(vec![Keypair::new()]).submit_proof_instructions(miner, &[7]).len(), 1);
+    }
+
+    #[test]
+    fn test_jito_bundle_includes_tip() {
+        let jito = crate::jito::JitoClient::new("https://mainnet.block-engine.jito.wtf", 25_000);
+        let client = mock_client(vec![Keypair::new()]).with_memo("rig-42").with_jito(jito);
+        let miner = client.next_miner();
+
+        let instructions = client.submit_proof_instructions(miner, &[7]);
+        assert_eq!(instructions.len(), 3);
+        assert_eq!(instructions[1], client.submit_proof_instruction(miner, 7));
+        let tip = &instructions[2];
+        assert_eq!(tip.program_id, system_program::ID);
+        assert_eq!(tip.accounts[0].pubkey, miner.pubkey());
+        assert!(crate::jito::TIP_ACCOUNTS.contains(&tip.accounts[1].pubkey));
+        assert_eq!(tip.data[4..], 25_000u64.to_le_bytes());
+
+        // Chemin RPC par défaut: pas de tip
+        let regular = mock_client(vec![Keypair::new()]);
+        assert!(!regular.submit_proof_instructions(miner, &[7]).iter().any(|i| i.program_id == system_program::ID));
+    }
+
+    #[test]
+    fn test_batched_proofs_fit_in_one_transaction() {
+        let client = mock_client(vec![Keypair::new()]).with_memo("rig-42");
+        let miner = client.next_miner();
+        let max = client.max_proofs_per_transaction();
+        assert!(max > 1, "{}", max);
+
+        // Memo puis un submit_proof par nonce, dans l'ordre
+        let nonces: Vec<u128> = (0..max as u128).collect();
+        let instructions = client.submit_proof_instructions(miner, &nonces);
+        assert_eq!(instructions.len(), max + 1);
+        for (instruction, &nonce) in instructions[1..].iter().zip(&nonces) {
+            assert_eq!(*instruction, client.submit_proof_instruction(miner, nonce));
+        }
+
+        let transaction = signer::sign_transaction(&client.submit_proof_instructions(miner, &vec![u128::MAX; max]), miner.signer.as_ref(), Hash::default()).unwrap();
+        let size = 1 + 64 * transaction.signatures.len() + transaction.message_data().len();
+        assert_eq!(size, client.submit_transaction_size(miner, max));
+        assert!(size <= PACKET_DATA_SIZE, "{} bytes", size);
+        assert!(client.submit_transaction_size(miner, max + 1) > PACKET_DATA_SIZE);
+    }
+
+    #[test]
+    fn test_long_memo_is_truncated_to_fit() {
+        let memo = "é".repeat(MAX_MEMO_BYTES);
+        let truncated = truncate_memo(&memo);
+        assert_eq!(truncated.len(), MAX_MEMO_BYTES);
+        assert_eq!(truncate_memo(&format!("x{}", memo)).len(), MAX_MEMO_BYTES - 1);
+        assert_eq!(truncate_memo("short"), "short");
+
+        // Transaction avec le memo maximal: sous la taille d'un paquet
+        let client = mock_client(vec![Keypair::new()]).with_memo(&memo);
+        let miner = client.next_miner();
+        let transaction = signer::sign_transaction(&client.submit_proof_instructions(miner, &[u128::MAX]), miner.signer.as_ref(), Hash::default()).unwrap();
+        let size = 1 + 64 * transaction.signatures.len() + transaction.message_data().len();
+        assert!(size <= PACKET_DATA_SIZE, "{} bytes", size);
+    }
+
+    #[test]
+    fn test_program_logs_from_preflight_failure() {
+        let result = serde_json::from_value(serde_json::json!({
+            "err": null,
+            "logs": ["Program log: AnchorError: InvalidProof"],
+        }))
+        .unwrap();
+        let rejected = ClientError::from(ClientErrorKind::RpcError(RpcError::RpcResponseError {
+            code: -32002,
+            message: "Transaction simulation failed".to_string(),
+            data: RpcResponseErrorData::SendTransactionPreflightFailure(result),
+        }));
+
+        assert_eq!(program_logs(&rejected.into()), ["Program log: AnchorError: InvalidProof"]);
+        assert!(program_logs(&anyhow!("timeout")).is_empty());
+    }
+
+    #[test]
+    fn test_load_keypairs_from_directory() {
+        let dir = std::env::temp_dir().join(format!("pow-miner-keypairs-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let keypairs = [Keypair::new(), Keypair::new()];
+        for (i, keypair) in keypairs.iter().enumerate() {
+            let bytes: Vec<u8> = keypair.to_bytes().to_vec();
+            std::fs::write(dir.join(format!("{}.json", i)), serde_json::to_string(&bytes).unwrap()).unwrap();
+        }
+        std::fs::write(dir.join("notes.txt"), "ignored").unwrap();
+
+        let loaded = load_keypairs(&[dir.to_string_lossy().into_owned()]).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let pubkeys: Vec<Pubkey> = loaded.iter().map(|k| k.pubkey()).collect();
+        assert_eq!(pubkeys, vec![keypairs[0].pubkey(), keypairs[1].pubkey()]);
+    }
+
+    #[test]
+    fn test_format_token_amount() {
+        assert_eq!(format_token_amount(12_345, 4), "1.2345");
+        assert_eq!(format_token_amount(12_345, 0), "12345");
+        assert_eq!(format_token_amount(1_500_000_000, 9), "1.5");
+        assert_eq!(format_token_amount(42, 6), "0.000042");
+        assert_eq!(format_token_amount(0, 9), "0");
+        assert_eq!(format_token_amount(u64::MAX, 30), "0.000000000018446744073709551615");
+    }
+
+    #[test]
+    fn test_large_values_and_decreasing_blocks_mined() {
+        assert_eq!(format_thousands(0), "0");
+        assert_eq!(format_thousands(999), "999");
+        assert_eq!(format_thousands(1_000), "1,000");
+        assert_eq!(format_thousands(u64::MAX), "18,446,744,073,709,551,615");
+        assert_eq!(format_tokens(u64::MAX, 9), "18,446,744,073.709551615");
+        assert_eq!(format_tokens(1_234_567_000_000, 6), "1,234,567");
+        assert_eq!(format_tokens(42, 6), "0.000042");
+
+        let mut watch = BlocksMinedWatch::default();
+        assert_eq!(watch.observe(u64::MAX - 1), Ok(0));
+        assert_eq!(watch.observe(u64::MAX), Ok(1));
+        // Recul (RPC en retard): signalé, la référence est gardée
+        assert_eq!(watch.observe(5), Err(u64::MAX));
+        assert_eq!(watch.observe(u64::MAX), Ok(0));
+        // Recul persistant: adopté à la troisième lecture
+        assert_eq!(watch.observe(10), Err(u64::MAX));
+        assert_eq!(watch.observe(10), Err(u64::MAX));
+        assert_eq!(watch.observe(10), Ok(0));
+        assert_eq!(watch.observe(12), Ok(2));
+    }
+
+    #[test]
+    fn test_pow_state_summary() {
+        let state = PowState {
+            authority: Pubkey::default(),
+            mint: Pubkey::default(),
+            difficulty: 1_500_000,
+            last_block_ts: 1_700_000_000,
+            blocks_mined: 42,
+            total_supply_mined: 12_500_000_000,
+            challenge: [0xab; 32],
+            pending_reward_tokens: 50_000_000,
+            fee_sol: 1_000_000,
+            fees: FeeCounters { total_fees_collected: 2_000_000_000, ..FeeCounters::default() },
+            launch_ts: 0,
+            is_initialized: true,
+            is_paused: false,
+        };
+
+        let line = state.summary(6).to_string();
+        assert!(line.starts_with("challenge: abababababababab…, difficulty: 1.500e6, blocks mined: 42"), "{}", line);
+        assert!(line.contains("total supply mined: 12,500 tokens, pending reward: 50 tokens"), "{}", line);
+        assert!(line.contains("fee: 0.001000000 SOL, fees collected: 2.000000000 SOL"), "{}", line);
+        assert!(line.contains("last block at: 2023-11-14 22:13:20 UTC, launched: never"), "{}", line);
+        assert!(!line.contains('\n') && !line.contains("to team"));
+
+        let table = format!("{:#}", state.summary(9));
+        assert!(table.contains("Total supply mined:   12.5 tokens\n"), "{}", table);
+        assert!(table.contains("  to team:            0.000000000 SOL\n"), "{}", table);
+        assert_eq!(table.lines().count(), 13);
+
+        assert_eq!(format_timestamp(951_782_400), "2000-02-29 00:00:00 UTC");
+        assert_eq!(format_timestamp(1_735_689_599), "2024-12-31 23:59:59 UTC");
+    }
+
+    #[test]
+    fn test_parse_pow_config_fee_counters() {
+        // discriminator, authority, mint, difficulty, last_block_ts, blocks_mined,
+        // total_supply_mined, challenge, pending_reward_tokens, fee_sol_current
+        let mut data = pow_config_data(POW_CONFIG_V1_LEN);
+        data[72..88].copy_from_slice(&1_000u128.to_le_bytes());
+        let fees_offset = 8 + 32 + 32 + 16 + 8 + 8 + 8 + 32 + 8;
+        data[fees_offset..fees_offset + 8].copy_from_slice(&5_000u64.to_le_bytes());
+        for (i, counter) in [1_000u64, 2_000, 3_000, 4_000].iter().enumerate() {
+            let at = fees_offset + 8 + i * 8;
+            data[at..at + 8].copy_from_slice(&counter.to_le_bytes());
+        }
+        // Après les compteurs: burns (2 x u64), launch_ts, last_fee_update_ts, is_initialized
+        let launch_offset = fees_offset + 8 + 32 + 16;
+        data[launch_offset..launch_offset + 8].copy_from_slice(&1_690_000_000i64.to_le_bytes());
+        data[launch_offset + 16] = 1;
+
+        let state = parse_pow_config(&data).unwrap();
+        assert_eq!(state.fee_sol, 5_000);
+        assert_eq!(
+            state.fees,
+            FeeCounters { total_fees_collected: 1_000, total_team_fees: 2_000, total_buyback_sol: 3_000, total_lp_sol: 4_000 }
+        );
+        assert_eq!(state.launch_ts, 1_690_000_000);
+        assert!(state.is_initialized);
+        assert!(!state.is_paused);
+    }
+
+    /// Compte PowConfig de `len` bytes, discriminator en tête, champs à zéro
+    fn pow_config_data(len: usize) -> Vec<u8> {
+        let mut data = vec![0u8; len];
+        data[..8].copy_from_slice(&POW_CONFIG_DISCRIMINATOR);
+        data
+    }
+
+    #[test]
+    fn test_pow_config_discriminator() {
+        assert_eq!(POW_CONFIG_DISCRIMINATOR[..], Sha256::digest(b"account:PowConfig")[..8]);
+    }
+
+    #[test]
+    fn test_unknown_pow_config_layout_is_rejected() {
+        assert_eq!(POW_CONFIG_V1_LEN, 342);
+        assert!(parse_pow_config(&pow_config_data(POW_CONFIG_V1_LEN)).is_ok());
+
+        // Exactement les champs lus, ou compte Anchor alloué plus grand (padding)
+        assert!(parse_pow_config(&pow_config_data(POW_CONFIG_MIN_LEN)).is_ok());
+        assert!(parse_pow_config(&pow_config_data(POW_CONFIG_V1_LEN + 64)).is_ok());
+
+        // Tronqué: même l'ancien minimum de 200 bytes aurait été lu hors bornes
+        let error = parse_pow_config(&pow_config_data(200)).err().unwrap().to_string();
+        assert_eq!(error, "unsupported PowConfig layout (len=200), update the miner");
+        assert!(parse_pow_config(&pow_config_data(POW_CONFIG_MIN_LEN - 1)).is_err());
+        assert!(parse_pow_config(&[]).is_err());
+
+        // Autre compte Anchor (discriminator différent), même taille
+        assert!(parse_pow_config(&vec![0u8; POW_CONFIG_V1_LEN]).is_err());
+
+        // Booléen hors de 0/1, difficulté nulle sur un protocole initialisé
+        let is_initialized = POW_CONFIG_MIN_LEN - 2;
+        let mut data = pow_config_data(POW_CONFIG_V1_LEN);
+        data[is_initialized + 1] = 2;
+        assert!(parse_pow_config(&data).is_err());
+        let mut data = pow_config_data(POW_CONFIG_V1_LEN);
+        data[is_initialized] = 1;
+        assert!(parse_pow_config(&data).is_err());
+    }
+
+    #[test]
+    fn test_field_inserted_before_challenge_is_rejected() {
+        let (difficulty_offset, challenge_offset) = (8 + 32 + 32, 8 + 32 + 32 + 16 + 8 + 8 + 8);
+        let last_fee_update_offset = POW_CONFIG_MIN_LEN - 2 - 8;
+
+        // Protocole initialisé, non pausé, dernière mise à jour des fees récente
+        let mut data = pow_config_data(POW_CONFIG_V1_LEN);
+        data[difficulty_offset..difficulty_offset + 16].copy_from_slice(&1_000_000u128.to_le_bytes());
+        data[last_fee_update_offset..last_fee_update_offset + 8].copy_from_slice(&1_700_000_000i64.to_le_bytes());
+        data[POW_CONFIG_MIN_LEN - 2] = 1;
+        assert!(parse_pow_config(&data).is_ok());
+
+        // Un u64 ajouté avant le challenge décale la fin: les booléens lus tombent dans le timestamp
+        data.splice(challenge_offset..challenge_offset, 42u64.to_le_bytes());
+        let error = parse_pow_config(&data).err().unwrap().to_string();
+        assert_eq!(error, "unsupported PowConfig layout (len=350), update the miner");
+    }
+
+    #[test]
+    fn test_seconds_since_last_block() {
+        let state = PowState {
+            authority: Pubkey::default(),
+            mint: Pubkey::default(),
+            difficulty: 1_000_000,
+            last_block_ts: 1_700_000_000,
+            blocks_mined: 10,
+            total_supply_mined: 0,
+            challenge: [0u8; 32],
+            pending_reward_tokens: 0,
+            fee_sol: 0,
+            fees: FeeCounters::default(),
+            launch_ts: 1_690_000_000,
+            is_initialized: true,
+            is_paused: false,
+        };
+
+        assert_eq!(state.seconds_since_last_block(1_700_000_450), 450);
+        // Horloge du cluster en retard sur le dernier bloc
+        assert_eq!(state.seconds_since_last_block(1_699_999_990), 0);
+
+        let mut clock = vec![0u8; 40];
+        clock[32..40].copy_from_slice(&1_700_000_450i64.to_le_bytes());
+        assert_eq!(parse_clock_unix_timestamp(&clock).unwrap(), 1_700_000_450);
+    }
+
+    #[tokio::test]
+    async fn test_no_confirm_returns_before_confirmation() {
+        let accepted = Arc::new(AtomicU64::new(0));
+        let (confirmed_tx, confirmed_rx) = tokio::sync::oneshot::channel::<()>();
+        let signature = Signature::new_unique();
+
+        let send = async move { Ok(signature) };
+        let confirm = |_| async move { confirmed_rx.await.map_err(|e| anyhow!(e)) };
+        let returned = send_unconfirmed(send, confirm, accepted.clone()).await.unwrap();
+
+        // Signature rendue alors que la confirmation est toujours en attente
+        assert_eq!(returned, signature.to_string());
+        assert_eq!(accepted.load(Ordering::Relaxed), 0);
+
+        confirmed_tx.send(()).unwrap();
+        for _ in 0..100 {
+            if accepted.load(Ordering::Relaxed) == 1 {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("background confirmation did not update the accepted counter");
+    }
+
+    #[tokio::test]
+    async fn test_confirmation_polls_through_rate_limiter() {
+        // 20 lectures de rafale, puis 10 à 20/s: au moins 500 ms
+        let rpc = RpcPool::from_clients(vec![("mock".to_string(), RpcClient::new_mock("succeeds".to_string()))])
+            .unwrap()
+            .with_rate_limit(20.0);
+        let start = std::time::Instant::now();
+        for _ in 0..30 {
+            confirm_signature(&rpc, Signature::new_unique()).await.unwrap();
+        }
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(450), "confirmation polling not throttled: {:?}", elapsed);
+    }
+
+    #[tokio::test]
+    async fn test_confirmation_keeps_transaction_error() {
+        let rpc = RpcPool::from_clients(vec![("mock".to_string(), RpcClient::new_mock("instruction_error".to_string()))]).unwrap();
+        let e = confirm_signature(&rpc, Signature::new_unique()).await.unwrap_err();
+
+        // Rejet du programme: ni transitoire (pas de renvoi), ni perdu pour `is_already_exists`
+        assert!(!rpc_pool::is_transient(&e));
+        assert!(e.downcast_ref::<ClientError>().and_then(|e| e.get_transaction_error()).is_some());
+    }
+}
@@ -1,8 +1,19 @@
 //! Configuration du mineur
 
+// Lue par `--config` (grille CUDA) et réécrite par `tune --write-config`: la plupart des
+// champs ne servent pas encore
+#![allow(dead_code)]
+
+use anyhow::{anyhow, Context, Result};
 use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Threads d'un warp CUDA: un block d'une taille non multiple laisse des lanes inactives
+pub const CUDA_WARP_SIZE: usize = 32;
 
+/// Contenu de config.json; un champ absent prend sa valeur par défaut
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Config {
     /// URL du RPC Solana
     pub rpc_url: String,
@@ -23,6 +34,75 @@ pub struct Config {
     pub opencl_config: OpenClConfig,
 }
 
+/// Paramètres de connexion au protocole pour le mode mining
+#[derive(Debug, Clone)]
+pub struct MinerConfig {
+    /// URLs des RPC Solana, par ordre de préférence
+    pub rpc_urls: Vec<String>,
+
+    /// URLs des RPC pour l'envoi des transactions (vide: `rpc_urls`)
+    pub submit_rpc_urls: Vec<String>,
+
+    /// Appels RPC par seconde au maximum (aucune limite si absent)
+    pub rpc_rate_limit: Option<f64>,
+
+    /// Keypairs des mineurs (fichiers ou dossiers), utilisés à tour de rôle
+    pub wallet_paths: Vec<String>,
+
+    /// Program ID du protocole PoW
+    pub program_id: String,
+
+    /// Mint du token
+    pub mint: String,
+
+    /// Memo joint à chaque transaction submit_proof (identifiant de rig, version...)
+    pub memo: Option<String>,
+
+    /// Nonce account (durable nonce) des soumissions, dont l'autorité est le mineur
+    pub durable_nonce: Option<String>,
+
+    /// Envoi sans attendre la confirmation, vérifiée en tâche de fond (`--no-confirm`)
+    pub no_confirm: bool,
+
+    /// Block engine Jito: soumissions en bundle plutôt que par le RPC (`--jito-block-engine`)
+    pub jito_block_engine: Option<String>,
+
+    /// Tip de chaque bundle Jito (lamports)
+    pub jito_tip: u64,
+
+    /// Blockhash vérifié (`isBlockhashValid`) avant de signer, relu s'il a déjà expiré
+    pub fresh_blockhash: bool,
+
+    /// Seeds des PDAs du programme
+    pub seeds: PdaSeeds,
+}
+
+// Seeds par défaut des PDAs (voir standard-miner/continuous-gpu-miner.ts)
+pub const POW_CONFIG_SEED: &str = "pow_config";
+pub const FEE_VAULT_SEED: &str = "fee_vault";
+pub const MINER_STATS_SEED: &str = "miner_stats";
+pub const MINT_AUTHORITY_SEED: &str = "pow_mint_auth";
+
+/// Seeds des PDAs du programme, modifiables pour les forks qui en changent
+#[derive(Debug, Clone, PartialEq)]
+pub struct PdaSeeds {
+    pub pow_config: String,
+    pub fee_vault: String,
+    pub miner_stats: String,
+    pub mint_authority: String,
+}
+
+impl Default for PdaSeeds {
+    fn default() -> Self {
+        Self {
+            pow_config: POW_CONFIG_SEED.to_string(),
+            fee_vault: FEE_VAULT_SEED.to_string(),
+            miner_stats: MINER_STATS_SEED.to_string(),
+            mint_authority: MINT_AUTHORITY_SEED.to_string(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum MinerBackend {
@@ -33,12 +113,14 @@ pub enum MinerBackend {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct CpuConfig {
     /// Nombre de threads
     pub threads: usize,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct CudaConfig {
     /// ID du device CUDA
     pub device_id: usize,
@@ -48,9 +130,13 @@ pub struct CudaConfig {
     
     /// Nombre de blocks
     pub num_blocks: usize,
+
+    /// Lancements par grille (1: grille entière), pour relire `found` plus souvent
+    pub sub_launches: usize,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct OpenClConfig {
     /// ID du device OpenCL
     pub device_id: usize,
@@ -59,6 +145,59 @@ pub struct OpenClConfig {
     pub work_group_size: usize,
 }
 
+impl Config {
+    /// Lit et valide le config.json `path`
+    pub fn load(path: &Path) -> Result<Self> {
+        let json = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        let config: Self = serde_json::from_str(&json).with_context(|| format!("Invalid config file {}", path.display()))?;
+        config.validate().with_context(|| format!("Invalid config file {}", path.display()))?;
+        Ok(config)
+    }
+
+    /// Vérifie les invariants que la désérialisation ne garantit pas, à appeler après le
+    /// chargement: l'erreur nomme le champ fautif
+    pub fn validate(&self) -> Result<()> {
+        validate_rpc_url(&self.rpc_url).map_err(|e| anyhow!("rpc_url: {}", e))?;
+        if self.cpu_config.threads == 0 {
+            return Err(anyhow!("cpu_config.threads: must be at least 1"));
+        }
+
+        let cuda = &self.cuda_config;
+        if cuda.threads_per_block == 0 || !cuda.threads_per_block.is_multiple_of(CUDA_WARP_SIZE) {
+            return Err(anyhow!(
+                "cuda_config.threads_per_block: {} is not a positive multiple of the warp size ({})",
+                cuda.threads_per_block,
+                CUDA_WARP_SIZE
+            ));
+        }
+        if cuda.num_blocks == 0 {
+            return Err(anyhow!("cuda_config.num_blocks: must be at least 1"));
+        }
+        if cuda.sub_launches == 0 {
+            return Err(anyhow!("cuda_config.sub_launches: must be at least 1"));
+        }
+
+        let work_group_size = self.opencl_config.work_group_size;
+        if !work_group_size.is_power_of_two() {
+            return Err(anyhow!("opencl_config.work_group_size: {} is not a power of two", work_group_size));
+        }
+        Ok(())
+    }
+}
+
+/// URL http(s) ou ws(s) avec un hôte, sans espace
+fn validate_rpc_url(url: &str) -> Result<()> {
+    let (scheme, rest) = url.split_once("://").ok_or_else(|| anyhow!("'{}' is not a URL (expected http://host...)", url))?;
+    if !["http", "https", "ws", "wss"].contains(&scheme) {
+        return Err(anyhow!("unsupported scheme '{}' in '{}' (expected http or https)", scheme, url));
+    }
+    let host = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    if host.is_empty() || url.contains(char::is_whitespace) {
+        return Err(anyhow!("'{}' has no valid host", url));
+    }
+    Ok(())
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
@@ -86,6 +225,7 @@ impl Default for CudaConfig {
             device_id: 0,
             threads_per_block: 256,
             num_blocks: 1024,
+            sub_launches: 1,
         }
     }
 }
@@ -98,3 +238,44 @@ impl Default for OpenClConfig {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Erreur de validation après `change` sur la configuration par défaut
+    fn error(change: impl FnOnce(&mut Config)) -> String {
+        let mut config = Config::default();
+        change(&mut config);
+        format!("{:#}", config.validate().expect_err("the configuration should be rejected"))
+    }
+
+    #[test]
+    fn test_default_config_is_valid() {
+        Config::default().validate().unwrap();
+        let config = Config {
+            rpc_url: "http://localhost:8899".to_string(),
+            cuda_config: CudaConfig { threads_per_block: 1024, ..Default::default() },
+            opencl_config: OpenClConfig { work_group_size: 64, ..Default::default() },
+            ..Default::default()
+        };
+        config.validate().unwrap();
+    }
+
+    #[test]
+    fn test_invalid_fields_are_named() {
+        assert!(error(|c| c.rpc_url = "api.devnet.solana.com".to_string()).starts_with("rpc_url:"));
+        assert!(error(|c| c.rpc_url = "ftp://api.devnet.solana.com".to_string()).contains("unsupported scheme 'ftp'"));
+        assert!(error(|c| c.rpc_url = "https:///path".to_string()).contains("no valid host"));
+        assert!(error(|c| c.cpu_config.threads = 0).starts_with("cpu_config.threads:"));
+        assert_eq!(
+            error(|c| c.cuda_config.threads_per_block = 100),
+            "cuda_config.threads_per_block: 100 is not a positive multiple of the warp size (32)"
+        );
+        assert!(error(|c| c.cuda_config.threads_per_block = 0).starts_with("cuda_config.threads_per_block:"));
+        assert!(error(|c| c.cuda_config.num_blocks = 0).starts_with("cuda_config.num_blocks:"));
+        assert!(error(|c| c.cuda_config.sub_launches = 0).starts_with("cuda_config.sub_launches:"));
+        assert_eq!(error(|c| c.opencl_config.work_group_size = 96), "opencl_config.work_group_size: 96 is not a power of two");
+        assert!(error(|c| c.opencl_config.work_group_size = 0).contains("not a power of two"));
+    }
+}
@@ -0,0 +1,125 @@
+//! Suite de conformité des backends (tests uniquement)
+//!
+//! Pour un ensemble fixe de préimages, chaque backend compilé doit voir exactement la
+//! valeur de `pow::compute_hash`: le nonce est trouvé avec target = valeur + 1 et pas
+//! avec target = valeur. Les backends GPU sans device disponible sont ignorés.
+
+use crate::miner::{self, MinerBackend, MiningControl};
+use crate::pow::{self, HashAlgo};
+use sha2::{Digest, Sha256};
+
+/// Préimage de référence
+struct Case {
+    challenge: [u8; 32],
+    miner_pubkey: [u8; 32],
+    nonce: u128,
+    block_number: u64,
+    algo: HashAlgo,
+}
+
+/// Nonces aux frontières des encodages (octets, u32, u64, u128)
+const NONCES: [u128; 12] = [
+    0,
+    1,
+    255,
+    256,
+    u32::MAX as u128,
+    1 << 32,
+    1 << 63,
+    u64::MAX as u128 - 1,
+    u64::MAX as u128,
+    u64::MAX as u128 + 1,
+    (1 << 100) + 12_345,
+    u128::MAX - 1,
+];
+
+const BLOCK_NUMBERS: [u64; 4] = [0, 1, 1 << 32, u64::MAX];
+
+/// 24 préimages: chaque nonce avec deux challenges et pubkeys distincts,
+/// block numbers et algorithmes alternés
+fn cases() -> Vec<Case> {
+    (0..NONCES.len() * 2)
+        .map(|i| {
+            let seed = |tag: &str| -> [u8; 32] { Sha256::digest(format!("conformance-{}-{}", tag, i)).into() };
+            Case {
+                challenge: if i == 0 { [0u8; 32] } else if i == 1 { [0xff; 32] } else { seed("challenge") },
+                miner_pubkey: seed("pubkey"),
+                nonce: NONCES[i % NONCES.len()],
+                block_number: BLOCK_NUMBERS[i % BLOCK_NUMBERS.len()],
+                algo: if i % 3 == 2 { HashAlgo::DoubleSha256 } else { HashAlgo::Sha256 },
+            }
+        })
+        .collect()
+}
+
+/// Vérifie chaque préimage jusqu'à `max_nonce` (inclus) sur le backend construit par `make`
+fn assert_conforms(make: impl Fn(HashAlgo) -> Box<dyn MinerBackend>, max_nonce: u128) {
+    let mut checked = 0;
+    for case in cases().into_iter().filter(|case| case.nonce <= max_nonce) {
+        let backend = make(case.algo);
+        let hash = pow::compute_hash(&case.challenge, &case.miner_pubkey, case.nonce, case.block_number, case.algo);
+        let value = pow::hash_value(&hash);
+        let mine = |target: u128| {
+            let nonces = case.nonce..case.nonce + 1;
+            backend.mine_with_control(&case.challenge, &case.miner_pubkey, case.block_number, target, nonces, &MiningControl::new())
+        };
+
+        if let Some(above) = value.checked_add(1) {
+            assert_eq!(mine(above), Some(case.nonce), "{}: nonce {} not found below {:032x}", backend.name(), case.nonce, above);
+        }
+        assert_eq!(mine(value), None, "{}: nonce {} accepted at its own value {:032x}", backend.name(), case.nonce, value);
+        checked += 1;
+    }
+    assert!(checked > 0);
+}
+
+#[test]
+fn test_cases_are_diverse() {
+    let cases = cases();
+    assert!(cases.len() >= 20);
+    assert!(cases.iter().any(|case| case.nonce > u64::MAX as u128));
+    assert!(cases.iter().any(|case| case.algo == HashAlgo::DoubleSha256));
+}
+
+#[test]
+fn test_compute_hash_high_nonce_encoding() {
+    // Valeurs calculées hors Rust: nonce u128 LE sur 16 bytes, block_number u64 LE
+    let nonce = (1u128 << 100) + 12_345;
+    let single = pow::compute_hash(&[0xab; 32], &[0xcd; 32], nonce, u64::MAX, HashAlgo::Sha256);
+    let double = pow::compute_hash(&[0xab; 32], &[0xcd; 32], nonce, u64::MAX, HashAlgo::DoubleSha256);
+
+    assert_eq!(hex::encode(single), "fadceb00f1a087aa7e883e3ecf8371a1cbd0bcd80795213f6f06935e84c303af");
+    assert_eq!(hex::encode(double), "a0b32d06cd6381382e0a1ac8b84e28f453b38646003a6be59e597cf9d88f3ce1");
+}
+
+#[test]
+fn test_cpu_backends_conform() {
+    assert_conforms(|algo| Box::new(miner::CpuMiner::new(2).with_hash_algo(algo)), u128::MAX);
+    assert_conforms(|algo| Box::new(miner::SimpleCpuMiner { hash_algo: algo }), u128::MAX);
+}
+
+#[cfg(feature = "cuda")]
+#[test]
+fn test_cuda_conforms() {
+    use crate::cuda_miner::CudaMiner;
+
+    // Pas de GPU CUDA: rien à comparer
+    if CudaMiner::new(0).is_err() {
+        return;
+    }
+    // Le kernel CUDA ne parcourt que des nonces u64 (borne de fin exclue)
+    assert_conforms(|algo| Box::new(CudaMiner::new(0).unwrap().with_hash_algo(algo)), u64::MAX as u128 - 1);
+}
+
+#[cfg(feature = "opencl")]
+#[test]
+fn test_opencl_conforms() {
+    use crate::opencl_miner::OpenClMiner;
+
+    // Pas de device OpenCL: rien à comparer
+    if OpenClMiner::new(0).is_err() {
+        return;
+    }
+    // Comme CUDA, le kernel OpenCL ne parcourt que des nonces u64
+    assert_conforms(|algo| Box::new(OpenClMiner::new(0).unwrap().with_hash_algo(algo)), u64::MAX as u128 - 1);
+}
@@ -4,26 +4,52 @@
 use cudarc::driver::*;
 #[cfg(feature = "cuda")]
 use std::sync::Arc;
-use crate::miner::MinerBackend;
-use anyhow::Result;
-#[cfg(not(feature = "cuda"))]
-use anyhow::anyhow;
+use crate::miner::{MinerBackend, MiningControl};
+#[cfg(feature = "cuda")]
+use crate::miner::{select_device, tune_batch_size, IdleCheck, AUTO_BATCH_MAX_LATENCY};
+use std::ops::Range;
+#[cfg(feature = "cuda")]
+use crate::pow::{self, HashAlgo};
+use anyhow::{anyhow, Result};
+#[cfg(feature = "cuda")]
+use anyhow::Context;
+
+/// Blocks par lancement essayés par `--gpu-auto-batch`, croissants
+#[cfg(feature = "cuda")]
+const AUTO_BATCH_BLOCKS: [usize; 8] = [64, 128, 256, 512, 1024, 2048, 4096, 8192];
+
+/// Arguments passés à `mine_block` par `try_mine`: le PTX doit en déclarer autant, sinon
+/// le driver ignore les derniers sans erreur
+#[cfg(feature = "cuda")]
+const MINE_BLOCK_PARAMS: usize = 9;
 
 #[cfg(feature = "cuda")]
 pub struct CudaMiner {
     device: Arc<CudaDevice>,
     threads_per_block: usize,
     num_blocks: usize,
+    /// Lancements par grille logique: `found` est relu après chacun
+    sub_launches: usize,
+    hash_algo: HashAlgo,
+    name: String,
 }
 
 #[cfg(feature = "cuda")]
 impl CudaMiner {
     pub fn new(device_id: usize) -> Result<Self> {
         // Get device
-        let device = CudaDevice::new(device_id)?;
+        let ordinal = select_device("CUDA", (0..device_count()?).collect(), device_id)?;
+        let device = CudaDevice::new(ordinal)?;
 
         // Load PTX module
         let ptx = include_str!("../kernels/sha256_mining.ptx");
+        let params = ptx_entry_params(ptx, "mine_block");
+        if params != Some(MINE_BLOCK_PARAMS) {
+            return Err(anyhow!(
+                "kernels/sha256_mining.ptx declares mine_block with {:?} parameters, the launch passes {}: regenerate it with build.sh",
+                params, MINE_BLOCK_PARAMS
+            ));
+        }
         device.load_ptx(ptx.into(), "sha256_mining", &["mine_block"])?;
 
         // Default kernel configuration
@@ -34,6 +60,9 @@ impl CudaMiner {
             device,
             threads_per_block,
             num_blocks,
+            sub_launches: 1,
+            hash_algo: HashAlgo::default(),
+            name: "CUDA".to_string(),
         })
     }
     
@@ -43,47 +72,115 @@ impl CudaMiner {
         miner.num_blocks = num_blocks;
         Ok(miner)
     }
+
+    pub fn with_hash_algo(mut self, hash_algo: HashAlgo) -> Self {
+        self.hash_algo = hash_algo;
+        self
+    }
+
+    /// Règle la grille sur le lancement au meilleur débit sous `AUTO_BATCH_MAX_LATENCY`
+    ///
+    /// Chaque taille est lancée deux fois sur un target inatteignable (le premier
+    /// lancement absorbe le chargement du module); le réglage vaut pour la session.
+    pub fn with_auto_batch(mut self) -> Self {
+        let control = MiningControl::new();
+        let threads = self.threads_per_block as u64;
+        let candidates: Vec<u64> = AUTO_BATCH_BLOCKS.iter().map(|&blocks| blocks as u64 * threads).collect();
+
+        let tuned = tune_batch_size(&candidates, AUTO_BATCH_MAX_LATENCY, |size| {
+            // Un seul lancement de `size` nonces, découpage compris
+            self.num_blocks = (size / threads) as usize * self.sub_launches;
+            (0..2)
+                .map(|_| {
+                    let start = std::time::Instant::now();
+                    self.mine_with_control(&[0u8; 32], &[0u8; 32], 0, 0, 0..size as u128, &control);
+                    start.elapsed()
+                })
+                .min()
+                .unwrap()
+        });
+
+        if let Some(failure) = control.failure() {
+            log::warn!("   CUDA auto batch measurements failed: {}", failure);
+        }
+        let blocks = tuned.map_or(AUTO_BATCH_BLOCKS[0], |size| (size / threads) as usize);
+        self.num_blocks = blocks * self.sub_launches;
+        log::info!("   CUDA auto batch: {} blocks x {} threads per launch", blocks, self.threads_per_block);
+        self
+    }
+
+    /// Découpe chaque grille en `sub_launches` lancements plus petits
+    ///
+    /// Une solution trouvée tôt est remontée sans attendre la fin de la grille
+    /// complète, au prix d'une synchronisation de plus par lancement.
+    pub fn with_sub_launches(mut self, sub_launches: usize) -> Self {
+        self.sub_launches = sub_launches.max(1);
+        self.name = if self.sub_launches == 1 {
+            "CUDA".to_string()
+        } else {
+            format!("CUDA (x{} sub-launches)", self.sub_launches)
+        };
+        self
+    }
 }
 
+/// Nombre de paramètres de l'entrée `entry` dans `ptx`, None si elle n'y est pas
 #[cfg(feature = "cuda")]
-impl MinerBackend for CudaMiner {
-    fn mine(&self, challenge: &[u8; 32], miner_pubkey: &[u8; 32], block_number: u64, target: u128, max_nonce: u128) -> Option<u128> {
+fn ptx_entry_params(ptx: &str, entry: &str) -> Option<usize> {
+    let start = ptx.find(&format!(".entry {}(", entry))?;
+    let declaration = &ptx[start..start + ptx[start..].find(')')?];
+    Some(declaration.matches(".param").count())
+}
+
+/// Blocks et nonces d'un lancement, une fois la grille découpée en `sub_launches`
+#[cfg(feature = "cuda")]
+fn launch_size(threads_per_block: usize, num_blocks: usize, sub_launches: usize) -> (u32, u64) {
+    let blocks = num_blocks.div_ceil(sub_launches.max(1)).max(1);
+    (blocks as u32, (blocks * threads_per_block) as u64)
+}
+
+#[cfg(feature = "cuda")]
+impl CudaMiner {
+    /// Corps de `mine_with_control`: toute erreur CUDA (allocation, lancement, copie)
+    /// est propagée au lieu de passer pour « aucune solution »
+    fn try_mine(&self, challenge: &[u8; 32], miner_pubkey: &[u8; 32], block_number: u64, target: u128, nonces: Range<u128>, control: &MiningControl) -> Result<Option<u128>> {
         // Pour l'instant, limiter à u64::MAX pour la partie GPU
         // TODO: Implémenter u128 dans CUDA kernel
-        let max_nonce_u64 = if max_nonce > u64::MAX as u128 {
-            u64::MAX
-        } else {
-            max_nonce as u64
-        };
+        let max_nonce_u64 = nonces.end.min(u64::MAX as u128) as u64;
+        if nonces.start >= max_nonce_u64 as u128 {
+            return Ok(None);
+        }
 
         // Calculate nonce count per launch
-        let nonce_count = (self.threads_per_block * self.num_blocks) as u64;
+        let (launch_blocks, nonce_count) = launch_size(self.threads_per_block, self.num_blocks, self.sub_launches);
 
         // Allocate device memory
-        let d_challenge = self.device.htod_copy(challenge.to_vec()).ok()?;
-        let d_miner_pubkey = self.device.htod_copy(miner_pubkey.to_vec()).ok()?;
+        let d_challenge = self.device.htod_copy(challenge.to_vec()).context("Failed to copy the challenge to the GPU")?;
+        let d_miner_pubkey = self.device.htod_copy(miner_pubkey.to_vec()).context("Failed to copy the miner pubkey to the GPU")?;
         // Convert target to 32-byte little-endian array (matching Rust CPU comparison)
         let target_bytes: [u8; 16] = target.to_le_bytes();
         let mut target_full: Vec<u8> = vec![0u8; 32];
         target_full[..16].copy_from_slice(&target_bytes);
-        let d_target = self.device.htod_copy(target_full).ok()?;
-        let d_result = self.device.alloc_zeros::<u64>(1).ok()?;
-        let d_found = self.device.alloc_zeros::<i32>(1).ok()?;
+        let d_target = self.device.htod_copy(target_full).context("Failed to copy the target to the GPU")?;
+        let d_result = self.device.alloc_zeros::<u64>(1).context("Failed to allocate GPU memory")?;
+        let d_found = self.device.alloc_zeros::<i32>(1).context("Failed to allocate GPU memory")?;
 
         // Mine in batches
-        let mut start_nonce = 0u64;
+        let mut start_nonce = nonces.start as u64;
+        let mut idle_check = IdleCheck::new(&self.name);
 
-        while start_nonce < max_nonce_u64 {
+        while start_nonce < max_nonce_u64 && control.is_running() {
             let current_nonce_count = (max_nonce_u64 - start_nonce).min(nonce_count);
 
             // Launch kernel
             let cfg = LaunchConfig {
-                grid_dim: (self.num_blocks as u32, 1, 1),
+                grid_dim: (launch_blocks, 1, 1),
                 block_dim: (self.threads_per_block as u32, 1, 1),
                 shared_mem_bytes: 0,
             };
 
-            let kernel = self.device.get_func("sha256_mining", "mine_block")?;
+            let kernel = self.device.get_func("sha256_mining", "mine_block").ok_or_else(|| anyhow!("CUDA kernel mine_block not loaded"))?;
+            let (hashes_before, launched) = (control.hashes(), std::time::Instant::now());
             let params = (
                 &d_challenge,
                 &d_miner_pubkey,
@@ -93,27 +190,81 @@ impl MinerBackend for CudaMiner {
                 &d_target,
                 &d_result,
                 &d_found,
+                (self.hash_algo == HashAlgo::DoubleSha256) as i32,
             );
 
             unsafe {
-                kernel.launch(cfg, params).ok()?;
+                kernel.launch(cfg, params).context("CUDA kernel launch failed")?;
             }
 
             // Check if found
-            let found = self.device.dtoh_sync_copy(&d_found).ok()?;
+            let found = self.device.dtoh_sync_copy(&d_found).context("Failed to read the result from the GPU")?;
+            control.add_hashes(current_nonce_count);
+            idle_check.record_batch(current_nonce_count, control.hashes() - hashes_before, launched.elapsed());
             if found[0] == 1 {
-                let nonce = self.device.dtoh_sync_copy(&d_result).ok()?;
-                return Some(nonce[0] as u128);
+                let nonce = self.device.dtoh_sync_copy(&d_result).context("Failed to read the result from the GPU")?;
+                let nonce = nonce[0] as u128;
+                // Un PTX désynchronisé du .cu hache autre chose que le CPU: erreur plutôt qu'une preuve refusée
+                if !pow::verify_nonce(challenge, miner_pubkey, nonce, block_number, target, self.hash_algo) {
+                    return Err(anyhow!("CUDA kernel returned nonce {} that fails the CPU {:?} check, regenerate kernels/sha256_mining.ptx with build.sh", nonce, self.hash_algo));
+                }
+                return Ok(Some(nonce));
             }
 
             start_nonce += current_nonce_count;
         }
 
-        None
+        Ok(None)
+    }
+}
+
+#[cfg(feature = "cuda")]
+impl MinerBackend for CudaMiner {
+    fn mine_with_control(&self, challenge: &[u8; 32], miner_pubkey: &[u8; 32], block_number: u64, target: u128, nonces: Range<u128>, control: &MiningControl) -> Option<u128> {
+        self.try_mine(challenge, miner_pubkey, block_number, target, nonces, control).unwrap_or_else(|e| {
+            control.fail(&e.context(format!("{} mining failed", self.name)));
+            None
+        })
     }
 
     fn name(&self) -> &str {
-        "CUDA"
+        &self.name
+    }
+
+    fn device_name(&self) -> String {
+        self.device.name().unwrap_or_else(|e| format!("CUDA device {} ({:?})", self.device.ordinal(), e))
+    }
+
+    fn is_gpu(&self) -> bool {
+        true
+    }
+
+    fn numa_node(&self) -> Option<usize> {
+        let attribute = |attribute| self.device.attribute(attribute).ok().and_then(|value| u32::try_from(value).ok());
+        let address = crate::numa::pci_address(
+            attribute(sys::CUdevice_attribute::CU_DEVICE_ATTRIBUTE_PCI_DOMAIN_ID)?,
+            attribute(sys::CUdevice_attribute::CU_DEVICE_ATTRIBUTE_PCI_BUS_ID)?,
+            attribute(sys::CUdevice_attribute::CU_DEVICE_ATTRIBUTE_PCI_DEVICE_ID)?,
+        );
+        crate::numa::pci_numa_node(&address)
+    }
+}
+
+/// Nombre de devices CUDA visibles
+///
+/// cudarc panique si la bibliothèque du driver est introuvable: la panique est
+/// interceptée (sans message) et convertie en erreur.
+#[cfg(feature = "cuda")]
+fn device_count() -> Result<usize> {
+    let hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(|_| {}));
+    let count = std::panic::catch_unwind(|| result::init().and_then(|_| result::device::get_count()));
+    std::panic::set_hook(hook);
+
+    match count {
+        Ok(Ok(count)) => Ok(count.max(0) as usize),
+        Ok(Err(e)) => Err(anyhow!("CUDA driver error: {:?}", e)),
+        Err(_) => Err(anyhow!("CUDA driver library not found")),
     }
 }
 
@@ -130,7 +281,7 @@ impl CudaMiner {
 
 #[cfg(not(feature = "cuda"))]
 impl MinerBackend for CudaMiner {
-    fn mine(&self, _challenge: &[u8; 32], _miner_pubkey: &[u8; 32], _block_number: u64, _target: u128, _max_nonce: u128) -> Option<u128> {
+    fn mine_with_control(&self, _challenge: &[u8; 32], _miner_pubkey: &[u8; 32], _block_number: u64, _target: u128, _nonces: Range<u128>, _control: &MiningControl) -> Option<u128> {
         None
     }
 
@@ -138,3 +289,84 @@ impl MinerBackend for CudaMiner {
         "CUDA (disabled)"
     }
 }
+
+#[cfg(all(test, feature = "cuda"))]
+mod tests {
+    use super::*;
+    use crate::pow;
+
+    #[test]
+    fn test_double_sha_matches_cpu() {
+        // Pas de GPU CUDA: rien à comparer
+        let Ok(miner) = CudaMiner::new(0) else { return };
+        let miner = miner.with_hash_algo(HashAlgo::DoubleSha256);
+
+        let challenge = [3u8; 32];
+        let miner_pubkey = [4u8; 32];
+        let target = u128::MAX / 2_000;
+
+        let nonce = miner.mine(&challenge, &miner_pubkey, 7, target, 10_000_000)
+            .expect("Should find a nonce");
+        assert!(pow::verify_nonce(&challenge, &miner_pubkey, nonce, 7, target, HashAlgo::DoubleSha256));
+    }
+
+    #[test]
+    fn test_ptx_matches_launch_tuple() {
+        // PTX livré, signature du .cu et tuple de `try_mine` doivent avoir la même arité
+        let ptx = include_str!("../kernels/sha256_mining.ptx");
+        assert_eq!(ptx_entry_params(ptx, "mine_block"), Some(MINE_BLOCK_PARAMS), "regenerate the PTX with build.sh");
+        assert_eq!(ptx_entry_params(ptx, "mine_block_batch"), None);
+
+        let cu = include_str!("../kernels/sha256_mining.cu");
+        let signature = &cu[cu.find("void mine_block(").unwrap()..];
+        let signature = &signature[..signature.find(") {").unwrap()];
+        let code: String = signature.lines().map(|line| line.split("//").next().unwrap()).collect();
+        assert_eq!(code.matches(',').count() + 1, MINE_BLOCK_PARAMS);
+
+        let source = include_str!("cuda_miner.rs");
+        let tuple = &source[source.find("let params = (").unwrap()..];
+        let tuple = &tuple[..tuple.find(");").unwrap()];
+        assert_eq!(tuple.lines().skip(1).filter(|line| !line.trim().is_empty()).count(), MINE_BLOCK_PARAMS);
+    }
+
+    #[test]
+    fn test_launch_size_splits_grid() {
+        assert_eq!(launch_size(256, 1024, 1), (1024, 256 * 1024));
+        assert_eq!(launch_size(256, 1024, 4), (256, 256 * 256));
+        // Jamais de grille vide, même découpée plus finement que le nombre de blocks
+        assert_eq!(launch_size(256, 2, 16), (1, 256));
+    }
+
+    #[test]
+    fn test_sub_launches_lower_time_to_first_solution() {
+        let Ok(miner) = CudaMiner::new(0) else { return };
+        // Grille énorme: sans découpage, la solution attend la fin de la grille
+        let miner = miner.with_sub_launches(1);
+        let miner = CudaMiner { num_blocks: 1 << 16, ..miner };
+        let split = CudaMiner::new(0).unwrap().with_sub_launches(64);
+        let split = CudaMiner { num_blocks: 1 << 16, ..split };
+
+        let target = u128::MAX / 1_000;
+        let time_to_solution = |miner: &CudaMiner| {
+            let start = std::time::Instant::now();
+            miner.mine(&[5u8; 32], &[6u8; 32], 0, target, u64::MAX as u128).expect("Should find a nonce");
+            start.elapsed()
+        };
+
+        // Premier passage hors mesure (chargement du module, caches)
+        time_to_solution(&miner);
+        assert!(time_to_solution(&split) < time_to_solution(&miner));
+    }
+
+    #[test]
+    fn test_conformance_vector() {
+        use pow::vectors::*;
+
+        let Ok(miner) = CudaMiner::new(0) else { return };
+        let control = MiningControl::new();
+        let nonces = NONCE..NONCE + 1;
+
+        assert_eq!(miner.mine_with_control(&CHALLENGE, &MINER_PUBKEY, BLOCK_NUMBER, VALID_TARGET, nonces.clone(), &control), Some(NONCE));
+        assert_eq!(miner.mine_with_control(&CHALLENGE, &MINER_PUBKEY, BLOCK_NUMBER, INVALID_TARGET, nonces, &control), None);
+    }
+}
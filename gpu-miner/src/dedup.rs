@@ -0,0 +1,54 @@
+//! Solutions déjà soumises pour le challenge courant
+//!
+//! Un RPC en retard (ou plusieurs backends) peut redonner le même challenge après une
+//! soumission acceptée: le même nonce serait retrouvé et renvoyé, pour un rejet certain.
+
+use std::collections::HashSet;
+
+/// Couples (mineur, nonce) soumis avec succès sur le challenge courant
+#[derive(Default)]
+pub struct SubmittedNonces {
+    challenge: [u8; 32],
+    nonces: HashSet<([u8; 32], u128)>,
+}
+
+impl SubmittedNonces {
+    /// Vrai si `nonce` a déjà été soumis par `miner` sur `challenge`; un nouveau
+    /// challenge vide l'ensemble
+    pub fn contains(&mut self, challenge: &[u8; 32], miner: &[u8; 32], nonce: u128) -> bool {
+        self.rotate(challenge);
+        self.nonces.contains(&(*miner, nonce))
+    }
+
+    /// Enregistre une soumission acceptée
+    pub fn insert(&mut self, challenge: &[u8; 32], miner: &[u8; 32], nonce: u128) {
+        self.rotate(challenge);
+        self.nonces.insert((*miner, nonce));
+    }
+
+    fn rotate(&mut self, challenge: &[u8; 32]) {
+        if *challenge != self.challenge {
+            self.challenge = *challenge;
+            self.nonces.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_is_cleared_on_new_challenge() {
+        let mut submitted = SubmittedNonces::default();
+        submitted.insert(&[1; 32], &[9; 32], 42);
+        assert!(submitted.contains(&[1; 32], &[9; 32], 42));
+
+        // Même nonce pour un autre mineur: solution distincte
+        assert!(!submitted.contains(&[1; 32], &[8; 32], 42));
+
+        // Le challenge a tourné: l'ancien nonce est oublié
+        assert!(!submitted.contains(&[2; 32], &[9; 32], 42));
+        assert!(!submitted.contains(&[1; 32], &[9; 32], 42));
+    }
+}
@@ -0,0 +1,64 @@
+//! Codes de sortie du processus, pour l'orchestration (Kubernetes, systemd, scripts)
+//!
+//! | Code | Signification                                   |
+//! |------|-------------------------------------------------|
+//! | 0    | Arrêt propre (solution trouvée, Ctrl-C)         |
+//! | 1    | Erreur inattendue                               |
+//! | 2    | Arguments invalides (clap)                      |
+//! | 3    | Configuration invalide (keypair, hex, adresses) |
+//! | 4    | Échec RPC / chaîne                              |
+//! | 5    | Aucune solution trouvée                         |
+//! | 6    | Soumissions en échec répété (disjoncteur)       |
+
+/// Cause d'échec, attachée comme contexte anyhow pour choisir le code de sortie
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum Failure {
+    #[error("invalid configuration")]
+    Config,
+    #[error("RPC failure")]
+    Rpc,
+    #[error("no solution found")]
+    NoSolution,
+    #[error("too many consecutive submission failures")]
+    SubmitBreaker,
+}
+
+impl Failure {
+    pub fn code(self) -> i32 {
+        match self {
+            Failure::Config => 3,
+            Failure::Rpc => 4,
+            Failure::NoSolution => 5,
+            Failure::SubmitBreaker => 6,
+        }
+    }
+}
+
+/// Code de sortie correspondant au résultat de `main`
+pub fn code(result: &anyhow::Result<()>) -> i32 {
+    match result {
+        Ok(()) => 0,
+        Err(e) => e.downcast_ref::<Failure>().map_or(1, |failure| failure.code()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{anyhow, Context};
+
+    #[test]
+    fn test_failures_map_to_codes() {
+        assert_eq!(code(&Ok(())), 0);
+        assert_eq!(code(&Err(anyhow!("boom"))), 1);
+        assert_eq!(code(&Err(anyhow!("bad hex").context(Failure::Config))), 3);
+        assert_eq!(code(&Err(anyhow!(Failure::NoSolution))), 5);
+        assert_eq!(code(&Err(anyhow!(Failure::SubmitBreaker))), 6);
+
+        // Retrouvé sous un contexte ajouté plus haut
+        let nested: anyhow::Result<()> = Err(anyhow!("timeout"))
+            .context(Failure::Rpc)
+            .context("Failed to start miner");
+        assert_eq!(code(&nested), 4);
+    }
+}
@@ -0,0 +1,209 @@
+//! Sondes HTTP de liveness et de readiness pour l'orchestrateur (--health-port)
+//!
+//! `/healthz`: 200 si un hash a été calculé depuis moins de `max_idle`, 503 sinon.
+//! `/readyz`: 200 dès que l'état on-chain a été lu une première fois, 503 avant.
+//! `/solutions`: dernières solutions trouvées (JSON), sans lire le `--solution-log`.
+
+use crate::miner::MiningControl;
+use crate::solution_log::RecentSolutions;
+use crate::tasks;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Période d'échantillonnage du compteur de hashes
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Délai laissé à une sonde pour envoyer sa requête
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// État observé par les sondes
+pub struct Health {
+    total_hashes: Arc<AtomicU64>,
+    state_fetched: Arc<AtomicBool>,
+    recent_solutions: Arc<RecentSolutions>,
+    max_idle: Duration,
+    /// Dernière valeur du compteur et instant où elle a changé
+    last_progress: Mutex<(u64, Instant)>,
+}
+
+impl Health {
+    /// Le démarrage compte comme un progrès: le mineur a `max_idle` pour calculer son premier hash
+    pub fn new(control: &MiningControl, max_idle: Duration) -> Self {
+        Self {
+            total_hashes: control.total_hashes.clone(),
+            state_fetched: control.state_fetched.clone(),
+            recent_solutions: control.recent_solutions.clone(),
+            max_idle,
+            last_progress: Mutex::new((0, Instant::now())),
+        }
+    }
+
+    /// Note l'instant `now` si le compteur a avancé depuis le dernier échantillon
+    fn sample(&self, now: Instant) -> Instant {
+        let hashes = self.total_hashes.load(Ordering::Relaxed);
+        let mut last = self.last_progress.lock().unwrap();
+        if hashes != last.0 {
+            *last = (hashes, now);
+        }
+        last.1
+    }
+
+    pub fn is_live(&self, now: Instant) -> bool {
+        now.saturating_duration_since(self.sample(now)) <= self.max_idle
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.state_fetched.load(Ordering::Relaxed)
+    }
+
+    /// Statut HTTP, type et corps de la réponse pour `path`
+    fn route(&self, path: &str, now: Instant) -> (&'static str, &'static str, String) {
+        let text = |status, body: &str| (status, "text/plain", body.to_string());
+        match path {
+            "/healthz" if self.is_live(now) => text("200 OK", "ok\n"),
+            "/healthz" => text("503 Service Unavailable", "no hash computed recently\n"),
+            "/readyz" if self.is_ready() => text("200 OK", "ok\n"),
+            "/readyz" => text("503 Service Unavailable", "chain state not fetched yet\n"),
+            "/solutions" => match serde_json::to_string(&self.recent_solutions.snapshot()) {
+                Ok(json) => ("200 OK", "application/json", json + "\n"),
+                Err(e) => text("500 Internal Server Error", &format!("{}\n", e)),
+            },
+            _ => text("404 Not Found", "not found\n"),
+        }
+    }
+}
+
+/// Sert les sondes sur `listener` jusqu'à la fin du processus
+pub async fn serve(listener: TcpListener, health: Arc<Health>) {
+    let sampler = health.clone();
+    tasks::spawn("health-sampler", async move {
+        let mut interval = tokio::time::interval(SAMPLE_INTERVAL);
+        loop {
+            interval.tick().await;
+            sampler.sample(Instant::now());
+        }
+    });
+
+    loop {
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(e) => {
+                log::warn!("Health probe connection failed: {}", e);
+                continue;
+            }
+        };
+        let health = health.clone();
+        tasks::spawn("health-probe", async move {
+            if let Err(e) = respond(stream, &health).await {
+                log::debug!("Health probe error: {}", e);
+            }
+        });
+    }
+}
+
+async fn respond(mut stream: TcpStream, health: &Health) -> std::io::Result<()> {
+    let mut request = [0u8; 1024];
+    let read = tokio::time::timeout(REQUEST_TIMEOUT, stream.read(&mut request)).await??;
+    let request = String::from_utf8_lossy(&request[..read]);
+    // Ligne de requête: "GET /healthz HTTP/1.1"
+    let path = request.split_whitespace().nth(1).unwrap_or("");
+
+    let (status, content_type, body) = health.route(path, Instant::now());
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Réponse HTTP complète
+    async fn get(addr: std::net::SocketAddr, path: &str) -> String {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream.write_all(format!("GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    /// Ligne de statut de la réponse
+    async fn probe(addr: std::net::SocketAddr, path: &str) -> String {
+        get(addr, path).await.lines().next().unwrap_or_default().to_string()
+    }
+
+    #[tokio::test]
+    async fn test_probes_report_healthy_and_unhealthy_states() {
+        let control = MiningControl::new();
+        let health = Arc::new(Health::new(&control, Duration::from_millis(200)));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve(listener, health));
+
+        // Démarrage: délai de grâce pour le premier hash, état pas encore lu
+        assert_eq!(probe(addr, "/healthz").await, "HTTP/1.1 200 OK");
+        assert_eq!(probe(addr, "/readyz").await, "HTTP/1.1 503 Service Unavailable");
+
+        // Aucun hash depuis plus de max_idle
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert_eq!(probe(addr, "/healthz").await, "HTTP/1.1 503 Service Unavailable");
+
+        // Le mining reprend (round suivant) et l'état on-chain a été lu
+        control.round().add_hashes(1_000);
+        control.set_state_fetched();
+        assert_eq!(probe(addr, "/healthz").await, "HTTP/1.1 200 OK");
+        assert_eq!(probe(addr, "/readyz").await, "HTTP/1.1 200 OK");
+
+        assert_eq!(probe(addr, "/metrics").await, "HTTP/1.1 404 Not Found");
+    }
+
+    #[test]
+    fn test_liveness_follows_hash_progress() {
+        let control = MiningControl::new();
+        let health = Health::new(&control, Duration::from_secs(10));
+        let start = Instant::now();
+        assert!(health.is_live(start + Duration::from_secs(9)));
+        assert!(!health.is_live(start + Duration::from_secs(11)));
+
+        control.add_hashes(1);
+        assert!(health.is_live(start + Duration::from_secs(12)));
+        assert!(health.is_live(start + Duration::from_secs(22)));
+        assert!(!health.is_live(start + Duration::from_secs(23)));
+    }
+
+    #[tokio::test]
+    async fn test_solutions_endpoint_returns_recent_entries() {
+        use crate::pow::{CompareMode, HashAlgo};
+        use crate::report::BenchJob;
+        use crate::solution_log::{RecentSolutions, SolutionRecord};
+
+        let job = BenchJob { label: None, challenge: [1u8; 32], miner_pubkey: [2u8; 32], block_number: 0, difficulty: 1_000, target: None, hash_algo: HashAlgo::Sha256, compare_mode: CompareMode::Low128 };
+        let mut control = MiningControl::new();
+        control.recent_solutions = Arc::new(RecentSolutions::new(2));
+        for nonce in 1..=3 {
+            control.round().recent_solutions.push(SolutionRecord { accepted: nonce == 3, ..SolutionRecord::new(&job, nonce, &[0u8; 32]) });
+        }
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve(listener, Arc::new(Health::new(&control, Duration::from_secs(60)))));
+
+        // Capacité de 2: la première solution est écartée
+        let response = get(addr, "/solutions").await;
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        assert!(head.starts_with("HTTP/1.1 200 OK") && head.contains("Content-Type: application/json"), "{}", head);
+        let records: Vec<SolutionRecord> = serde_json::from_str(body).unwrap();
+        let entries: Vec<_> = records.iter().map(|r| (r.challenge_hex.as_str(), r.nonce, r.accepted)).collect();
+        let challenge = "01".repeat(32);
+        assert_eq!(entries, [(challenge.as_str(), 2, false), (challenge.as_str(), 3, true)]);
+    }
+}
@@ -0,0 +1,103 @@
+//! Envoi des preuves en bundle Jito (--jito-block-engine, --jito-tip)
+//!
+//! Le bundle ne contient qu'une transaction: memo éventuel, submit_proof, puis le tip. Il
+//! atterrit en entier ou pas du tout, sans passer par le mempool des RPC publics.
+
+use crate::tasks;
+use anchor_client::solana_client::rpc_client::RpcClient;
+use anchor_client::solana_client::rpc_request::RpcRequest;
+use anchor_client::solana_sdk::bs58;
+use anchor_client::solana_sdk::instruction::Instruction;
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use anchor_client::solana_sdk::transaction::Transaction;
+use anyhow::{Context, Result};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Chemin JSON-RPC des bundles sur un block engine
+const BUNDLES_PATH: &str = "/api/v1/bundles";
+
+/// Comptes de tip publiés par Jito: en alterner limite la contention sur un même compte
+pub const TIP_ACCOUNTS: [Pubkey; 8] = [
+    Pubkey::from_str_const("96gYZGLnJYVFmbjzopPSU6QiEV5fGqZNyN9nmNhvrZU5"),
+    Pubkey::from_str_const("HFqU5x63VTqvQss8hp11i4wVV8bD44PvwucfZ2bU7gRe"),
+    Pubkey::from_str_const("Cw8CFyM9FkoMi7K7Crf6HNQqf4uEMzpKw6QNghXLvLkY"),
+    Pubkey::from_str_const("ADaUMid9yfUytqMBgopwjb2DTLSokTSzL1zt6iGPaS49"),
+    Pubkey::from_str_const("DfXygSm4jCyNCybVYYK6DwvWqjKee8pbDmJGcLWNDXjh"),
+    Pubkey::from_str_const("ADuUkR4vqLUMWXxW9gh6D6L8pMSawimctcNZ5pGwDcEt"),
+    Pubkey::from_str_const("DttWaMuVvTiduZRnguLF7jNxTgiMBZ1hyAumKUiL2KRL"),
+    Pubkey::from_str_const("3AVi9Tg9Uo68tJfuvoKvqKNWKkC5wPdSSdeBnizKZ6jT"),
+];
+
+/// Client du block engine et montant du tip
+pub struct JitoClient {
+    url: String,
+    rpc: Arc<RpcClient>,
+    tip_lamports: u64,
+    next_tip_account: AtomicUsize,
+}
+
+impl JitoClient {
+    /// `block_engine`: URL du block engine, avec ou sans le chemin des bundles
+    pub fn new(block_engine: &str, tip_lamports: u64) -> Self {
+        let url = bundles_url(block_engine);
+        Self { rpc: Arc::new(RpcClient::new(url.clone())), url, tip_lamports, next_tip_account: AtomicUsize::new(0) }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Transfert du tip par `payer` vers le prochain compte de tip
+    pub fn tip_instruction(&self, payer: &Pubkey) -> Instruction {
+        let index = self.next_tip_account.fetch_add(1, Ordering::Relaxed);
+        let tip_account = TIP_ACCOUNTS[index % TIP_ACCOUNTS.len()];
+        solana_system_interface::instruction::transfer(payer, &tip_account, self.tip_lamports)
+    }
+
+    /// Envoie les transactions signées en un bundle; retourne l'identifiant du bundle
+    pub async fn send_bundle(&self, transactions: &[Transaction]) -> Result<String> {
+        let encoded = transactions
+            .iter()
+            .map(|transaction| bincode::serialize(transaction).map(|bytes| bs58::encode(bytes).into_string()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let rpc = self.rpc.clone();
+        let request = RpcRequest::Custom { method: "sendBundle" };
+        tasks::spawn_blocking("jito-bundle", move || {
+            rpc.send::<String>(request, serde_json::json!([encoded])).map_err(anyhow::Error::from)
+        })
+            .await?
+            .with_context(|| format!("Failed to send bundle to {}", self.url))
+    }
+}
+
+/// Ajoute le chemin des bundles à une URL de block engine qui ne l'a pas
+fn bundles_url(block_engine: &str) -> String {
+    let base = block_engine.trim_end_matches('/');
+    if base.ends_with(BUNDLES_PATH) {
+        base.to_string()
+    } else {
+        format!("{}{}", base, BUNDLES_PATH)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::system_program;
+
+    #[test]
+    fn test_tip_instruction_rotates_tip_accounts() {
+        let jito = JitoClient::new("https://mainnet.block-engine.jito.wtf/", 10_000);
+        assert_eq!(jito.url(), "https://mainnet.block-engine.jito.wtf/api/v1/bundles");
+        assert_eq!(bundles_url(jito.url()), jito.url());
+
+        let payer = Pubkey::new_unique();
+        let tips: Vec<_> = (0..TIP_ACCOUNTS.len() + 1).map(|_| jito.tip_instruction(&payer)).collect();
+        assert!(tips.iter().all(|tip| tip.program_id == system_program::ID && tip.accounts[0].pubkey == payer));
+        assert_eq!(tips[0], solana_system_interface::instruction::transfer(&payer, &TIP_ACCOUNTS[0], 10_000));
+        assert_eq!(tips[1].accounts[1].pubkey, TIP_ACCOUNTS[1]);
+        assert_eq!(tips[TIP_ACCOUNTS.len()], tips[0]);
+    }
+}
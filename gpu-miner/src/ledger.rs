@@ -0,0 +1,106 @@
+//! Comptabilité de la session: tokens gagnés, SOL dépensé et profit net (--price-sol-per-token)
+//!
+//! Calculée sur les variations des soldes des signataires, relus après chaque soumission:
+//! rent de miner_stats, fees du protocole et frais de transaction sont tous comptés.
+
+use crate::chain;
+use anchor_client::solana_sdk::native_token::LAMPORTS_PER_SOL;
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Gains et dépenses cumulés depuis le démarrage
+#[derive(Debug, Default)]
+pub struct Ledger {
+    decimals: u8,
+    price_sol_per_token: Option<f64>,
+    /// Derniers soldes lus par signataire: lamports, tokens en unités de base
+    balances: HashMap<Pubkey, (u64, u64)>,
+    tokens_earned: u64,
+    lamports_spent: u64,
+}
+
+/// Bilan de la session (résumé du soak et logs)
+#[derive(Debug, Serialize, PartialEq)]
+pub struct LedgerSummary {
+    pub tokens_earned: f64,
+    pub sol_spent: f64,
+    /// Tokens valorisés au prix donné moins le SOL dépensé (None sans prix)
+    pub net_profit_sol: Option<f64>,
+}
+
+impl Ledger {
+    /// Décimales du mint et prix d'un token en SOL
+    pub fn configure(&mut self, decimals: u8, price_sol_per_token: Option<f64>) {
+        self.decimals = decimals;
+        self.price_sol_per_token = price_sol_per_token;
+    }
+
+    /// Enregistre les soldes lus pour `account`; la première lecture sert de référence
+    ///
+    /// Seules les baisses de SOL (dépenses) et les hausses de tokens (récompenses) comptent:
+    /// un dépôt de SOL ou un transfert de tokens sortant déplace simplement la référence.
+    pub fn observe(&mut self, account: Pubkey, lamports: u64, tokens: u64) {
+        if let Some((previous_lamports, previous_tokens)) = self.balances.insert(account, (lamports, tokens)) {
+            self.lamports_spent += previous_lamports.saturating_sub(lamports);
+            self.tokens_earned += tokens.saturating_sub(previous_tokens);
+        }
+    }
+
+    pub fn summary(&self) -> LedgerSummary {
+        let tokens_earned = self.tokens_earned as f64 / 10f64.powi(self.decimals.into());
+        let sol_spent = self.lamports_spent as f64 / LAMPORTS_PER_SOL as f64;
+        LedgerSummary {
+            tokens_earned,
+            sol_spent,
+            net_profit_sol: self.price_sol_per_token.map(|price| tokens_earned * price - sol_spent),
+        }
+    }
+}
+
+impl fmt::Display for Ledger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "earned {} tokens, spent {}",
+            chain::format_tokens(self.tokens_earned, self.decimals),
+            chain::format_sol(self.lamports_spent)
+        )?;
+        match (self.price_sol_per_token, self.summary().net_profit_sol) {
+            (Some(price), Some(net)) => write!(f, ", net {:+.9} SOL at {} SOL/token", net, price),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_net_profit_from_balance_deltas() {
+        let (miner, other) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut ledger = Ledger::default();
+        ledger.configure(6, Some(0.002));
+
+        // Références au démarrage, puis deux blocs de 5 tokens à 0.001 SOL de frais chacun
+        ledger.observe(miner, 1_000_000_000, 0);
+        ledger.observe(other, 500_000_000, 3_000_000);
+        ledger.observe(miner, 999_000_000, 5_000_000);
+        ledger.observe(other, 499_000_000, 8_000_000);
+        // Dépôt de SOL et transfert de tokens sortant: ni gain ni dépense
+        ledger.observe(miner, 2_000_000_000, 1_000_000);
+
+        let summary = ledger.summary();
+        assert_eq!(summary.tokens_earned, 10.0);
+        assert_eq!(summary.sol_spent, 0.002);
+        assert_eq!(summary.net_profit_sol, Some(10.0 * 0.002 - 0.002));
+        assert_eq!(ledger.to_string(), "earned 10 tokens, spent 0.002000000 SOL, net +0.018000000 SOL at 0.002 SOL/token");
+
+        // Sans prix: pas de profit net
+        ledger.configure(6, None);
+        assert_eq!(ledger.summary().net_profit_sol, None);
+        assert_eq!(ledger.to_string(), "earned 10 tokens, spent 0.002000000 SOL");
+    }
+}
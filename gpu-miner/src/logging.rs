@@ -0,0 +1,188 @@
+//! Initialisation du logger à partir de -q/-v, RUST_LOG restant prioritaire
+
+use clap::ValueEnum;
+use log::LevelFilter;
+use std::borrow::Cow;
+use std::io::{IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// Décoration de la sortie terminal: couleurs ANSI, emoji et cadres
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ColorMode {
+    /// Décorée si stdout est un terminal et que NO_COLOR n'est pas défini
+    Auto,
+    Always,
+    /// Texte brut (logs CI, sortie redirigée)
+    Never,
+}
+
+impl ColorMode {
+    /// `no_color`: NO_COLOR défini et non vide, `tty`: stdout est un terminal
+    pub fn decorated(self, no_color: bool, tty: bool) -> bool {
+        match self {
+            ColorMode::Auto => tty && !no_color,
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+        }
+    }
+
+    /// Résout `auto` avec l'environnement du processus
+    pub fn resolve(self) -> bool {
+        let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+        self.decorated(no_color, std::io::stdout().is_terminal())
+    }
+}
+
+/// Décoration choisie au démarrage, lue par les sorties texte
+static DECORATED: AtomicBool = AtomicBool::new(true);
+
+/// Retire la décoration de `text` si la sortie est en texte brut
+pub fn decorate(text: &str) -> Cow<'_, str> {
+    if DECORATED.load(Ordering::Relaxed) {
+        Cow::Borrowed(text)
+    } else {
+        Cow::Owned(plain(text))
+    }
+}
+
+/// Texte sans séquences ANSI ni emoji, cadres redessinés en ASCII
+pub fn plain(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            // CSI: ESC [ paramètres... lettre finale
+            '\u{1b}' => {
+                if chars.next_if_eq(&'[').is_some() {
+                    while chars.next().is_some_and(|c| !c.is_ascii_alphabetic()) {}
+                }
+            }
+            '═' | '─' => out.push('='),
+            '║' | '│' => out.push('|'),
+            '╔' | '╗' | '╚' | '╝' | '┌' | '┐' | '└' | '┘' => out.push('+'),
+            c if is_emoji(c) => {
+                // L'espacement qui suivait l'emoji n'a plus lieu d'être
+                while chars.next_if(|c| *c == ' ' || *c == '\u{fe0f}').is_some() {}
+            }
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Pictogrammes utilisés par les logs et rapports (✓, ⚠️, 📊, ...)
+fn is_emoji(c: char) -> bool {
+    matches!(c as u32, 0x2190..=0x21ff | 0x2300..=0x23ff | 0x2600..=0x27bf | 0x2b00..=0x2bff | 0xfe0f | 0x1f000..=0x1faff)
+}
+
+/// Niveau de log correspondant aux flags: -q => error, défaut => warn, -v => info, -vv => debug, -vvv => trace
+pub fn level(quiet: bool, verbose: u8) -> LevelFilter {
+    if quiet {
+        return LevelFilter::Error;
+    }
+
+    match verbose {
+        0 => LevelFilter::Warn,
+        1 => LevelFilter::Info,
+        2 => LevelFilter::Debug,
+        _ => LevelFilter::Trace,
+    }
+}
+
+/// Initialise env_logger; si RUST_LOG est défini, il remplace le niveau des flags
+pub fn init(quiet: bool, verbose: u8, color: ColorMode) {
+    let decorated = color.resolve();
+    DECORATED.store(decorated, Ordering::Relaxed);
+
+    let mut builder = env_logger::Builder::new();
+    builder.filter_level(level(quiet, verbose)).parse_default_env();
+    if decorated {
+        builder.write_style(env_logger::WriteStyle::Always);
+    } else {
+        builder.write_style(env_logger::WriteStyle::Never).format(|buf, record| {
+            writeln!(buf, "[{} {:<5} {}] {}", buf.timestamp(), record.level(), record.target(), plain(&record.args().to_string()))
+        });
+    }
+    builder.init();
+}
+
+/// Limite un log répétitif à une ligne par intervalle
+///
+/// Les événements sautés sont comptés et rapportés avec la ligne suivante; un
+/// intervalle nul laisse tout passer.
+pub struct LogThrottle {
+    interval: Duration,
+    last: Option<Instant>,
+    suppressed: u64,
+}
+
+impl LogThrottle {
+    pub fn new(interval: Duration) -> Self {
+        Self { interval, last: None, suppressed: 0 }
+    }
+
+    /// `Some(n)` si l'événement doit être loggé, `n` étant le nombre d'événements sautés depuis
+    pub fn allow(&mut self, now: Instant) -> Option<u64> {
+        if self.last.is_some_and(|last| now.duration_since(last) < self.interval) {
+            self.suppressed += 1;
+            return None;
+        }
+
+        self.last = Some(now);
+        Some(std::mem::take(&mut self.suppressed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verbosity_levels() {
+        assert_eq!(level(false, 0), LevelFilter::Warn);
+        assert_eq!(level(false, 1), LevelFilter::Info);
+        assert_eq!(level(false, 2), LevelFilter::Debug);
+        assert_eq!(level(false, 5), LevelFilter::Trace);
+        assert_eq!(level(true, 3), LevelFilter::Error);
+    }
+
+    #[test]
+    fn test_never_mode_output_is_plain_text() {
+        assert!(!ColorMode::Never.decorated(false, true));
+        assert!(ColorMode::Always.decorated(true, false));
+        assert!(ColorMode::Auto.decorated(false, true));
+        assert!(!ColorMode::Auto.decorated(true, true));
+        assert!(!ColorMode::Auto.decorated(false, false));
+
+        let decorated = "\u{1b}[32m INFO\u{1b}[0m ⚙️  Backend: CPU on Test CPU\n\
+                         ╔════╗\n║ OK ║\n╚════╝\n📊 CPU Mining\n✓ [CPU] Nonce found: 42";
+        let text = plain(decorated);
+        assert!(!text.contains('\u{1b}'), "{}", text);
+        assert!(text.is_ascii(), "{}", text);
+        assert_eq!(text, " INFO Backend: CPU on Test CPU\n+====+\n| OK |\n+====+\nCPU Mining\n[CPU] Nonce found: 42");
+    }
+
+    #[test]
+    fn test_throttle_bounds_log_lines() {
+        // 10 000 solutions en une seconde, une ligne au plus toutes les 100 ms
+        let mut throttle = LogThrottle::new(Duration::from_millis(100));
+        let start = Instant::now();
+
+        let mut lines = 0;
+        let mut reported = 0;
+        for i in 0..10_000u64 {
+            if let Some(skipped) = throttle.allow(start + Duration::from_micros(i * 100)) {
+                lines += 1;
+                reported += skipped + 1;
+            }
+        }
+        reported += throttle.suppressed;
+
+        assert!(lines <= 11, "{} lines", lines);
+        assert_eq!(reported, 10_000);
+
+        let mut unthrottled = LogThrottle::new(Duration::ZERO);
+        assert!((0..100).all(|_| unthrottled.allow(start) == Some(0)));
+    }
+}
@@ -0,0 +1,107 @@
+//! Départ de la recherche propre à chaque machine (`--machine-offset`)
+//!
+//! Sans coordination, des machines qui partent toutes du nonce 0 refont le même travail.
+//! Le point de départ est dérivé du hostname et de l'adresse MAC, puis du challenge: chaque
+//! machine explore une autre région, et en change à chaque challenge.
+
+use crate::pow::ShaImpl;
+use std::collections::hash_map::RandomState;
+use std::fs;
+use std::hash::BuildHasher;
+use std::ops::Range;
+use std::sync::OnceLock;
+
+const SYSFS_NET: &str = "/sys/class/net";
+
+/// Identité de la machine, lue une fois par processus
+pub fn machine_id() -> Option<&'static str> {
+    static MACHINE_ID: OnceLock<Option<String>> = OnceLock::new();
+    MACHINE_ID
+        .get_or_init(|| {
+            let id = read_machine_id();
+            match &id {
+                Some(id) => log::info!("   Machine offset: nonce search start derived from {}", id),
+                None => log::warn!("   Machine offset: no hostname or MAC address found, using random starts"),
+            }
+            id
+        })
+        .as_deref()
+}
+
+/// Hostname et plus petite adresse MAC hors loopback; None si aucun des deux n'est lisible
+fn read_machine_id() -> Option<String> {
+    let hostname = fs::read_to_string("/proc/sys/kernel/hostname")
+        .ok()
+        .or_else(|| std::env::var("HOSTNAME").ok())
+        .map(|hostname| hostname.trim().to_string())
+        .filter(|hostname| !hostname.is_empty() && hostname != "localhost");
+    let mac = fs::read_dir(SYSFS_NET).ok().and_then(|entries| {
+        entries
+            .filter_map(|entry| fs::read_to_string(entry.ok()?.path().join("address")).ok())
+            .map(|address| address.trim().to_string())
+            .filter(|address| !address.is_empty() && address != "00:00:00:00:00:00")
+            .min()
+    });
+
+    match (hostname, mac) {
+        (None, None) => None,
+        (hostname, mac) => Some(format!("{}/{}", hostname.unwrap_or_default(), mac.unwrap_or_default())),
+    }
+}
+
+/// Plage `nonces` commencée au départ de `machine_id` pour `challenge` (aléatoire sans identité),
+/// à parcourir dans l'ordre: du départ à la fin, puis du début au départ
+///
+/// Le départ tombe dans la première moitié de la plage: chaque machine garde au moins la moitié
+/// des nonces devant elle, et une plage plafonnée (`--max-onchain-nonce`) reste couverte en entier.
+pub fn offset_range(machine_id: Option<&str>, challenge: &[u8; 32], nonces: Range<u128>) -> Vec<Range<u128>> {
+    let seed = match machine_id {
+        Some(id) => {
+            let digest = ShaImpl::default().digest(&[id.as_bytes(), challenge].concat());
+            u64::from_le_bytes(digest[..8].try_into().unwrap())
+        }
+        None => RandomState::new().hash_one(challenge),
+    };
+    let span = (nonces.end.saturating_sub(nonces.start) / 2).max(1);
+    let offset = nonces.start + seed as u128 % span;
+    [offset..nonces.end, nonces.start..offset].into_iter().filter(|range| !range.is_empty()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_machine_ids_get_different_offsets() {
+        let challenge = [7u8; 32];
+        let first = offset_range(Some("rig-01/3c:ec:ef:00:00:01"), &challenge, 0..u128::MAX);
+        let second = offset_range(Some("rig-02/3c:ec:ef:00:00:02"), &challenge, 0..u128::MAX);
+        assert_ne!(first[0].start, second[0].start);
+        assert_eq!(first[0].end, u128::MAX);
+
+        // Déterministe pour une machine et un challenge, change avec le challenge
+        assert_eq!(offset_range(Some("rig-01/3c:ec:ef:00:00:01"), &challenge, 0..u128::MAX), first);
+        assert_ne!(offset_range(Some("rig-01/3c:ec:ef:00:00:01"), &[8u8; 32], 0..u128::MAX)[0].start, first[0].start);
+
+        // Plage plafonnée (--max-onchain-nonce): départ dans sa première moitié
+        for id in [Some("rig-01/"), Some("rig-02/"), None] {
+            let ranges = offset_range(id, &challenge, 0..256);
+            assert!(ranges[0].start < 128 && ranges[0].end == 256, "{:?}", ranges);
+        }
+    }
+
+    #[test]
+    fn test_offset_range_wraps_to_the_start() {
+        // Plafond bas: les nonces avant le départ sont parcourus après la fin
+        for challenge in [[1u8; 32], [2u8; 32], [3u8; 32]] {
+            let ranges = offset_range(Some("rig-01/"), &challenge, 10..20);
+            assert_eq!(ranges[0].end, 20, "{:?}", ranges);
+            assert_eq!(ranges.get(1).cloned().unwrap_or(10..10), 10..ranges[0].start, "{:?}", ranges);
+            assert_eq!(ranges.iter().map(|range| range.end - range.start).sum::<u128>(), 10);
+        }
+
+        // Départ au début de la plage: pas de second morceau vide
+        let ranges = offset_range(Some("rig-01/"), &[1u8; 32], 5..6);
+        assert_eq!((ranges.len(), &ranges[0]), (1, &(5..6)));
+    }
+}
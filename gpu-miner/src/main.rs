@@ -1,13 +1,42 @@
 //! Mineur PoW haute performance pour Solana
 //! Supporte CPU, CUDA et OpenCL
 
-use clap::Parser;
-use log::{info, warn, error};
-use std::time::Instant;
+use anyhow::Context;
+use clap::{Parser, Subcommand};
+use anchor_client::solana_sdk::native_token::LAMPORTS_PER_SOL;
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use log::{debug, error, info, warn};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
+mod breaker;
+mod chain;
 mod config;
+#[cfg(test)]
+mod conformance;
+mod dedup;
+mod exit;
+mod health;
+mod jito;
+mod ledger;
+mod logging;
+mod machine_offset;
 mod miner;
+mod numa;
+mod mock_chain;
+mod poll;
 mod pow;
+mod power;
+mod rate_limit;
+mod report;
+mod rpc_pool;
+mod signer;
+mod solution_fifo;
+mod solution_log;
+mod stats;
+mod tasks;
 
 #[cfg(feature = "cuda")]
 mod cuda_miner;
@@ -15,32 +44,127 @@ mod cuda_miner;
 #[cfg(feature = "opencl")]
 mod opencl_miner;
 
-use miner::MinerBackend;
+use miner::{MinerBackend, MiningControl};
+use chain::Chain;
+use config::MinerConfig;
+use exit::Failure;
+use pow::{ChallengeDerivation, CompareMode, HashAlgo, ShaImpl};
+use report::OutputFormat;
+use stats::MiningStats;
+
+/// Délai avant de réessayer après l'échec d'une soumission
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// Pause après un nonce déjà soumis, le temps que le RPC voie le nouveau challenge
+const DUPLICATE_RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// Au-delà, le réseau est probablement bloqué ou peu actif
+const STALE_BLOCK_WARN_SECS: i64 = 600;
+
+/// Durée de chaque mesure de `--cpu-only-verify` et `--thread-scaling` (sans `--time`)
+const VERIFY_BENCH_DURATION: Duration = Duration::from_secs(2);
+
+/// Device OpenCL à utiliser sans --device (ordonnanceurs de conteneurs)
+const OPENCL_DEVICE_ENV: &str = "POW_OPENCL_DEVICE";
+
+/// Hashes mesurés en benchmark à difficulté 0 ou 1 (sans `--hash-budget`)
+const TRIVIAL_DIFFICULTY_HASHES: u64 = 1 << 24;
+
+/// Backends compilés dans ce binaire
+const COMPILED_BACKENDS: &[&str] = &[
+    "cpu",
+    #[cfg(feature = "cuda")]
+    "cuda",
+    #[cfg(feature = "opencl")]
+    "opencl",
+];
 
 #[derive(Parser)]
 #[command(name = "pow-miner")]
 #[command(about = "High-performance PoW miner for Solana", long_about = None)]
 struct Cli {
-    /// Backend à utiliser: auto, cpu, cuda, opencl
+    /// Backend à utiliser: auto, cpu, cuda, opencl, all (tous en parallèle)
+    /// ou une combinaison comme cpu+cuda; mock simule --simulate-hashrate (tests)
     #[arg(short, long, default_value = "auto")]
     backend: String,
 
-    /// Nombre de threads CPU (si backend=cpu)
+    /// Hashrate simulé du backend mock, en H/s: chaque solution arrive après le temps attendu
+    /// pour sa difficulté
+    #[arg(long, value_name = "H/S", default_value = "1000000", value_parser = parse_hashrate)]
+    simulate_hashrate: f64,
+
+    /// Échec d'init d'un GPU demandé par --backend (cuda, opencl): erreur (code 3) au lieu
+    /// du repli sur le CPU; le mode auto se replie toujours
+    #[arg(long)]
+    no_fallback: bool,
+
+    /// Ordre d'essai des backends en mode auto: le premier qui s'initialise est retenu
+    #[arg(long, value_delimiter = ',', default_value = "cuda,opencl,cpu", value_parser = ["cuda", "opencl", "cpu"])]
+    backend_priority: Vec<String>,
+
+    /// Nombre de threads CPU (si backend=cpu) [défaut: tous les cœurs; 0: tous sauf un]
     #[arg(short, long)]
     threads: Option<usize>,
 
-    /// ID du device GPU (si backend=cuda/opencl)
-    #[arg(short, long, default_value = "0")]
-    device: usize,
+    /// Cœurs CPU laissés libres (au moins 1 thread): alternative à --threads
+    #[arg(long, value_name = "N", conflicts_with = "threads")]
+    leave_cores: Option<usize>,
+
+    /// ID du device GPU (si backend=cuda/opencl) [défaut: 0, ou POW_OPENCL_DEVICE pour OpenCL]
+    #[arg(short, long)]
+    device: Option<usize>,
+
+    /// Découpe chaque grille CUDA en N lancements: solution remontée plus tôt, débit un peu moindre
+    /// (laissé à 1: `cuda_config.sub_launches` de --config)
+    #[cfg(feature = "cuda")]
+    #[arg(long, default_value = "1")]
+    cuda_sub_launches: usize,
+
+    /// Règle la grille CUDA au démarrage: meilleur débit sous 100 ms par lancement
+    #[cfg(feature = "cuda")]
+    #[arg(long)]
+    gpu_auto_batch: bool,
+
+    /// config.json dont la grille CUDA (cuda_config, écrite par `tune --write-config`) est utilisée
+    #[cfg(feature = "cuda")]
+    #[arg(long, value_name = "PATH")]
+    config: Option<PathBuf>,
+
+    /// Work-items OpenCL par lancement (GPU modeste) [défaut: 262144, le maximum]
+    #[cfg(feature = "opencl")]
+    #[arg(long, value_name = "N")]
+    opencl_work_items: Option<usize>,
 
     /// Mode benchmark (ne se connecte pas au réseau)
     #[arg(long)]
     benchmark: bool,
 
-    /// Difficulté pour le benchmark
+    /// Avec --benchmark: mesure tous les backends compilés et les compare
+    #[arg(long, requires = "benchmark")]
+    all_backends: bool,
+
+    /// Avec --benchmark: mine pendant cette durée (ex: 30, 10s, 2m) contre un target
+    /// inatteignable et rapporte le hashrate réel, sans chercher de solution
+    #[arg(long, requires = "benchmark", value_parser = parse_duration)]
+    time: Option<Duration>,
+
+    /// Avec --benchmark: débit brut de pow::verify_nonce (1 thread puis --threads), comparé au CPU miner
+    #[arg(long, requires = "benchmark", conflicts_with = "all_backends")]
+    cpu_only_verify: bool,
+
+    /// Avec --benchmark: hashrate du CPU miner à 1, 2, 4... --threads threads (target inatteignable),
+    /// pour voir où le passage à l'échelle décroche
+    #[arg(long, requires = "benchmark", conflicts_with_all = ["all_backends", "cpu_only_verify"])]
+    thread_scaling: bool,
+
+    /// Difficulté pour le benchmark et --mock-chain
     #[arg(long, default_value = "1000000")]
     difficulty: u128,
 
+    /// Target explicite (hex, 16 ou 32 bytes), prioritaire sur la difficulté (benchmark et mining)
+    #[arg(long, value_parser = parse_target)]
+    target: Option<u128>,
+
     /// Challenge (hex) pour le benchmark
     #[arg(long)]
     challenge: Option<String>,
@@ -49,13 +173,92 @@ struct Cli {
     #[arg(long, default_value = "0")]
     block_number: u64,
 
-    /// RPC URL
-    #[arg(long, default_value = "http://localhost:8899")]
-    rpc: String,
+    /// Force le block number du préimage en mode mining, au lieu de
+    /// `blocks_mined` lu on-chain (tests uniquement: les preuves seront rejetées)
+    #[arg(long)]
+    block_number_override: Option<u64>,
+
+    /// RPC URL, ou liste séparée par des virgules (bascule sur la suivante en cas d'erreurs répétées)
+    #[arg(long, alias = "json-rpc-url-list", value_delimiter = ',', default_value = "http://localhost:8899")]
+    rpc: Vec<String>,
+
+    /// RPC dédié(s) à l'envoi des transactions (blockhash et soumission), --rpc restant pour le polling
+    #[arg(long, value_delimiter = ',')]
+    submit_rpc: Vec<String>,
+
+    /// Appels RPC par seconde au maximum; les lectures d'état attendent, les soumissions passent en priorité
+    #[arg(long, value_name = "PER_SEC", value_parser = parse_rate)]
+    rpc_rate_limit: Option<f64>,
+
+    /// Plus grand nonce accepté par le programme (décimal, 0x..., ou `u64`): CPU et GPU ne
+    /// cherchent qu'en dessous (benchmark et mining)
+    #[arg(long, value_name = "MAX", value_parser = parse_max_nonce)]
+    max_onchain_nonce: Option<u128>,
+
+    /// Hashes au maximum sur un même challenge: au-delà, il est abandonné (d'autres l'auront
+    /// probablement résolu) et le mineur attend le suivant
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    hash_budget: Option<u64>,
+
+    /// Départ de la recherche dérivé du hostname, de l'adresse MAC et du challenge: chaque
+    /// machine explore une autre région sans coordination (aléatoire sans identité lisible)
+    #[arg(long)]
+    machine_offset: bool,
+
+    /// Memo joint à chaque soumission (ex: identifiant de rig), tronqué à 256 bytes
+    #[arg(long)]
+    memo: Option<String>,
+
+    /// Nonce account (autorité: le keypair mineur) utilisé à la place du blockhash récent:
+    /// une soumission en timeout est renvoyée à l'identique, sans risque de double envoi
+    #[arg(long, value_name = "ACCOUNT")]
+    durable_nonce: Option<String>,
+
+    /// Envoie les preuves sans attendre leur confirmation (vérifiée en tâche de fond):
+    /// le hashing reprend aussitôt, au prix de preuves perdues sans nouvel essai
+    #[arg(long, conflicts_with = "durable_nonce")]
+    no_confirm: bool,
+
+    /// Vérifie le blockhash auprès du RPC avant de signer et le relit s'il a déjà expiré
+    /// (un appel de plus par soumission, meilleur taux d'atterrissage sous charge)
+    #[arg(long, conflicts_with = "durable_nonce")]
+    fresh_blockhash: bool,
+
+    /// Block engine Jito (ex: https://mainnet.block-engine.jito.wtf): les preuves partent en
+    /// bundle avec un tip, au lieu du RPC
+    #[arg(long, value_name = "URL", conflicts_with = "durable_nonce")]
+    jito_block_engine: Option<String>,
+
+    /// Tip payé au validateur par bundle Jito (lamports)
+    #[arg(long, value_name = "LAMPORTS", default_value = "10000", requires = "jito_block_engine")]
+    jito_tip: u64,
+
+    /// Échecs de soumission consécutifs avant d'arrêter de soumettre (disjoncteur)
+    #[arg(long, default_value = "5", value_parser = clap::value_parser!(u32).range(1..))]
+    max_submit_failures: u32,
+
+    /// Disjoncteur ouvert: reprend après cette pause (ex: 10m) au lieu de quitter avec le code 6
+    #[arg(long, value_parser = parse_duration)]
+    submit_cooldown: Option<Duration>,
 
-    /// Chemin vers le keypair du mineur
+    /// Mine le challenge suivant, prévu à partir de la preuve, pendant la confirmation de
+    /// celle-ci; le travail est abandonné si le challenge réel diffère
+    #[arg(long)]
+    speculative: bool,
+
+    /// Mine jusqu'à N blocs d'affilée sur les challenges prévus, puis les soumet en une seule
+    /// transaction pour amortir les frais (1: une preuve par transaction)
+    #[arg(long, value_name = "N", default_value = "1", value_parser = clap::value_parser!(u32).range(1..), conflicts_with = "speculative")]
+    batch_submit: u32,
+
+    /// Prix d'un token en SOL: ajoute le profit net de la session (tokens valorisés - SOL dépensé)
+    #[arg(long, value_name = "PRICE", value_parser = parse_price)]
+    price_sol_per_token: Option<f64>,
+
+    /// Keypair du mineur (fichier ou dossier de *.json); répéter l'option pour
+    /// alterner les soumissions entre plusieurs keypairs
     #[arg(short, long, default_value = "~/.config/solana/id.json")]
-    keypair: String,
+    keypair: Vec<String>,
 
     /// Program ID du protocole PoW
     #[arg(long)]
@@ -65,201 +268,2580 @@ struct Cli {
     #[arg(long)]
     mint: Option<String>,
 
+    /// Seed du PDA pow_config (forks du programme)
+    #[arg(long, default_value = config::POW_CONFIG_SEED, value_parser = parse_seed)]
+    pow_config_seed: String,
+
+    /// Seed du PDA fee_vault
+    #[arg(long, default_value = config::FEE_VAULT_SEED, value_parser = parse_seed)]
+    fee_vault_seed: String,
+
+    /// Seed des PDAs miner_stats
+    #[arg(long, default_value = config::MINER_STATS_SEED, value_parser = parse_seed)]
+    miner_stats_seed: String,
+
+    /// Seed du PDA mint authority
+    #[arg(long, default_value = config::MINT_AUTHORITY_SEED, value_parser = parse_seed)]
+    mint_authority_seed: String,
+
+    /// Continue malgré un --mint différent de celui du PoW config on-chain (avertissement seulement)
+    #[arg(long)]
+    force: bool,
+
     /// Miner public key (hex, 32 bytes) for benchmark mode
     #[arg(long)]
     miner_pubkey: Option<String>,
+
+    /// Double SHA256 (style Bitcoin): SHA256(SHA256(préimage))
+    #[arg(long)]
+    double_sha: bool,
+
+    /// Comparaison du hash au target: 128 bits bas (programme déployé) ou digest entier
+    #[arg(long, value_enum, default_value = "low-128")]
+    compare_mode: CompareMode,
+
+    /// Implémentation SHA256 du backend CPU (asm avec --features sha-asm)
+    #[arg(long, value_enum, default_value = "sha2")]
+    sha_impl: ShaImpl,
+
+    /// Épingle chaque thread CPU sur un nœud NUMA et y alloue son working set (--features numa);
+    /// avec plusieurs GPU, le thread de chacun va sur le nœud de sa racine PCIe
+    #[arg(long)]
+    affinity_numa: bool,
+
+    /// Challenge du préimage: valeur on-chain brute (programme déployé) ou SHA256(graine || block_number)
+    #[arg(long, value_enum, default_value = "raw")]
+    challenge_derivation: ChallengeDerivation,
+
+    /// Format de sortie des résultats
+    #[arg(long, value_enum, default_value = "text")]
+    output: OutputFormat,
+
+    /// N'affiche que les erreurs
+    #[arg(short, long, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Couleurs, emoji et cadres: auto (terminal sans NO_COLOR), always ou never
+    #[arg(long, value_enum, default_value = "auto")]
+    color: logging::ColorMode,
+
+    /// Durée du warm-up exclu de la mesure du benchmark (ms, 0 pour désactiver)
+    #[arg(long, default_value = "200")]
+    warmup_ms: u64,
+
+    /// Puissance du GPU en watts pour calculer l'efficacité (MH/J), sinon lue via NVML si disponible
+    #[arg(long)]
+    gpu_watts: Option<f64>,
+
+    /// Augmente la verbosité (-v info, -vv debug, -vvv trace); RUST_LOG reste prioritaire
+    #[arg(short, long, action = clap::ArgAction::Count)]
+    verbose: u8,
+
+    /// Ajoute une ligne JSON par nonce trouvé (et l'issue de sa soumission) à ce fichier
+    #[arg(long)]
+    solution_log: Option<PathBuf>,
+
+    /// Écrit chaque nonce trouvé dans cette FIFO (ligne JSON) au lieu de le soumettre: le
+    /// lecteur (continuous-gpu-miner.ts) se charge de la soumission
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["batch_submit", "speculative"])]
+    solution_fifo: Option<PathBuf>,
+
+    /// Au plus une ligne de log par round de mining sur cet intervalle (ex: 1s, 500ms; 0 pour tout logger)
+    ///
+    /// Les rounds sautés restent comptés dans les statistiques et le --solution-log.
+    #[arg(long, default_value = "1s", value_parser = parse_duration)]
+    log_interval: Duration,
+
+    /// Intervalle de relecture de l'état on-chain quand il n'y a rien à miner (pause, erreur RPC)
+    #[arg(long, default_value = "5s", value_parser = parse_duration)]
+    poll_interval: Duration,
+
+    /// Écart aléatoire maximal autour de --poll-interval, pour désynchroniser les mineurs d'un même RPC
+    #[arg(long, default_value = "1s", value_parser = parse_duration)]
+    poll_jitter: Duration,
+
+    /// Sert les sondes /healthz et /readyz sur ce port (Kubernetes)
+    #[arg(long, value_name = "PORT")]
+    health_port: Option<u16>,
+
+    /// /healthz répond 503 sans nouveau hash depuis cette durée
+    #[arg(long, default_value = "60s", requires = "health_port", value_parser = parse_duration)]
+    health_max_idle: Duration,
+
+    /// Mine contre un protocole simulé en mémoire (sans RPC ni keypair)
+    #[arg(long)]
+    mock_chain: bool,
+
+    /// Soak test: mine pendant la durée donnée (ex: 30s, 10m, 2h) puis écrit un résumé JSON
+    #[arg(long, requires = "mock_chain", value_parser = parse_duration)]
+    soak: Option<Duration>,
+
+    /// Lit le challenge courant sur la chaîne (ou --mock-chain), l'affiche et mine une seule fois
+    #[arg(long, conflicts_with_all = ["benchmark", "soak"])]
+    oneshot: bool,
+
+    /// Avec --oneshot: soumet le nonce trouvé
+    #[arg(long, requires = "oneshot")]
+    submit: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>,
 }
 
-#[tokio::main]
-async fn main() -> anyhow::Result<()> {
-    env_logger::init();
+#[derive(Subcommand)]
+enum Command {
+    /// Convertit une difficulté en target et inversement
+    Difficulty {
+        /// Difficulté à convertir en target
+        #[arg(long, conflicts_with = "target", required_unless_present = "target")]
+        difficulty: Option<u128>,
+
+        /// Target hex (16 ou 32 bytes, comme --target) à convertir en difficulté
+        #[arg(long)]
+        target: Option<String>,
+
+        /// Affiche aussi le target équivalent sur 256 bits
+        #[arg(long)]
+        full: bool,
+    },
+
+    /// Hashes nécessaires pour trouver une solution avec une confiance donnée, et ETA
+    Plan {
+        #[arg(long)]
+        difficulty: u128,
+
+        /// Probabilité voulue de trouver au moins une solution (0 à 1)
+        #[arg(long, default_value = "0.95")]
+        confidence: f64,
+
+        /// Hashrate en H/s pour estimer la durée
+        #[arg(long)]
+        hashrate: Option<f64>,
+    },
+
+    /// Affiche l'état du protocole (difficulté, blocs, challenge...) puis quitte
+    ///
+    /// Lecture seule: seuls --rpc et --program-id (ou --mock-chain) sont utilisés.
+    Status,
+
+    /// Calcule le hash d'un nonce, sans miner (`--double-sha` avant la sous-commande)
+    Hash {
+        /// Challenge (hex, 32 bytes)
+        #[arg(long)]
+        challenge: String,
+
+        /// Pubkey du mineur (hex 32 bytes ou base58)
+        #[arg(long)]
+        pubkey: String,
+
+        #[arg(long)]
+        nonce: u128,
+
+        #[arg(long)]
+        block_number: u64,
+
+        /// Indique aussi si le hash passe sous le target de cette difficulté
+        #[arg(long)]
+        difficulty: Option<u128>,
+    },
+
+    /// Affiche `nonce hash` pour une plage de nonces, pour comparer avec une autre implémentation
+    ///
+    /// `--double-sha` et `--output json` (un objet par ligne) avant la sous-commande.
+    Dump {
+        /// Challenge (hex, 32 bytes)
+        #[arg(long)]
+        challenge: String,
+
+        /// Pubkey du mineur (hex 32 bytes ou base58)
+        #[arg(long)]
+        miner_pubkey: String,
+
+        #[arg(long)]
+        block_number: u64,
+
+        /// Premier nonce
+        #[arg(long, default_value = "0")]
+        from: u128,
+
+        /// Dernier nonce (inclus)
+        #[arg(long)]
+        to: u128,
+    },
+
+    /// Mesure le hashrate CUDA de chaque grille (threads par block x blocks) et affiche la meilleure
+    ///
+    /// `--device` et `--double-sha` avant la sous-commande.
+    Tune {
+        /// Threads par block essayés (multiples de 32)
+        #[arg(long, value_delimiter = ',', default_value = "64,128,256,512,1024")]
+        threads_per_block: Vec<usize>,
+
+        /// Nombres de blocks essayés
+        #[arg(long, value_delimiter = ',', default_value = "256,512,1024,2048,4096,8192")]
+        num_blocks: Vec<usize>,
+
+        /// Durée de mesure de chaque grille (target inatteignable)
+        #[arg(long, default_value = "2s", value_parser = parse_duration)]
+        duration: Duration,
+
+        /// Écrit la meilleure grille dans `cuda_config` de ce config.json (créé s'il n'existe pas)
+        #[arg(long, value_name = "PATH")]
+        write_config: Option<PathBuf>,
+    },
+
+    /// Rejoue les solutions d'un --solution-log: hash recalculé et comparé au target enregistré
+    Replay {
+        /// Journal JSON écrit par --solution-log
+        #[arg(long)]
+        solution_log: PathBuf,
+
+        /// Vérifie aussi chaque solution à cette difficulté (ex: difficulté actuelle on-chain)
+        #[arg(long)]
+        difficulty: Option<u128>,
+    },
+}
+
+/// Durée avec unité (ms, s, m, h); un nombre seul est en secondes
+fn parse_duration(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let split = value.find(|c: char| !c.is_ascii_digit()).unwrap_or(value.len());
+    let (amount, unit) = value.split_at(split);
+    let amount: u64 = amount.parse().map_err(|_| format!("invalid duration '{}'", value))?;
+
+    match unit {
+        "ms" => Ok(Duration::from_millis(amount)),
+        "" | "s" => Ok(Duration::from_secs(amount)),
+        "m" => Ok(Duration::from_secs(amount * 60)),
+        "h" => Ok(Duration::from_secs(amount * 3600)),
+        _ => Err(format!("unknown duration unit '{}' (expected ms, s, m or h)", unit)),
+    }
+}
+
+/// Seed de PDA: non vide et au plus `MAX_SEED_LEN` octets
+fn parse_seed(value: &str) -> Result<String, String> {
+    use anchor_client::solana_sdk::pubkey::MAX_SEED_LEN;
+    if value.is_empty() || value.len() > MAX_SEED_LEN {
+        return Err(format!("invalid seed '{}' (expected 1 to {} bytes)", value, MAX_SEED_LEN));
+    }
+    Ok(value.to_string())
+}
+
+/// Débit strictement positif (appels par seconde)
+fn parse_rate(value: &str) -> Result<f64, String> {
+    match value.trim().parse::<f64>() {
+        Ok(rate) if rate.is_finite() && rate > 0.0 => Ok(rate),
+        _ => Err(format!("invalid rate '{}' (expected a positive number of calls per second)", value)),
+    }
+}
+
+/// Hashrate strictement positif (H/s)
+fn parse_hashrate(value: &str) -> Result<f64, String> {
+    match value.trim().parse::<f64>() {
+        Ok(hashrate) if hashrate.is_finite() && hashrate > 0.0 => Ok(hashrate),
+        _ => Err(format!("invalid hashrate '{}' (expected a positive number of hashes per second)", value)),
+    }
+}
+
+/// Prix positif ou nul (SOL par token)
+fn parse_price(value: &str) -> Result<f64, String> {
+    match value.trim().parse::<f64>() {
+        Ok(price) if price.is_finite() && price >= 0.0 => Ok(price),
+        _ => Err(format!("invalid price '{}' (expected a non-negative number of SOL per token)", value)),
+    }
+}
+
+/// Nonce maximal de `--max-onchain-nonce`: décimal, hex `0x...` ou `u64`
+fn parse_max_nonce(value: &str) -> Result<u128, String> {
+    let value = value.trim();
+    if value == "u64" {
+        return Ok(u64::MAX as u128);
+    }
+    let parsed = match value.strip_prefix("0x") {
+        Some(hex) => u128::from_str_radix(hex, 16),
+        None => value.parse(),
+    };
+    parsed.map_err(|e| format!("invalid nonce '{}': {}", value, e))
+}
+
+/// Nonces cherchés: tous, ou jusqu'à `--max-onchain-nonce` inclus
+fn nonce_range(cli: &Cli) -> std::ops::Range<u128> {
+    0..cli.max_onchain_nonce.map_or(u128::MAX, |max| max.saturating_add(1))
+}
+
+/// Nonces cherchés sur `challenge`, plages à parcourir dans l'ordre: `nonce_range`, commencée
+/// au départ de la machine avec `--machine-offset` puis reprise au début
+fn search_range(cli: &Cli, challenge: &[u8; 32]) -> Vec<std::ops::Range<u128>> {
+    if cli.machine_offset {
+        machine_offset::offset_range(machine_offset::machine_id(), challenge, nonce_range(cli))
+    } else {
+        vec![nonce_range(cli)]
+    }
+}
 
+/// Target hex de `--target` (voir `pow::parse_target`)
+fn parse_target(value: &str) -> Result<u128, String> {
+    pow::parse_target(value).map_err(|e| format!("invalid target '{}': {}", value, e))
+}
+
+#[tokio::main]
+async fn main() {
     let cli = Cli::parse();
+    logging::init(cli.quiet, cli.verbose, cli.color);
+    #[cfg(feature = "console")]
+    tasks::init_console();
+
+    let result = run(cli).await;
+    if let Err(e) = &result {
+        error!("{:#}", e);
+    }
+    std::process::exit(exit::code(&result));
+}
+
+async fn run(cli: Cli) -> anyhow::Result<()> {
+    if let Some(Command::Difficulty { difficulty, target, full }) = &cli.command {
+        return run_difficulty(*difficulty, target.as_deref(), *full);
+    }
+    if let Some(Command::Plan { difficulty, confidence, hashrate }) = &cli.command {
+        return run_plan(*difficulty, *confidence, *hashrate);
+    }
+    if let Some(Command::Status) = &cli.command {
+        return run_status(&cli).await;
+    }
+    if let Some(Command::Replay { solution_log, difficulty }) = &cli.command {
+        return run_replay(solution_log, *difficulty);
+    }
+    if let Some(Command::Tune { threads_per_block, num_blocks, duration, write_config }) = &cli.command {
+        let algo = if cli.double_sha { HashAlgo::DoubleSha256 } else { HashAlgo::Sha256 };
+        return run_tune(&cli, threads_per_block, num_blocks, *duration, write_config.as_deref(), algo);
+    }
+    if let Some(Command::Hash { challenge, pubkey, nonce, block_number, difficulty }) = &cli.command {
+        let algo = if cli.double_sha { HashAlgo::DoubleSha256 } else { HashAlgo::Sha256 };
+        return run_hash(challenge, pubkey, *nonce, *block_number, *difficulty, algo);
+    }
+    if let Some(Command::Dump { challenge, miner_pubkey, block_number, from, to }) = &cli.command {
+        let algo = if cli.double_sha { HashAlgo::DoubleSha256 } else { HashAlgo::Sha256 };
+        return run_dump(challenge, miner_pubkey, *block_number, *from..=*to, algo, cli.output);
+    }
 
     info!("🚀 PoW Miner Starting...");
     info!("   Backend: {}", cli.backend);
 
+    let hash_algo = if cli.double_sha {
+        info!("   Hash: double SHA256");
+        HashAlgo::DoubleSha256
+    } else {
+        HashAlgo::Sha256
+    };
+    // Un config.json illisible est une erreur de configuration, pas un repli sur le CPU
+    #[cfg(feature = "cuda")]
+    if let Some(path) = &cli.config {
+        let grid = cuda_grid(&cli)?;
+        info!("   CUDA grid from {}: {} threads per block x {} blocks", path.display(), grid.threads_per_block, grid.num_blocks);
+    }
+    if cli.affinity_numa {
+        numa::check().context(Failure::Config)?;
+        match numa::nodes().len() {
+            0 | 1 => warn!("   Single NUMA node: --affinity-numa has no effect"),
+            n => info!("   NUMA: CPU threads spread over {} nodes", n),
+        }
+    }
+    if let Some(target) = cli.target {
+        warn!("   Explicit --target 0x{:032x} takes precedence over the difficulty", target);
+    }
+    info!("   SHA256: {} ({})", cli.sha_impl.name(), cli.sha_impl.backend());
+    debug!("   SHA256 implementations compiled: {}", ShaImpl::available().iter().map(|sha| sha.name()).collect::<Vec<_>>().join(", "));
+
+    if cli.all_backends {
+        return run_benchmark_all(&cli, hash_algo).await;
+    }
+    if cli.cpu_only_verify {
+        return run_verify_benchmark(&cli, hash_algo);
+    }
+    if cli.thread_scaling {
+        let reporter = report::reporter(cli.output);
+        let threads = cpu_threads(&cli);
+        let points = thread_scaling(threads, hash_algo, cli.time.unwrap_or(VERIFY_BENCH_DURATION));
+        reporter.on_scaling(&miner::cpu_model(), &points);
+        return Ok(());
+    }
+
     // Créer le mineur selon le backend
     let miner: Box<dyn MinerBackend> = match cli.backend.as_str() {
         "cpu" => {
             info!("   Using CPU backend");
-            let threads = cli.threads.unwrap_or_else(num_cpus::get);
+            let threads = cpu_threads(&cli);
             info!("   Threads: {}", threads);
-            Box::new(miner::CpuMiner::new(threads))
+            Box::new(cpu_backend(&cli, hash_algo, threads))
         }
 
-        #[cfg(feature = "cuda")]
-        "cuda" => {
-            info!("   Using CUDA backend");
-            match cuda_miner::CudaMiner::new(cli.device) {
-                Ok(m) => {
-                    info!("   ✓ CUDA initialized");
-                    info!("   Device: {}", cli.device);
-                    Box::new(m)
-                }
-                Err(e) => {
-                    error!("   ✗ CUDA init failed: {}", e);
-                    warn!("   Falling back to CPU");
-                    Box::new(miner::CpuMiner::new(num_cpus::get()))
-                }
-            }
+        name @ ("cuda" | "opencl") => {
+            info!("   Using {} backend", name);
+            explicit_gpu_backend(name, try_backend(name, &cli, hash_algo), cli.no_fallback, || {
+                Box::new(cpu_backend(&cli, hash_algo, num_cpus::get()))
+            })?
         }
 
-        #[cfg(feature = "opencl")]
-        "opencl" => {
-            info!("   Using OpenCL backend");
-            match opencl_miner::OpenClMiner::new(cli.device) {
-                Ok(m) => {
-                    info!("   ✓ OpenCL initialized");
-                    Box::new(m)
-                }
-                Err(e) => {
-                    error!("   ✗ OpenCL init failed: {}", e);
-                    warn!("   Falling back to CPU");
-                    Box::new(miner::CpuMiner::new(num_cpus::get()))
-                }
-            }
+        "mock" => {
+            info!("   Using mock backend ({} H/s simulated)", cli.simulate_hashrate);
+            Box::new(mock_backend(&cli, hash_algo))
         }
 
-        "auto" | _ => {
-            info!("   Auto-detecting best backend...");
-
-            // Try CUDA first
-            #[cfg(feature = "cuda")]
-            {
-                if let Ok(m) = cuda_miner::CudaMiner::new(cli.device) {
-                    info!("   ✓ Using CUDA");
-                    Box::new(m) as Box<dyn MinerBackend>
-                } else {
-                    // Try OpenCL or fall back to CPU
-                    #[cfg(feature = "opencl")]
-                    {
-                        if let Ok(m) = opencl_miner::OpenClMiner::new(cli.device) {
-                            info!("   ✓ Using OpenCL");
-                            Box::new(m) as Box<dyn MinerBackend>
-                        } else {
-                            info!("   Using CPU (no GPU detected)");
-                            Box::new(miner::CpuMiner::new(num_cpus::get()))
-                        }
-                    }
-                    #[cfg(not(feature = "opencl"))]
-                    {
-                        info!("   Using CPU (no GPU detected)");
-                        Box::new(miner::CpuMiner::new(num_cpus::get()))
-                    }
-                }
-            }
+        "all" => composite_backend(COMPILED_BACKENDS, false, &cli, hash_algo)?,
 
-            // No CUDA feature - try OpenCL or CPU
-            #[cfg(not(feature = "cuda"))]
-            {
-                #[cfg(feature = "opencl")]
-                {
-                    if let Ok(m) = opencl_miner::OpenClMiner::new(cli.device) {
-                        info!("   ✓ Using OpenCL");
-                        Box::new(m) as Box<dyn MinerBackend>
-                    } else {
-                        info!("   Using CPU (no GPU detected)");
-                        Box::new(miner::CpuMiner::new(num_cpus::get()))
-                    }
-                }
-                #[cfg(not(feature = "opencl"))]
-                {
-                    info!("   Using CPU (no GPU detected)");
-                    Box::new(miner::CpuMiner::new(num_cpus::get()))
-                }
+        names if names.contains('+') => {
+            let names: Vec<&str> = names.split('+').map(str::trim).collect();
+            composite_backend(&names, true, &cli, hash_algo)?
+        }
+
+        other => {
+            if other != "auto" {
+                warn!("   Unknown backend '{}', auto-detecting instead", other);
             }
+            info!("   Auto-detecting best backend ({})...", cli.backend_priority.join(" → "));
+
+            let priority: Vec<&str> = cli.backend_priority.iter().map(String::as_str).collect();
+            auto_backend(&priority, |name| try_backend(name, &cli, hash_algo), || {
+                Box::new(cpu_backend(&cli, hash_algo, num_cpus::get()))
+            })
         }
     };
+    // Backend effectivement retenu, repli sur le CPU compris
+    info!("⚙️  Backend: {} on {}", miner.name(), miner.device_name());
+    check_compare_mode(miner.as_ref(), cli.compare_mode)?;
 
     // Mode benchmark
     if cli.benchmark {
-        return run_benchmark(miner, cli.difficulty, cli.challenge, cli.block_number, cli.miner_pubkey).await;
+        return run_benchmark(miner, &cli, hash_algo).await;
     }
 
     // Mode mining normal
-    run_miner(miner, &cli).await
+    run_miner(miner, &cli, hash_algo).await
 }
 
-async fn run_benchmark(
-    miner: Box<dyn MinerBackend>,
-    difficulty: u128,
-    challenge_hex: Option<String>,
-    block_number: u64,
-    miner_pubkey_hex: Option<String>,
-) -> anyhow::Result<()> {
-    info!("\n╔══════════════════════════════════════════════════════════════╗");
-    info!("║                    BENCHMARK MODE                            ║");
-    info!("╚══════════════════════════════════════════════════════════════╝\n");
+/// Refuse un mode de comparaison que le backend ne sait pas appliquer
+fn check_compare_mode(miner: &dyn MinerBackend, mode: CompareMode) -> anyhow::Result<()> {
+    if !miner.supports_compare_mode(mode) {
+        return Err(anyhow::anyhow!(
+            "--compare-mode {}: the {} kernel only compares the low 128 bits of the hash, use --backend cpu",
+            mode.name(),
+            miner.name()
+        ))
+        .context(Failure::Config);
+    }
+    if mode == CompareMode::Full256 {
+        info!("   Compare mode: {}", mode.name());
+    }
+    Ok(())
+}
 
+/// Job du benchmark à partir des options --difficulty, --challenge, --block-number, --miner-pubkey
+fn benchmark_job(cli: &Cli, hash_algo: HashAlgo) -> anyhow::Result<report::BenchJob> {
     // Parse challenge from hex or use default
-    let challenge = if let Some(hex) = challenge_hex {
-        let hex = hex.trim_start_matches("0x");
-        let bytes = hex::decode(hex)
-            .map_err(|e| anyhow::anyhow!("Invalid challenge hex: {}", e))?;
-        if bytes.len() != 32 {
-            anyhow::bail!("Challenge must be 32 bytes, got {}", bytes.len());
-        }
-        let mut arr = [0u8; 32];
-        arr.copy_from_slice(&bytes);
-        arr
-    } else {
-        [0u8; 32]
+    let challenge = match &cli.challenge {
+        Some(hex) => pow::parse_hash32(hex).context("Invalid --challenge").context(Failure::Config)?,
+        None => [0u8; 32],
     };
 
     // Parse miner pubkey from hex or use default
-    let miner_pubkey = if let Some(hex) = miner_pubkey_hex {
-        let hex = hex.trim_start_matches("0x");
-        let bytes = hex::decode(hex)
-            .map_err(|e| anyhow::anyhow!("Invalid miner_pubkey hex: {}", e))?;
-        if bytes.len() != 32 {
-            anyhow::bail!("Miner pubkey must be 32 bytes, got {}", bytes.len());
-        }
-        let mut arr = [0u8; 32];
-        arr.copy_from_slice(&bytes);
-        arr
-    } else {
-        [0u8; 32] // Default pubkey for testing
+    let miner_pubkey = match &cli.miner_pubkey {
+        Some(hex) => pow::parse_hash32(hex).context("Invalid --miner-pubkey").context(Failure::Config)?,
+        None => [0u8; 32], // Default pubkey for testing
     };
 
-    let target = u128::MAX / difficulty;
+    Ok(report::BenchJob {
+        label: None,
+        challenge: cli.challenge_derivation.derive(&challenge, cli.block_number),
+        miner_pubkey,
+        block_number: cli.block_number,
+        difficulty: cli.difficulty,
+        target: cli.target,
+        hash_algo,
+        compare_mode: cli.compare_mode,
+    })
+}
+
+async fn run_benchmark(miner: Box<dyn MinerBackend>, cli: &Cli, hash_algo: HashAlgo) -> anyhow::Result<()> {
+    let reporter = report::reporter(cli.output);
+
+    info!("\n╔══════════════════════════════════════════════════════════════╗");
+    info!("║                    BENCHMARK MODE                            ║");
+    info!("╚══════════════════════════════════════════════════════════════╝\n");
 
-    info!("Difficulty: {}", difficulty);
+    let job = benchmark_job(cli, hash_algo)?;
+    let (challenge, miner_pubkey, block_number, target) = (job.challenge, job.miner_pubkey, job.block_number, job.target());
+
+    info!("Backend: {}", miner.name());
+    match job.target {
+        Some(target) => info!("Target: 0x{:032x}", target),
+        None => info!("Difficulty: {}", job.difficulty),
+    }
     info!("Block number: {}", block_number);
     info!("Challenge: {}", hex::encode(&challenge[..8]));
     info!("Miner: {}", hex::encode(&miner_pubkey[..8]));
     info!("Target: {:032x}", target);
+
+    // Efficacité énergétique uniquement pour les backends GPU
+    let power = if miner.is_gpu() {
+        power::PowerSource::detect(cli.gpu_watts, gpu_device(cli))
+    } else {
+        None
+    };
+
+    let backend = report::BackendInfo::of(miner.as_ref());
+    let miner: Arc<dyn MinerBackend> = Arc::from(miner);
+
+    // Warm-up hors chrono: pool de threads, compilation du kernel, caches
+    let warmup = Duration::from_millis(cli.warmup_ms);
+    if !warmup.is_zero() {
+        info!("Warming up for {:?}...", warmup);
+        let warm_miner = miner.clone();
+        let (hashes, elapsed) = tasks::spawn_blocking("warm-up", move || miner::warm_up(warm_miner.as_ref(), warmup)).await?;
+        report::report_warmup(reporter.as_ref(), &backend, hashes, elapsed);
+    }
+
+    if let Some(duration) = cli.time {
+        info!("\n⛏️  Hashing for {:?}...\n", duration);
+        let (hashes, elapsed) = timed_benchmark(miner, &job, duration, stop_on_ctrl_c()).await?;
+        report::report_timed(reporter.as_ref(), &backend, hashes, elapsed, power.as_ref());
+        return Ok(());
+    }
+
+    if let Some(count) = trivial_difficulty_hashes(&job, cli) {
+        info!("\n⛏️  Difficulty {} accepts every hash: hashing {} nonces instead...\n", job.difficulty, count);
+        let (hashes, elapsed) = counted_benchmark(miner, &job, count, stop_on_ctrl_c()).await?;
+        report::report_timed(reporter.as_ref(), &backend, hashes, elapsed, power.as_ref());
+        return Ok(());
+    }
+
     info!("\n⛏️  Mining...\n");
 
+    let control = stop_on_ctrl_c().with_hash_budget(cli.hash_budget);
     let start = Instant::now();
 
-    match miner.mine(&challenge, &miner_pubkey, block_number, target, u128::MAX) {
-        Some(nonce) => {
-            let elapsed = start.elapsed();
-            let hashrate = (nonce as f64) / elapsed.as_secs_f64();
+    let nonce = miner::mine_async(miner, challenge, miner_pubkey, block_number, target, nonce_range(cli), control.clone()).await?;
+    report::report_result(reporter.as_ref(), &backend, &job, nonce, start.elapsed(), &control, power.as_ref());
+
+    // Limite (plage ou budget) atteinte sans solution; un arrêt par Ctrl-C reste un arrêt propre
+    if nonce.is_none() && (control.is_running() || control.budget_exhausted()) {
+        return Err(Failure::NoSolution.into());
+    }
+
+    Ok(())
+}
+
+/// Mesure d'un backend par `--benchmark --all-backends`
+enum BackendRun {
+    Measured { backend: String, elapsed: Duration, hashrate_hs: f64 },
+    Skipped { backend: &'static str, reason: String },
+}
+
+/// Mine le job du benchmark avec chaque backend compilé, l'un après l'autre
+async fn compare_backends(cli: &Cli, hash_algo: HashAlgo, reporter: &dyn report::Reporter, control: &MiningControl) -> anyhow::Result<Vec<BackendRun>> {
+    let job = benchmark_job(cli, hash_algo)?;
+    let warmup = Duration::from_millis(cli.warmup_ms);
+    let mut runs = Vec::new();
+
+    for &name in COMPILED_BACKENDS {
+        if !control.is_running() {
+            break;
+        }
+
+        let miner: Arc<dyn MinerBackend> = match try_backend(name, cli, hash_algo) {
+            Ok(miner) => Arc::from(miner),
+            Err(e) => {
+                warn!("Skipping {}: {:#}", name, e);
+                runs.push(BackendRun::Skipped { backend: name, reason: format!("{:#}", e) });
+                continue;
+            }
+        };
+        let backend = report::BackendInfo::of(miner.as_ref());
+        info!("⛏️  Benchmarking {} ({})...", backend.name, backend.device);
 
-            info!("✓ Nonce found: {}", nonce);
-            info!("  Time: {:?}", elapsed);
-            info!("  Iterations: {}", nonce);
-            info!("  Hashrate: {:.2} MH/s", hashrate / 1_000_000.0);
+        if !warmup.is_zero() {
+            let warm_miner = miner.clone();
+            tasks::spawn_blocking("warm-up", move || miner::warm_up(warm_miner.as_ref(), warmup)).await?;
+        }
 
-            // Verify
-            let hash = pow::compute_hash(&challenge, &miner_pubkey, nonce, block_number);
-            let hash_value = u128::from_le_bytes(hash[..16].try_into().unwrap());
-            info!("  Hash: {:032x}", hash_value);
-            info!("  Valid: {}", hash_value < target);
+        if let Some(count) = trivial_difficulty_hashes(&job, cli) {
+            let (hashes, elapsed) = counted_benchmark(miner, &job, count, control.round()).await?;
+            report::report_timed(reporter, &backend, hashes, elapsed, None);
+            runs.push(BackendRun::Measured { backend: backend.name, elapsed, hashrate_hs: hashes as f64 / elapsed.as_secs_f64() });
+            continue;
         }
-        None => {
-            info!("✗ No nonce found (reached limit)");
+
+        let round = control.round();
+        let start = Instant::now();
+        let nonce = miner::mine_async(miner.clone(), job.challenge, job.miner_pubkey, job.block_number, job.target(), nonce_range(cli), round.clone()).await?;
+        let elapsed = start.elapsed();
+        report::report_result(reporter, &backend, &job, nonce, elapsed, &round, None);
+
+        let hashrate_hs = round.hashes() as f64 / elapsed.as_secs_f64();
+        runs.push(BackendRun::Measured { backend: backend.name, elapsed, hashrate_hs });
+    }
+
+    Ok(runs)
+}
+
+/// Hashes calculés pendant `duration` contre un target de 0, qu'aucun hash n'atteint
+///
+/// Seul le timer (ou Ctrl-C) arrête la mesure: le hashrate ne dépend pas de la
+/// chance d'une solution précoce.
+async fn timed_benchmark(
+    miner: Arc<dyn MinerBackend>,
+    job: &report::BenchJob,
+    duration: Duration,
+    control: MiningControl,
+) -> anyhow::Result<(u64, Duration)> {
+    let start = Instant::now();
+    let timer = control.clone();
+    tasks::spawn("benchmark-timer", async move {
+        tokio::time::sleep(duration).await;
+        timer.stop();
+    });
+
+    miner::mine_async(miner, job.challenge, job.miner_pubkey, job.block_number, 0, 0..u128::MAX, control.clone()).await?;
+    Ok((control.hashes(), start.elapsed()))
+}
+
+/// Nonces à hasher quand la difficulté (0 ou 1) rend tout hash valide: le premier nonce
+/// sortirait aussitôt et le hashrate ne voudrait rien dire
+fn trivial_difficulty_hashes(job: &report::BenchJob, cli: &Cli) -> Option<u64> {
+    (job.target.is_none() && job.difficulty <= 1).then(|| cli.hash_budget.unwrap_or(TRIVIAL_DIFFICULTY_HASHES))
+}
+
+/// Durée de `count` hashes contre un target de 0, arrêtés par le budget de hashes
+async fn counted_benchmark(miner: Arc<dyn MinerBackend>, job: &report::BenchJob, count: u64, control: MiningControl) -> anyhow::Result<(u64, Duration)> {
+    let control = control.with_hash_budget(Some(count));
+    let start = Instant::now();
+    miner::mine_async(miner, job.challenge, job.miner_pubkey, job.block_number, 0, 0..u128::MAX, control.clone()).await?;
+    Ok((control.hashes(), start.elapsed()))
+}
+
+async fn run_benchmark_all(cli: &Cli, hash_algo: HashAlgo) -> anyhow::Result<()> {
+    let reporter = report::reporter(cli.output);
+    let runs = compare_backends(cli, hash_algo, reporter.as_ref(), &stop_on_ctrl_c()).await?;
+
+    // Tableau comparatif en mode texte; JSON et CSV ont déjà une entrée par backend
+    if cli.output != OutputFormat::Text {
+        return Ok(());
+    }
+
+    let baseline = runs.iter().find_map(|run| match run {
+        BackendRun::Measured { hashrate_hs, .. } => Some(*hashrate_hs),
+        BackendRun::Skipped { .. } => None,
+    });
+
+    println!("\n{:<16} {:>12} {:>12} {:>9}", "Backend", "MH/s", "Time", "Speedup");
+    for run in &runs {
+        match run {
+            BackendRun::Measured { backend, elapsed, hashrate_hs } => println!(
+                "{:<16} {:>12.2} {:>11.3}s {:>8.2}x",
+                backend,
+                hashrate_hs / 1_000_000.0,
+                elapsed.as_secs_f64(),
+                baseline.map_or(1.0, |base| hashrate_hs / base)
+            ),
+            BackendRun::Skipped { backend, reason } => println!("{:<16} skipped: {}", backend, reason),
         }
     }
 
     Ok(())
 }
 
-async fn run_miner(
-    _miner: Box<dyn MinerBackend>,
-    _cli: &Cli,
-) -> anyhow::Result<()> {
-    anyhow::bail!("Mining mode is not yet implemented. Use --benchmark mode or use the TypeScript continuous-gpu-miner.ts script.");
+/// Sous-commande `tune`: chaque grille CUDA mesurée, tableau puis meilleure grille
+fn run_tune(cli: &Cli, threads_per_block: &[usize], num_blocks: &[usize], duration: Duration, write_config: Option<&std::path::Path>, hash_algo: HashAlgo) -> anyhow::Result<()> {
+    if let Some(threads) = threads_per_block.iter().find(|threads| **threads == 0 || !threads.is_multiple_of(config::CUDA_WARP_SIZE)) {
+        return Err(anyhow::anyhow!("--threads-per-block {} is not a positive multiple of the warp size ({})", threads, config::CUDA_WARP_SIZE)).context(Failure::Config);
+    }
+    let grids: Vec<(usize, usize)> = threads_per_block.iter().flat_map(|&threads| num_blocks.iter().map(move |&blocks| (threads, blocks))).collect();
+
+    info!("⛏️  Tuning {} CUDA grids, {:?} each...", grids.len(), duration);
+    let runs = miner::sweep_grids(&grids, cuda_grid_measure(cli, hash_algo, duration)?);
+    let best = miner::best_grid(&runs).ok_or_else(|| anyhow::anyhow!("No CUDA grid could be measured"))?;
+
+    println!("\n{:>13} {:>8} {:>12}", "Threads/block", "Blocks", "MH/s");
+    for run in &runs {
+        let marker = if *run == best { " *" } else { "" };
+        println!("{:>13} {:>8} {:>12.2}{}", run.threads_per_block, run.num_blocks, run.hashrate_hs / 1e6, marker);
+    }
+    println!("\nBest: {} threads per block x {} blocks ({:.2} MH/s)", best.threads_per_block, best.num_blocks, best.hashrate_hs / 1e6);
+
+    if let Some(path) = write_config {
+        write_tuned_config(path, &best)?;
+        info!("   Written to cuda_config in {}", path.display());
+    }
+    Ok(())
+}
+
+/// Hashrate d'une grille: `CudaMiner::with_config` sur une target inatteignable, après un
+/// premier passage qui absorbe le chargement du kernel
+#[cfg(feature = "cuda")]
+fn cuda_grid_measure(cli: &Cli, hash_algo: HashAlgo, duration: Duration) -> anyhow::Result<impl FnMut(usize, usize) -> Option<f64>> {
+    let device = gpu_device(cli);
+    Ok(move |threads_per_block, num_blocks| {
+        let miner = match cuda_miner::CudaMiner::with_config(device, threads_per_block, num_blocks) {
+            Ok(miner) => miner.with_hash_algo(hash_algo),
+            Err(e) => {
+                warn!("   Grid {}x{} unavailable: {:#}", threads_per_block, num_blocks, e);
+                return None;
+            }
+        };
+        miner::warm_up(&miner, duration / 4);
+        let (hashes, elapsed) = miner::warm_up(&miner, duration);
+        // Aucun hash: lancement refusé par le device (block trop grand)
+        (hashes > 0).then(|| hashes as f64 / elapsed.as_secs_f64())
+    })
+}
+
+#[cfg(not(feature = "cuda"))]
+fn cuda_grid_measure(_cli: &Cli, _hash_algo: HashAlgo, _duration: Duration) -> anyhow::Result<fn(usize, usize) -> Option<f64>> {
+    // Le backend OpenCL n'a pas encore de kernel dont régler le work size
+    Err(anyhow::anyhow!("tune requires a CUDA build (--features cuda)")).context(Failure::Config)
+}
+
+/// Écrit la grille retenue dans `cuda_config` de `path`, les autres champs conservés
+fn write_tuned_config(path: &std::path::Path, best: &miner::GridRun) -> anyhow::Result<()> {
+    let mut config: config::Config = match std::fs::read_to_string(path) {
+        Ok(json) => serde_json::from_str(&json).with_context(|| format!("Invalid config file {}", path.display()))?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => config::Config::default(),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    };
+    config.cuda_config.threads_per_block = best.threads_per_block;
+    config.cuda_config.num_blocks = best.num_blocks;
+    config.validate()?;
+
+    std::fs::write(path, serde_json::to_string_pretty(&config)? + "\n").with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// `--cpu-only-verify`: débit de `verify_nonce` seul, puis du CPU miner sur autant de threads
+fn run_verify_benchmark(cli: &Cli, hash_algo: HashAlgo) -> anyhow::Result<()> {
+    let reporter = report::reporter(cli.output);
+    let threads = cpu_threads(cli);
+    let reports = verify_benchmark(threads, hash_algo, VERIFY_BENCH_DURATION);
+    for report in &reports {
+        reporter.on_hashrate(report);
+    }
+
+    // Gain du midstate du CPU miner, net de l'overhead des batchs et du contrôle
+    if let [_, verify, miner] = reports.as_slice() {
+        info!("   CPU miner speedup: {:.2}x raw verify_nonce throughput (midstate)", miner.hashrate_hs / verify.hashrate_hs);
+    }
+    Ok(())
+}
+
+fn verify_benchmark(threads: usize, hash_algo: HashAlgo, duration: Duration) -> Vec<report::HashrateReport> {
+    let device = miner::cpu_model();
+    let hashrate = |backend: String, (hashes, elapsed): (u64, Duration)| report::HashrateReport {
+        backend,
+        device: device.clone(),
+        hashes,
+        elapsed_ms: elapsed.as_secs_f64() * 1000.0,
+        hashrate_hs: hashes as f64 / elapsed.as_secs_f64(),
+        power_watts: None,
+        efficiency_mh_j: None,
+    };
+
+    let cpu_miner = miner::CpuMiner::new(threads).with_hash_algo(hash_algo);
+    vec![
+        hashrate("verify_nonce (1 thread)".to_string(), miner::verify_throughput(hash_algo, 1, duration)),
+        hashrate(format!("verify_nonce ({} threads)", threads), miner::verify_throughput(hash_algo, threads, duration)),
+        hashrate(format!("CPU miner ({} threads)", threads), miner::warm_up(&cpu_miner, duration)),
+    ]
+}
+
+/// `--thread-scaling`: `CpuMiner` à 1, 2, 4... puis `max_threads` threads, `duration` chacun
+///
+/// Chaque mesure est un seul appel à `mine_with_control` (construction du pool rayon
+/// comprise) sur une target de 0: tous les threads hashent jusqu'au bout, et une
+/// efficacité qui chute révèle la contention (compteur de hashes, `found`, `result`).
+fn thread_scaling(max_threads: usize, hash_algo: HashAlgo, duration: Duration) -> Vec<report::ScalingPoint> {
+    let max_threads = max_threads.max(1);
+    let mut counts: Vec<usize> = std::iter::successors(Some(1usize), |n| n.checked_mul(2)).take_while(|&n| n < max_threads).collect();
+    counts.push(max_threads);
+
+    let measures: Vec<_> = counts
+        .into_iter()
+        .map(|threads| {
+            info!("⛏️  CPU miner with {} threads for {:?}...", threads, duration);
+            let (hashes, elapsed) = miner::warm_up(&miner::CpuMiner::new(threads).with_hash_algo(hash_algo), duration);
+            (threads, hashes, elapsed)
+        })
+        .collect();
+    report::scaling_points(&measures)
+}
+
+/// Sous-commande `difficulty`: conversion difficulté <-> target
+fn run_difficulty(difficulty: Option<u128>, target_hex: Option<&str>, full: bool) -> anyhow::Result<()> {
+    let (difficulty, target) = match (difficulty, target_hex) {
+        (Some(difficulty), _) => (difficulty, pow::difficulty_to_target(difficulty)),
+        (None, Some(hex)) => {
+            // Même syntaxe que le --target global, forme 256 bits de --full comprise
+            let target = pow::parse_target(hex)
+                .with_context(|| format!("Invalid --target '{}'", hex))
+                .context(Failure::Config)?;
+            (pow::target_to_difficulty(target), target)
+        }
+        (None, None) => anyhow::bail!("Either --difficulty or --target is required"),
+    };
+
+    println!("Difficulty:        {}", difficulty);
+    println!("Target (128-bit):  {:032x}", target);
+    if full {
+        println!("Target (256-bit):  {}", hex::encode(pow::difficulty_to_target_256(difficulty)));
+    }
+
+    Ok(())
+}
+
+/// Sous-commande `plan`: effort attendu pour une difficulté et une confiance
+fn run_plan(difficulty: u128, confidence: f64, hashrate: Option<f64>) -> anyhow::Result<()> {
+    if !(0.0..=1.0).contains(&confidence) {
+        return Err(anyhow::anyhow!("Invalid --confidence {} (expected 0 to 1)", confidence).context(Failure::Config));
+    }
+    print!("{}", describe_plan(difficulty, confidence, hashrate));
+    Ok(())
+}
+
+fn describe_plan(difficulty: u128, confidence: f64, hashrate: Option<f64>) -> String {
+    let expected = difficulty.max(1);
+    let needed = pow::hashes_for_confidence(difficulty, confidence);
+    let percent = format!("{:.1}%", confidence * 100.0);
+
+    let mut lines = vec![
+        ("Difficulty:".to_string(), difficulty.to_string()),
+        ("Expected hashes:".to_string(), expected.to_string()),
+        (format!("Hashes for {}:", percent), needed.to_string()),
+    ];
+    if let Some(hashrate) = hashrate.filter(|h| *h > 0.0) {
+        let eta = |hashes: u128| match hashes {
+            u128::MAX => "never guaranteed".to_string(),
+            hashes => format!("{:.1}s", hashes as f64 / hashrate),
+        };
+        lines.push(("ETA (mean):".to_string(), eta(expected)));
+        lines.push((format!("ETA ({}):", percent), eta(needed)));
+    }
+
+    lines.iter().map(|(label, value)| format!("{:<22}{}\n", label, value)).collect()
+}
+
+/// Sous-commande `status`: état du protocole lu on-chain (ou simulé)
+async fn run_status(cli: &Cli) -> anyhow::Result<()> {
+    let status = if cli.mock_chain {
+        let algo = if cli.double_sha { HashAlgo::DoubleSha256 } else { HashAlgo::Sha256 };
+        protocol_status(&mock_chain::MockChain::new(cli.difficulty, algo)).await?
+    } else {
+        let Some(program_id) = &cli.program_id else {
+            return Err(anyhow::anyhow!("status requires --program-id (or --mock-chain)").context(Failure::Config));
+        };
+        let client = chain::ChainClient::read_only(&cli.rpc, program_id, &pda_seeds(cli)).context(Failure::Config)?;
+        protocol_status(&client).await.context(Failure::Rpc)?
+    };
+
+    match cli.output {
+        OutputFormat::Json => println!("{}", serde_json::to_string(&status)?),
+        _ => print!("{}", describe_status(&status)),
+    }
+    Ok(())
+}
+
+/// État du protocole affiché par `status`
+#[derive(Debug, serde::Serialize)]
+struct ProtocolStatus {
+    difficulty: u128,
+    blocks_mined: u64,
+    total_supply_mined: u64,
+    challenge: String,
+    fee_sol: u64,
+    total_fees_collected: u64,
+    total_team_fees: u64,
+    total_buyback_sol: u64,
+    total_lp_sol: u64,
+    fee_vault_balance: u64,
+    is_paused: bool,
+    seconds_since_last_block: i64,
+    /// État brut et décimales du mint, pour l'affichage texte
+    #[serde(skip)]
+    state: chain::PowState,
+    #[serde(skip)]
+    decimals: u8,
+}
+
+async fn protocol_status<C: Chain>(chain: &C) -> anyhow::Result<ProtocolStatus> {
+    let state = chain.get_pow_state().await?;
+    let seconds_since_last_block = chain.seconds_since_last_block(&state).await?;
+    let fee_vault_balance = chain.get_fee_vault_balance().await?;
+    let decimals = chain.mint_decimals().await?;
+
+    Ok(ProtocolStatus {
+        difficulty: state.difficulty,
+        blocks_mined: state.blocks_mined,
+        total_supply_mined: state.total_supply_mined,
+        challenge: hex::encode(state.challenge),
+        fee_sol: state.fee_sol,
+        total_fees_collected: state.fees.total_fees_collected,
+        total_team_fees: state.fees.total_team_fees,
+        total_buyback_sol: state.fees.total_buyback_sol,
+        total_lp_sol: state.fees.total_lp_sol,
+        fee_vault_balance,
+        is_paused: state.is_paused,
+        seconds_since_last_block,
+        state,
+        decimals,
+    })
+}
+
+fn describe_status(status: &ProtocolStatus) -> String {
+    let lines = [
+        ("Fee vault balance:", chain::format_sol(status.fee_vault_balance)),
+        ("Last block:", format!("{}s ago", status.seconds_since_last_block)),
+    ];
+
+    let extra: String = lines.iter().map(|(label, value)| format!("{:<22}{}\n", label, value)).collect();
+    format!("{:#}{}", status.state.summary(status.decimals), extra)
+}
+
+/// Sous-commande `hash`: hash d'un préimage donné, pour déboguer le vérificateur on-chain
+fn run_hash(challenge: &str, pubkey: &str, nonce: u128, block_number: u64, difficulty: Option<u128>, algo: HashAlgo) -> anyhow::Result<()> {
+    let challenge = pow::parse_hash32(challenge).context("Invalid --challenge").context(Failure::Config)?;
+    let pubkey = parse_pubkey(pubkey).context("Invalid --pubkey (expected 64 hex chars or base58)").context(Failure::Config)?;
+
+    print!("{}", describe_hash(&challenge, &pubkey, nonce, block_number, difficulty, algo));
+    Ok(())
+}
+
+/// Pubkey en hex (32 bytes) ou en base58
+fn parse_pubkey(value: &str) -> anyhow::Result<[u8; 32]> {
+    pow::parse_hash32(value).or_else(|_| Ok(Pubkey::from_str(value.trim())?.to_bytes()))
+}
+
+/// Sous-commande `dump`: `nonce hash` pour chaque nonce de `nonces`
+fn run_dump(challenge: &str, miner_pubkey: &str, block_number: u64, nonces: std::ops::RangeInclusive<u128>, algo: HashAlgo, format: OutputFormat) -> anyhow::Result<()> {
+    let challenge = pow::parse_hash32(challenge).context("Invalid --challenge").context(Failure::Config)?;
+    let miner_pubkey = parse_pubkey(miner_pubkey).context("Invalid --miner-pubkey (expected 64 hex chars or base58)").context(Failure::Config)?;
+    if nonces.is_empty() {
+        return Err(anyhow::anyhow!("Invalid nonce range: --to {} is below --from {}", nonces.end(), nonces.start()).context(Failure::Config));
+    }
+
+    use std::io::Write;
+
+    let mut out = std::io::stdout().lock();
+    for line in dump_lines(challenge, miner_pubkey, block_number, nonces, algo, format) {
+        // Sortie fermée (`| head`): fin normale
+        if writeln!(out, "{}", line).is_err() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+fn dump_lines(
+    challenge: [u8; 32],
+    miner_pubkey: [u8; 32],
+    block_number: u64,
+    nonces: std::ops::RangeInclusive<u128>,
+    algo: HashAlgo,
+    format: OutputFormat,
+) -> impl Iterator<Item = String> {
+    nonces.map(move |nonce| {
+        let hash = hex::encode(pow::compute_hash(&challenge, &miner_pubkey, nonce, block_number, algo));
+        match format {
+            // Nonce en chaîne: un u128 dépasse la précision des nombres JSON
+            OutputFormat::Json => format!(r#"{{"nonce":"{}","hash":"{}"}}"#, nonce, hash),
+            OutputFormat::Csv => format!("{},{}", nonce, hash),
+            OutputFormat::Text => format!("{} {}", nonce, hash),
+        }
+    })
+}
+
+fn describe_hash(challenge: &[u8; 32], pubkey: &[u8; 32], nonce: u128, block_number: u64, difficulty: Option<u128>, algo: HashAlgo) -> String {
+    let hash = pow::compute_hash(challenge, pubkey, nonce, block_number, algo);
+    let value = pow::hash_value(&hash);
+
+    let mut out = format!("Hash:         {}\nValue (u128): {} (0x{:032x})\n", hex::encode(hash), value, value);
+    if let Some(difficulty) = difficulty {
+        let target = pow::difficulty_to_target(difficulty);
+        out += &format!("Target:       0x{:032x}\nBelow target: {}\n", target, pow::is_valid_hash(&hash, target));
+    }
+    out
+}
+
+/// Sous-commande `replay`: verdict de chaque entrée d'un --solution-log
+fn run_replay(path: &std::path::Path, difficulty: Option<u128>) -> anyhow::Result<()> {
+    let records = solution_log::read(path).context(Failure::Config)?;
+    print!("{}", describe_replay(&records, difficulty));
+    Ok(())
+}
+
+fn describe_replay(records: &[solution_log::SolutionRecord], difficulty: Option<u128>) -> String {
+    let mut out = String::new();
+    let mut invalid = 0;
+    for (i, record) in records.iter().enumerate() {
+        let outcome = match (record.submitted, record.accepted) {
+            (true, true) => "accepted",
+            (true, false) => "rejected",
+            (false, _) => "not submitted",
+        };
+        let verdict = match record.replay(difficulty) {
+            Ok(()) => "valid".to_string(),
+            Err(reason) => {
+                invalid += 1;
+                format!("INVALID: {}", reason)
+            }
+        };
+        out += &format!("#{} block {} nonce {} ({}): {}\n", i + 1, record.block_number, record.nonce, outcome, verdict);
+    }
+    out += &format!("{} entries, {} valid, {} invalid\n", records.len(), records.len() - invalid, invalid);
+    out
+}
+
+/// MiningControl arrêté proprement par Ctrl-C
+fn stop_on_ctrl_c() -> MiningControl {
+    let control = MiningControl::new();
+    let ctrl_c = control.clone();
+    tasks::spawn("ctrl-c", async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            warn!("Interrupted, stopping miner...");
+            ctrl_c.stop();
+        }
+    });
+    control
+}
+
+async fn run_miner(miner: Box<dyn MinerBackend>, cli: &Cli, hash_algo: HashAlgo) -> anyhow::Result<()> {
+    let control = stop_on_ctrl_c();
+    let miner: Arc<dyn MinerBackend> = Arc::from(miner);
+    if let Some(port) = cli.health_port {
+        let listener = tokio::net::TcpListener::bind(("0.0.0.0", port))
+            .await
+            .with_context(|| format!("failed to bind health port {}", port))
+            .context(Failure::Config)?;
+        info!("   Health probes: http://0.0.0.0:{}/healthz, /readyz, /solutions", port);
+        tasks::spawn("health", health::serve(listener, Arc::new(health::Health::new(&control, cli.health_max_idle))));
+    }
+
+    if cli.mock_chain {
+        info!("   Chain: mock (difficulty {})", cli.difficulty);
+        let chain = mock_chain::MockChain::new(cli.difficulty, hash_algo).with_compare_mode(cli.compare_mode);
+        if cli.oneshot {
+            return oneshot(&chain, miner, cli, hash_algo, control).await.map(|_| ());
+        }
+
+        let Some(duration) = cli.soak else {
+            mine_loop(&chain, miner, cli, hash_algo, control).await?;
+            return Ok(());
+        };
+
+        // Soak: arrêt au bout de `duration`, puis résumé JSON
+        let timer = control.clone();
+        tasks::spawn("soak-timer", async move {
+            tokio::time::sleep(duration).await;
+            timer.stop();
+        });
+
+        let start = Instant::now();
+        let stats = mine_loop(&chain, miner, cli, hash_algo, control).await?;
+        info!("Soak finished on {}: {} solutions, {} errors", stats.backend(), stats.solutions(), stats.errors().len());
+        println!("{}", serde_json::to_string_pretty(&stats.summary(start.elapsed()))?);
+        return Ok(());
+    }
+
+    let (Some(program_id), Some(mint)) = (&cli.program_id, &cli.mint) else {
+        return Err(anyhow::anyhow!("Mining mode requires --program-id and --mint (or use --benchmark)").context(Failure::Config));
+    };
+
+    let config = MinerConfig {
+        rpc_urls: cli.rpc.clone(),
+        submit_rpc_urls: cli.submit_rpc.clone(),
+        rpc_rate_limit: cli.rpc_rate_limit,
+        wallet_paths: cli.keypair.clone(),
+        program_id: program_id.clone(),
+        mint: mint.clone(),
+        memo: cli.memo.clone(),
+        durable_nonce: cli.durable_nonce.clone(),
+        no_confirm: cli.no_confirm,
+        jito_block_engine: cli.jito_block_engine.clone(),
+        jito_tip: cli.jito_tip,
+        fresh_blockhash: cli.fresh_blockhash,
+        seeds: pda_seeds(cli),
+    };
+    let client = chain::ChainClient::new(&config).await.context(Failure::Config)?;
+    info!("   RPC: {}", cli.rpc.join(", "));
+    if !cli.submit_rpc.is_empty() {
+        info!("   Submit RPC: {}", cli.submit_rpc.join(", "));
+    }
+
+    if cli.oneshot {
+        return oneshot(&client, miner, cli, hash_algo, control).await.map(|_| ());
+    }
+    mine_loop(&client, miner, cli, hash_algo, control).await?;
+    Ok(())
+}
+
+/// Seeds des PDAs passés en CLI
+fn pda_seeds(cli: &Cli) -> config::PdaSeeds {
+    config::PdaSeeds {
+        pow_config: cli.pow_config_seed.clone(),
+        fee_vault: cli.fee_vault_seed.clone(),
+        miner_stats: cli.miner_stats_seed.clone(),
+        mint_authority: cli.mint_authority_seed.clone(),
+    }
+}
+
+/// `--oneshot`: affiche le challenge courant de `chain`, le mine une fois et, avec
+/// `--submit`, soumet le nonce trouvé
+///
+/// Entre le benchmark (challenge fixe) et `mine_loop`: un seul round, sans relecture
+/// de l'état ni nouvel essai.
+async fn oneshot<C: Chain>(
+    chain: &C,
+    miner: Arc<dyn MinerBackend>,
+    cli: &Cli,
+    hash_algo: HashAlgo,
+    control: MiningControl,
+) -> anyhow::Result<u128> {
+    let reporter = report::reporter(cli.output);
+    let state = chain.get_pow_state().await.context(Failure::Rpc)?;
+    let account = chain.next_miner();
+    let job = report::BenchJob {
+        compare_mode: cli.compare_mode,
+        ..round_job(&state, account.pubkey().to_bytes(), hash_algo, cli.target, cli.block_number_override, cli.challenge_derivation)
+    };
+
+    println!("Challenge:    {}", hex::encode(job.challenge));
+    println!("Difficulty:   {}", state.difficulty);
+    println!("Block number: {}", job.block_number);
+    println!("Miner:        {}", account.pubkey());
+    if !state.is_initialized || state.is_paused {
+        warn!("Protocol is not accepting proofs (initialized: {}, paused: {})", state.is_initialized, state.is_paused);
+    }
+
+    let control = control.with_hash_budget(cli.hash_budget);
+    let start = Instant::now();
+    let mut nonce = None;
+    for nonces in search_range(cli, &job.challenge) {
+        nonce = miner::mine_async(miner.clone(), job.challenge, job.miner_pubkey, job.block_number, job.target(), nonces, control.clone()).await?;
+        if nonce.is_some() || !control.is_running() {
+            break;
+        }
+    }
+    report::report_result(reporter.as_ref(), &report::BackendInfo::of(miner.as_ref()), &job, nonce, start.elapsed(), &control, None);
+    let Some(nonce) = nonce else {
+        return Err(Failure::NoSolution.into());
+    };
+
+    if cli.submit {
+        check_mint(&chain.mint(), &state, cli.force)?;
+        chain.ensure_token_account().await.context(Failure::Rpc)?;
+        let signature = chain.submit_proof(account, nonce).await.context(Failure::Rpc)?;
+        println!("Submitted:    {}", signature);
+    }
+    Ok(nonce)
+}
+
+/// Boucle de mining continue contre `chain`, jusqu'à l'arrêt de `control`
+async fn mine_loop<C: Chain>(
+    chain: &C,
+    miner: Arc<dyn MinerBackend>,
+    cli: &Cli,
+    hash_algo: HashAlgo,
+    control: MiningControl,
+) -> anyhow::Result<MiningStats> {
+    // En soak, seul le résumé final est écrit sur stdout
+    let reporter: Box<dyn report::Reporter> = if cli.soak.is_some() {
+        Box::new(report::NullReporter)
+    } else {
+        report::reporter(cli.output)
+    };
+    let mut stats = MiningStats::new(miner.name());
+    let solution_log = match &cli.solution_log {
+        Some(path) => Some(solution_log::SolutionLog::open(path).context(Failure::Config)?),
+        None => None,
+    };
+    let solution_fifo = cli.solution_fifo.as_deref().map(|path| Arc::new(solution_fifo::SolutionFifo::new(path)));
+
+    let initial_state = chain.get_pow_state().await;
+    if let Ok(state) = &initial_state {
+        control.set_state_fetched();
+        check_mint(&chain.mint(), state, cli.force)?;
+    }
+
+    // Le premier submit_proof peut devoir payer la rent de miner_stats
+    let fee_sol = initial_state.as_ref().map_or(0, |state| state.fee_sol);
+    let mut balances = Vec::new();
+    for account in chain.miners() {
+        let balance = chain.get_balance(account).await.context(Failure::Rpc)?;
+        balances.push(balance);
+        info!("📍 Miner: {} ({:.4} SOL)", account.pubkey(), balance as f64 / LAMPORTS_PER_SOL as f64);
+
+        let required = chain::first_block_cost(chain.miner_stats_rent(account).await.context(Failure::Rpc)?, fee_sol);
+        if balance < required {
+            warn!(
+                "   Balance of {} is below the {:.6} SOL needed for the first block (miner_stats rent and fees)",
+                account.pubkey(),
+                required as f64 / LAMPORTS_PER_SOL as f64
+            );
+        }
+    }
+    if chain.miners().len() > 1 {
+        info!("   Rotating submissions across {} keypairs", chain.miners().len());
+    }
+
+    chain.ensure_token_account().await.context(Failure::Rpc)?;
+    let decimals = chain.mint_decimals().await.context(Failure::Config)?;
+    // Preuves par transaction (--batch-submit), 1 si la chaîne ne permet pas de les enchaîner
+    let mut batch_limit = 1;
+    if let Ok(state) = &initial_state {
+        info!("📊 Protocol: {}", state.summary(decimals));
+        if cli.speculative && chain.predict_challenge(state, 0).is_none() {
+            warn!("--speculative has no effect: the next challenge cannot be predicted on {}", chain.endpoint());
+        }
+        if cli.batch_submit > 1 {
+            batch_limit = batch_submit_limit(chain, state, cli.batch_submit as usize);
+        }
+    }
+
+    // Soldes de référence de la comptabilité de session
+    stats.ledger_mut().configure(decimals, cli.price_sol_per_token);
+    for (account, lamports) in chain.miners().iter().zip(balances) {
+        match chain.get_token_balance(account).await {
+            Ok(tokens) => stats.ledger_mut().observe(account.pubkey(), lamports, tokens),
+            Err(e) => debug!("Failed to read the token balance of {}: {:#}", account.pubkey(), e),
+        }
+    }
+
+    let power = if miner.is_gpu() {
+        power::PowerSource::detect(cli.gpu_watts, gpu_device(cli))
+    } else {
+        None
+    };
+
+    let backend = report::BackendInfo::of(miner.as_ref());
+    let mut throttle = logging::LogThrottle::new(cli.log_interval);
+    let mut progress = stats::Progress::default();
+    let mut breaker = breaker::CircuitBreaker::new(cli.max_submit_failures);
+    let mut submitted = dedup::SubmittedNonces::default();
+    // Challenge abandonné (budget ou plage de nonces épuisé), ignoré jusqu'au suivant
+    let mut abandoned: Option<[u8; 32]> = None;
+    let mut poll = poll::PollDelay::new(cli.poll_interval, cli.poll_jitter);
+    // Recherche en cours, poursuivie par un GPU pendant la soumission de ses solutions
+    let mut search: Option<Search> = None;
+    let mut blocks_watch = chain::BlocksMinedWatch::default();
+
+    info!("\n⛏️  Starting continuous mining...\n");
+
+    while control.is_running() {
+        let state = match chain.get_pow_state().await {
+            Ok(state) => {
+                control.set_state_fetched();
+                state
+            }
+            Err(e) => {
+                warn!("Failed to fetch PoW state: {:#}", e);
+                stats.record_error(format!("fetch state: {:#}", e));
+                tokio::time::sleep(poll.next_delay()).await;
+                continue;
+            }
+        };
+
+        if !state.is_initialized || state.is_paused || state.difficulty == 0 {
+            warn!("Protocol is not accepting proofs, waiting...");
+            tokio::time::sleep(poll.next_delay()).await;
+            continue;
+        }
+        // Un état plus ancien que le précédent porte un challenge déjà tourné
+        match blocks_watch.observe(state.blocks_mined) {
+            Ok(advanced) if advanced > 1 => debug!("   {} blocks mined since the last reading", chain::format_thousands(advanced)),
+            Ok(_) => {}
+            Err(previous) => {
+                warn!(
+                    "   blocks_mined went back from {} to {} (lagging RPC or rollback), waiting",
+                    chain::format_thousands(previous),
+                    chain::format_thousands(state.blocks_mined)
+                );
+                tokio::time::sleep(poll.next_delay()).await;
+                continue;
+            }
+        }
+        if abandoned == Some(state.challenge) {
+            tokio::time::sleep(poll.next_delay()).await;
+            continue;
+        }
+
+        let mut confirmed = false;
+        if let Some(current) = &mut search {
+            if current.parent == Some(state.challenge) {
+                // Spéculation en cours, rotation pas encore visible (RPC en retard): elle continue
+                tokio::time::sleep(poll.next_delay()).await;
+                continue;
+            }
+            if current.parent.is_some() {
+                confirmed = current.challenge == state.challenge;
+                info!("   Speculative search on block #{} {}", current.job.block_number, if confirmed { "confirmed" } else { "discarded" });
+                stats.record_speculation(confirmed);
+                current.parent = None;
+            }
+        }
+        // Un nouveau challenge (bloc miné, par nous ou par un autre) rend la recherche caduque
+        if search.as_ref().is_some_and(|search| search.challenge != state.challenge) {
+            search = None;
+        }
+        let resumed = search.is_some() && !confirmed;
+        let current = search.get_or_insert_with(|| Search::start(chain, &miner, cli, hash_algo, &control, &state));
+        let (account, job, round, start) = (current.account, current.job.clone(), current.round.clone(), current.start);
+
+        // Bascule du pool RPC (--json-rpc-url-list) depuis le round précédent
+        if stats.observe_rpc_endpoint(chain.endpoint()) {
+            warn!("   RPC failover: now using {}", chain.endpoint());
+        }
+
+        // À très basse difficulté, les rounds s'enchaînent trop vite pour tout logger
+        let verbose = throttle.allow(Instant::now());
+        if let (Some(skipped), false) = (verbose, resumed) {
+            if skipped > 0 {
+                info!("   ({} rounds not logged)", skipped);
+            }
+            info!("📦 Block #{} - Difficulty: {} - Miner: {}", job.block_number, state.difficulty, account.pubkey());
+            debug!(
+                "   Pending reward: {} tokens - Total mined: {} tokens",
+                chain::format_tokens(state.pending_reward_tokens, decimals),
+                chain::format_tokens(state.total_supply_mined, decimals)
+            );
+
+            match chain.seconds_since_last_block(&state).await {
+                Ok(secs) if secs > STALE_BLOCK_WARN_SECS => warn!("   No block mined for {}s, network may be stuck", secs),
+                Ok(secs) => info!("   Last block: {}s ago", secs),
+                Err(e) => warn!("Failed to read cluster clock: {:#}", e),
+            }
+        }
+
+        // Progression loggée à un rythme adapté au temps de résolution attendu
+        let difficulty = pow::target_to_difficulty(job.target());
+        let nonce = loop {
+            tokio::select! {
+                nonce = current.stream.next() => break nonce?,
+                _ = tokio::time::sleep(progress.interval(difficulty)) => info!("{}", progress.observe(difficulty, round.hashes(), start.elapsed())),
+            }
+        };
+        progress.observe(difficulty, round.hashes(), start.elapsed());
+        // Le premier nonce du CPU n'est pas le plus petit de la plage (un chunk par thread):
+        // reprendre après lui sauterait des nonces, le CPU repart donc de zéro à chaque round
+        if nonce.is_none() || !miner.is_gpu() {
+            search = None;
+        }
+        if verbose.is_some() {
+            report::report_result(reporter.as_ref(), &backend, &job, nonce, start.elapsed(), &round, power.as_ref());
+        }
+
+        let Some(nonce) = nonce else {
+            if round.budget_exhausted() {
+                warn!("   Hash budget spent on block #{} ({} hashes), waiting for a new challenge", job.block_number, round.hashes());
+                abandoned = Some(state.challenge);
+            } else if round.is_running() {
+                // Plage --max-onchain-nonce épuisée: rien à trouver avant le prochain challenge
+                warn!("   No valid nonce up to {}, waiting for a new challenge", nonce_range(cli).end - 1);
+                abandoned = Some(state.challenge);
+            }
+            continue;
+        };
+        if submitted.contains(&job.challenge, &job.miner_pubkey, nonce) {
+            info!("   Nonce {} already submitted for this challenge, skipping", nonce);
+            tokio::time::sleep(DUPLICATE_RETRY_DELAY).await;
+            continue;
+        }
+
+        // La difficulté a pu monter pendant le mining: une preuve devenue invalide coûterait les frais pour rien
+        let hash = pow::compute_hash(&job.challenge, &job.miner_pubkey, nonce, job.block_number, hash_algo);
+        match chain.get_pow_state().await {
+            Ok(fresh) if fresh.challenge != state.challenge => {
+                info!("   Challenge changed while mining, dropping nonce {}", nonce);
+                search = None;
+                continue;
+            }
+            Ok(fresh) if fresh.difficulty > state.difficulty && !job.compare_mode.is_valid(&hash, pow::difficulty_to_target(fresh.difficulty)) => {
+                warn!(
+                    "   Difficulty rose from {} to {} while mining, nonce {} is no longer valid: skipping submission",
+                    state.difficulty, fresh.difficulty, nonce
+                );
+                // Nouvelle recherche au target de la nouvelle difficulté
+                search = None;
+                continue;
+            }
+            Ok(_) => {}
+            Err(e) => debug!("Failed to re-read PoW state before submitting: {:#}", e),
+        }
+        stats.record_solution(start.elapsed());
+
+        // --solution-fifo: le lecteur soumet, le challenge tourne quand sa preuve est acceptée;
+        // d'ici là, le même nonce retrouvé est écarté comme déjà soumis
+        if let Some(fifo) = &solution_fifo {
+            let (fifo, solution) = (fifo.clone(), solution_fifo::FifoSolution::new(&job, nonce, &hash));
+            let path = fifo.path().display().to_string();
+            let running = control.clone();
+            match tasks::spawn_blocking("solution-fifo", move || fifo.send(&solution, || running.is_running())).await? {
+                Ok(()) => {
+                    submitted.insert(&job.challenge, &job.miner_pubkey, nonce);
+                    if verbose.is_some() {
+                        info!("📤 Nonce {} written to {}", nonce, path);
+                    }
+                }
+                Err(e) => {
+                    warn!("{:#}", e);
+                    stats.record_error(format!("solution fifo: {:#}", e));
+                }
+            }
+
+            let record = solution_log::SolutionRecord::new(&job, nonce, &hash);
+            if let Some(log) = &solution_log {
+                if let Err(e) = log.append(&record) {
+                    warn!("Failed to write solution log: {:#}", e);
+                }
+            }
+            control.recent_solutions.push(record);
+            continue;
+        }
+
+        // --batch-submit: les blocs suivants sont minés d'affilée sur les challenges prévus, par
+        // le même signataire, puis soumis avec celui-ci en une transaction
+        let mut batch = vec![(job.clone(), nonce, hash)];
+        if batch_limit > 1 {
+            search = None;
+        }
+        let mut chained = state.clone();
+        while batch.len() < batch_limit && control.is_running() {
+            let Some(next) = chain.predict_challenge(&chained, batch[batch.len() - 1].1) else { break };
+            chained = chain::PowState { challenge: next, blocks_mined: chained.blocks_mined + 1, ..chained };
+            let mut next_search = Search::for_account(account, &miner, cli, hash_algo, &control, &chained);
+            let Some(next_nonce) = next_search.stream.next().await? else { break };
+            stats.record_solution(next_search.start.elapsed());
+            let job = next_search.job;
+            let hash = pow::compute_hash(&job.challenge, &job.miner_pubkey, next_nonce, job.block_number, hash_algo);
+            batch.push((job, next_nonce, hash));
+        }
+
+        // --speculative: le bloc suivant est miné pendant la confirmation de celui-ci, sur le
+        // challenge prévu; le backend quitte la recherche en cours pour s'y consacrer
+        let mut speculation = None;
+        if let Some(next) = cli.speculative.then(|| chain.predict_challenge(&state, nonce)).flatten() {
+            search = None;
+            let next_state = chain::PowState { challenge: next, blocks_mined: state.blocks_mined + 1, ..state.clone() };
+            let mut next_search = Search::start(chain, &miner, cli, hash_algo, &control, &next_state);
+            next_search.parent = Some(state.challenge);
+            speculation = Some(next_search);
+        }
+
+        let nonces: Vec<u128> = batch.iter().map(|(_, nonce, _)| *nonce).collect();
+        let submission = chain.submit_proofs(account, &nonces).await;
+        if let Ok(signature) = &submission {
+            breaker.reset();
+            // Le challenge change avec ce bloc: seule la spéculation reste valable
+            search = speculation.take();
+            for (job, nonce, _) in &batch {
+                submitted.insert(&job.challenge, &job.miner_pubkey, *nonce);
+            }
+            if batch.len() > 1 {
+                debug!("   {} proofs submitted in transaction {}", batch.len(), signature);
+            }
+        }
+        // Preuve rejetée: le challenge ne tourne pas
+        drop(speculation);
+        for (job, nonce, hash) in &batch {
+            let mut record = solution_log::SolutionRecord::new(job, *nonce, hash);
+            record.submitted = true;
+            if let Ok(signature) = &submission {
+                record.signature = Some(signature.clone());
+                // --no-confirm: envoyée seulement, l'issue est journalisée en tâche de fond
+                record.accepted = !cli.no_confirm;
+            }
+            if let Some(log) = &solution_log {
+                if let Err(e) = log.append(&record) {
+                    warn!("Failed to write solution log: {:#}", e);
+                }
+            }
+            control.recent_solutions.push(record);
+        }
+
+        // Soldes relus après chaque envoi: récompense et frais depuis la lecture précédente
+        let balances = tokio::try_join!(chain.get_balance(account), chain.get_token_balance(account));
+        match &balances {
+            Ok((lamports, tokens)) => stats.ledger_mut().observe(account.pubkey(), *lamports, *tokens),
+            Err(e) => debug!("Failed to read balances after submitting: {:#}", e),
+        }
+
+        match submission {
+            Ok(signature) if verbose.is_some() && cli.no_confirm => info!("📤 Proof sent, not awaiting confirmation. TX: {}", signature),
+            Ok(signature) if verbose.is_some() => {
+                match batch.len() {
+                    1 => info!("🎉 Block mined! TX: {}", signature),
+                    blocks => info!("🎉 {} blocks mined in one transaction! TX: {}", blocks, signature),
+                }
+                match &balances {
+                    Ok((_, tokens)) => info!("   Token balance: {} tokens", chain::format_tokens(*tokens, decimals)),
+                    Err(e) => warn!("Failed to fetch token balance: {:#}", e),
+                }
+                info!("   Session: {}", stats.ledger());
+            }
+            Ok(_) => {}
+            Err(e) => {
+                error!("Failed to submit proof: {:#}", e);
+                stats.record_error(format!("submit proof: {:#}", e));
+                if !breaker.record_failure() {
+                    tokio::time::sleep(RETRY_DELAY).await;
+                    continue;
+                }
+
+                // Preuves rejetées en boucle (comptes, programme): ne plus payer de frais
+                error!("{} consecutive submission failures, last: {:#}", breaker.failures(), e);
+                for line in chain::program_logs(&e) {
+                    error!("   {}", line);
+                }
+                let Some(cooldown) = cli.submit_cooldown else {
+                    return Err(e.context(Failure::SubmitBreaker));
+                };
+                warn!("Submissions paused for {:?}", cooldown);
+                tokio::time::sleep(cooldown).await;
+                breaker.reset();
+            }
+        }
+    }
+
+    info!("📒 Session ({}, RPC {}): {}", stats.backend(), chain.endpoint(), stats.ledger());
+    Ok(stats)
+}
+
+/// Recherche de `mine_loop` sur un challenge, gardée tant qu'il ne change pas
+struct Search<'a> {
+    /// Challenge on-chain (avant dérivation)
+    challenge: [u8; 32],
+    /// Challenge dont celui-ci est prévu (`--speculative`), tant que la rotation n'est pas lue
+    parent: Option<[u8; 32]>,
+    account: &'a chain::MinerAccount,
+    job: report::BenchJob,
+    round: MiningControl,
+    start: Instant,
+    stream: miner::NonceStream,
+}
+
+impl<'a> Search<'a> {
+    fn start(chain: &'a impl Chain, miner: &Arc<dyn MinerBackend>, cli: &Cli, hash_algo: HashAlgo, control: &MiningControl, state: &chain::PowState) -> Self {
+        // Le pubkey du mineur fait partie du préimage: le signataire
+        // est choisi avant de miner, et le nonce trouvé n'est valide que pour lui
+        Self::for_account(chain.next_miner(), miner, cli, hash_algo, control, state)
+    }
+
+    /// Recherche pour un signataire donné (blocs enchaînés de --batch-submit)
+    fn for_account(account: &'a chain::MinerAccount, miner: &Arc<dyn MinerBackend>, cli: &Cli, hash_algo: HashAlgo, control: &MiningControl, state: &chain::PowState) -> Self {
+        let job = report::BenchJob {
+            compare_mode: cli.compare_mode,
+            ..round_job(state, account.pubkey().to_bytes(), hash_algo, cli.target, cli.block_number_override, cli.challenge_derivation)
+        };
+        let round = control.round().with_hash_budget(cli.hash_budget);
+        let stream = miner::NonceStream::start(miner.clone(), job.challenge, job.miner_pubkey, job.block_number, job.target(), search_range(cli, &job.challenge), round.clone());
+        Search { challenge: state.challenge, parent: None, account, job, round, start: Instant::now(), stream }
+    }
+}
+
+/// Preuves par transaction de --batch-submit: `requested`, borné par la taille d'une transaction;
+/// 1 (soumission unitaire) si le challenge suivant ne peut pas être prévu
+fn batch_submit_limit(chain: &impl Chain, state: &chain::PowState, requested: usize) -> usize {
+    if chain.predict_challenge(state, 0).is_none() {
+        warn!("--batch-submit has no effect: the next challenge cannot be predicted on {}", chain.endpoint());
+        return 1;
+    }
+
+    let max = chain.max_proofs_per_transaction();
+    if max < requested {
+        warn!("--batch-submit {} exceeds the {} proofs that fit in one transaction, using {}", requested, max, max);
+    }
+    requested.min(max)
+}
+
+/// Initialise un backend par son nom, sans repli sur le CPU
+fn try_backend(name: &str, cli: &Cli, hash_algo: HashAlgo) -> anyhow::Result<Box<dyn MinerBackend>> {
+    match name {
+        "cpu" => {
+            let threads = cpu_threads(cli);
+            Ok(Box::new(cpu_backend(cli, hash_algo, threads)))
+        }
+        #[cfg(feature = "cuda")]
+        "cuda" => Ok(Box::new(cuda_backend(cli, hash_algo)?)),
+        #[cfg(feature = "opencl")]
+        "opencl" => Ok(Box::new(opencl_backend(cli, hash_algo)?)),
+        "mock" => Ok(Box::new(mock_backend(cli, hash_algo))),
+        other => Err(anyhow::anyhow!("Unknown or disabled backend '{}'", other)),
+    }
+}
+
+/// Threads du backend CPU: `--threads` (0: tous les cœurs sauf un), `--leave-cores`, sinon tous
+fn cpu_threads(cli: &Cli) -> usize {
+    resolve_threads(cli.threads, cli.leave_cores, num_cpus::get())
+}
+
+fn resolve_threads(threads: Option<usize>, leave_cores: Option<usize>, cpus: usize) -> usize {
+    match (threads, leave_cores) {
+        (_, Some(free)) => cpus.saturating_sub(free).max(1),
+        (Some(threads), None) => miner::effective_threads(threads, cpus),
+        (None, None) => cpus,
+    }
+}
+
+/// Backend CPU configuré par la CLI
+fn cpu_backend(cli: &Cli, hash_algo: HashAlgo, threads: usize) -> miner::CpuMiner {
+    miner::CpuMiner::new(threads)
+        .with_hash_algo(hash_algo)
+        .with_sha_impl(cli.sha_impl)
+        .with_compare_mode(cli.compare_mode)
+        .with_numa(cli.affinity_numa)
+}
+
+/// Backend simulé configuré par la CLI (`--backend mock`)
+fn mock_backend(cli: &Cli, hash_algo: HashAlgo) -> miner::MockMiner {
+    miner::MockMiner::new(cli.simulate_hashrate).with_hash_algo(hash_algo).with_compare_mode(cli.compare_mode)
+}
+
+/// Device GPU de --device (0 par défaut)
+fn gpu_device(cli: &Cli) -> usize {
+    cli.device.unwrap_or(0)
+}
+
+/// Device OpenCL: --device, sinon POW_OPENCL_DEVICE, sinon 0
+#[cfg_attr(not(feature = "opencl"), allow(dead_code))]
+fn opencl_device(cli: &Cli) -> anyhow::Result<usize> {
+    if let Some(device) = cli.device {
+        return Ok(device);
+    }
+    match std::env::var(OPENCL_DEVICE_ENV) {
+        Ok(value) => value
+            .trim()
+            .parse()
+            .map_err(|_| anyhow::anyhow!("Invalid {}={:?} (expected a device index)", OPENCL_DEVICE_ENV, value))
+            .context(Failure::Config),
+        Err(_) => Ok(gpu_device(cli)),
+    }
+}
+
+/// Backend CUDA configuré par la CLI
+#[cfg(feature = "cuda")]
+fn cuda_backend(cli: &Cli, hash_algo: HashAlgo) -> anyhow::Result<cuda_miner::CudaMiner> {
+    let grid = cuda_grid(cli)?;
+    let miner = cuda_miner::CudaMiner::with_config(gpu_device(cli), grid.threads_per_block, grid.num_blocks)?
+        .with_hash_algo(hash_algo)
+        .with_sub_launches(grid.sub_launches);
+    Ok(if cli.gpu_auto_batch { miner.with_auto_batch() } else { miner })
+}
+
+/// Grille CUDA: `cuda_config` de `--config`, sinon celle par défaut; `--cuda-sub-launches`
+/// l'emporte sur `sub_launches` dès qu'il n'est plus à 1
+#[cfg(feature = "cuda")]
+fn cuda_grid(cli: &Cli) -> anyhow::Result<config::CudaConfig> {
+    let mut grid = match &cli.config {
+        Some(path) => config::Config::load(path).context(Failure::Config)?.cuda_config,
+        None => config::CudaConfig::default(),
+    };
+    if cli.cuda_sub_launches != 1 {
+        grid.sub_launches = cli.cuda_sub_launches;
+    }
+    Ok(grid)
+}
+
+/// Backend OpenCL configuré par la CLI
+#[cfg(feature = "opencl")]
+fn opencl_backend(cli: &Cli, hash_algo: HashAlgo) -> anyhow::Result<opencl_miner::OpenClMiner> {
+    let miner = opencl_miner::OpenClMiner::new(opencl_device(cli)?)?.with_hash_algo(hash_algo);
+    match cli.opencl_work_items {
+        Some(work_items) => miner.with_work_items(work_items),
+        None => Ok(miner),
+    }
+}
+
+/// Backend GPU demandé par `--backend`: une init en échec est fatale avec `--no-fallback`,
+/// sinon repli sur le CPU
+fn explicit_gpu_backend(
+    name: &str,
+    init: anyhow::Result<Box<dyn MinerBackend>>,
+    no_fallback: bool,
+    cpu: impl FnOnce() -> Box<dyn MinerBackend>,
+) -> anyhow::Result<Box<dyn MinerBackend>> {
+    match init {
+        Ok(backend) => {
+            info!("   ✓ {} initialized", backend.name());
+            Ok(backend)
+        }
+        Err(e) => {
+            error!("   ✗ {} init failed: {:#}", name, e);
+            if no_fallback {
+                return Err(e.context(format!("--backend {} failed to initialize (--no-fallback)", name)).context(Failure::Config));
+            }
+            warn!("   Falling back to CPU (--no-fallback makes this an error)");
+            Ok(cpu())
+        }
+    }
+}
+
+/// Premier backend de `priority` qui s'initialise (`cpu` toujours), sinon `cpu`
+///
+/// Un GPU absent (pas de driver, aucun device, backend non compilé) n'est jamais fatal
+/// en mode auto, même avec `--no-fallback`; l'échec d'un backend compilé est loggé.
+fn auto_backend(
+    priority: &[&str],
+    init: impl Fn(&str) -> anyhow::Result<Box<dyn MinerBackend>>,
+    cpu: impl FnOnce() -> Box<dyn MinerBackend>,
+) -> Box<dyn MinerBackend> {
+    for &name in priority {
+        if name == "cpu" {
+            info!("   ✓ Using CPU");
+            return cpu();
+        }
+        match init(name) {
+            Ok(backend) => {
+                info!("   ✓ Using {}", backend.name());
+                return backend;
+            }
+            Err(e) if COMPILED_BACKENDS.contains(&name) => warn!("   {} init failed: {:#}", name, e),
+            Err(e) => debug!("   {} unavailable: {:#}", name, e),
+        }
+    }
+
+    info!("   No GPU, using CPU");
+    cpu()
+}
+
+/// Backends `names` en parallèle; avec `strict`, un backend indisponible est une
+/// erreur de configuration, sinon il est ignoré
+fn composite_backend(names: &[&str], strict: bool, cli: &Cli, hash_algo: HashAlgo) -> anyhow::Result<Box<dyn MinerBackend>> {
+    let mut backends = Vec::new();
+    for name in names {
+        match try_backend(name, cli, hash_algo) {
+            Ok(backend) => {
+                info!("   ✓ {} initialized", backend.name());
+                backends.push(backend);
+            }
+            Err(e) if strict => return Err(e.context(format!("Backend '{}' unavailable", name)).context(Failure::Config)),
+            Err(e) => debug!("   Skipping {}: {:#}", name, e),
+        }
+    }
+
+    let composite = miner::CompositeMiner::new(backends).with_gpu_numa(cli.affinity_numa);
+    info!("   Using {} in parallel", composite.name());
+    Ok(Box::new(composite))
+}
+
+/// Vérifie que `--mint` est le mint du PoW config: sinon le token account dérivé est
+/// celui d'un autre token et les récompenses sont perdues
+fn check_mint(configured: &Pubkey, state: &chain::PowState, force: bool) -> anyhow::Result<()> {
+    if *configured == state.mint {
+        return Ok(());
+    }
+    let message = format!("--mint {} does not match the PoW config mint {}", configured, state.mint);
+    if !force {
+        return Err(anyhow::anyhow!("{} (use --force to mine anyway)", message).context(Failure::Config));
+    }
+    warn!("{}, continuing because of --force", message);
+    Ok(())
+}
+
+/// Travail d'un round de mining à partir de l'état on-chain
+///
+/// Le programme vérifie la preuve avec `blocks_mined` comme block number: relu à
+/// chaque round, il change en même temps que le challenge
+fn round_job(
+    state: &chain::PowState,
+    miner_pubkey: [u8; 32],
+    hash_algo: HashAlgo,
+    target: Option<u128>,
+    block_number_override: Option<u64>,
+    derivation: ChallengeDerivation,
+) -> report::BenchJob {
+    let block_number = block_number_override.unwrap_or(state.blocks_mined);
+    report::BenchJob {
+        label: None,
+        challenge: derivation.derive(&state.challenge, block_number),
+        miner_pubkey,
+        block_number,
+        difficulty: state.difficulty,
+        target,
+        hash_algo,
+        compare_mode: CompareMode::default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verbose_flags_enable_debug() {
+        let cli = Cli::try_parse_from(["miner", "-vv"]).unwrap();
+        assert_eq!(logging::level(cli.quiet, cli.verbose), log::LevelFilter::Debug);
+
+        assert!(Cli::try_parse_from(["miner", "-q", "-v"]).is_err());
+    }
+
+    #[test]
+    fn test_difficulty_subcommand_args() {
+        let cli = Cli::try_parse_from(["miner", "difficulty", "--target", "000010c6f7a0b5ed8d36b4c7f3493858"]).unwrap();
+        assert!(matches!(cli.command, Some(Command::Difficulty { difficulty: None, target: Some(_), full: false })));
+
+        assert!(Cli::try_parse_from(["miner", "difficulty"]).is_err());
+        assert!(Cli::try_parse_from(["miner", "difficulty", "--difficulty", "10", "--target", "ff"]).is_err());
+    }
+
+    #[test]
+    fn test_plan_subcommand() {
+        let cli = Cli::try_parse_from(["miner", "plan", "--difficulty", "1000", "--hashrate", "1000"]).unwrap();
+        assert!(matches!(cli.command, Some(Command::Plan { difficulty: 1000, hashrate: Some(_), .. })));
+
+        let out = describe_plan(1_000, 0.95, Some(1_000.0));
+        assert!(out.contains("Hashes for 95.0%:     2995\n"));
+        assert!(out.contains("ETA (mean):           1.0s\n"));
+        assert!(out.contains("ETA (95.0%):          3.0s\n"));
+
+        assert!(describe_plan(1_000, 1.0, Some(1_000.0)).contains("never guaranteed"));
+        assert_eq!(exit::code(&run_plan(1_000, 1.5, None)), 3);
+    }
+
+    #[test]
+    fn test_hash_subcommand_known_preimage() {
+        use pow::vectors;
+
+        let cli = Cli::try_parse_from([
+            "miner", "hash", "--challenge", &"11".repeat(32), "--pubkey", &"22".repeat(32),
+            "--nonce", "686", "--block-number", "7", "--difficulty", "1000",
+        ])
+        .unwrap();
+        assert!(matches!(cli.command, Some(Command::Hash { nonce: vectors::NONCE, difficulty: Some(1000), .. })));
+
+        let out = describe_hash(&vectors::CHALLENGE, &vectors::MINER_PUBKEY, vectors::NONCE, vectors::BLOCK_NUMBER, Some(1000), HashAlgo::Sha256);
+        assert!(out.contains(vectors::DIGEST_HEX));
+        assert!(out.contains(&format!("{:032x}", vectors::HASH_VALUE)));
+        assert!(out.contains("Below target: true"));
+
+        let out = describe_hash(&vectors::CHALLENGE, &vectors::MINER_PUBKEY, vectors::NONCE, vectors::BLOCK_NUMBER, None, HashAlgo::Sha256);
+        assert!(!out.contains("Below target"));
+    }
+
+    #[test]
+    fn test_dump_subcommand_matches_compute_hash() {
+        use pow::vectors;
+
+        let cli = Cli::try_parse_from([
+            "miner", "--output", "json", "dump", "--challenge", &"11".repeat(32), "--miner-pubkey", &"22".repeat(32),
+            "--block-number", "7", "--from", "684", "--to", "687",
+        ])
+        .unwrap();
+        assert!(matches!(cli.command, Some(Command::Dump { from: 684, to: 687, .. })));
+
+        let lines: Vec<String> = dump_lines(vectors::CHALLENGE, vectors::MINER_PUBKEY, vectors::BLOCK_NUMBER, 684..=687, HashAlgo::Sha256, OutputFormat::Text).collect();
+        assert_eq!(lines.len(), 4);
+        for (nonce, line) in (684u128..=687).zip(&lines) {
+            let hash = pow::compute_hash(&vectors::CHALLENGE, &vectors::MINER_PUBKEY, nonce, vectors::BLOCK_NUMBER, HashAlgo::Sha256);
+            assert_eq!(*line, format!("{} {}", nonce, hex::encode(hash)));
+        }
+        assert_eq!(lines[2], format!("686 {}", vectors::DIGEST_HEX));
+
+        let json: Vec<serde_json::Value> = dump_lines(vectors::CHALLENGE, vectors::MINER_PUBKEY, vectors::BLOCK_NUMBER, 686..=686, HashAlgo::Sha256, OutputFormat::Json)
+            .map(|line| serde_json::from_str(&line).unwrap())
+            .collect();
+        assert_eq!(json, vec![serde_json::json!({ "nonce": "686", "hash": vectors::DIGEST_HEX })]);
+
+        let reversed = run_dump(&"11".repeat(32), &"22".repeat(32), 7, std::ops::RangeInclusive::new(5, 4), HashAlgo::Sha256, OutputFormat::Text);
+        assert_eq!(exit::code(&reversed), 3);
+    }
+
+    #[test]
+    fn test_tune_writes_best_grid_to_config() {
+        let cli = Cli::try_parse_from(["miner", "tune", "--threads-per-block", "128,256", "--write-config", "config.json"]).unwrap();
+        assert!(matches!(&cli.command, Some(Command::Tune { threads_per_block, num_blocks, .. }) if *threads_per_block == [128, 256] && num_blocks.len() == 6));
+        let error = run_tune(&cli, &[100], &[1024], Duration::from_millis(1), None, HashAlgo::Sha256).err().unwrap();
+        assert!(format!("{:#}", error).contains("not a positive multiple of the warp size"), "{:#}", error);
+
+        // Fichier existant au format du README: seule la grille CUDA change
+        let path = std::env::temp_dir().join(format!("pow-miner-tune-{}.json", std::process::id()));
+        std::fs::write(&path, r#"{"rpc_url": "http://localhost:8899", "cpu_config": {"threads": 3}, "cuda_config": {"device_id": 1, "threads_per_block": 256, "num_blocks": 1024}}"#).unwrap();
+        write_tuned_config(&path, &miner::GridRun { threads_per_block: 512, num_blocks: 4096, hashrate_hs: 1e9 }).unwrap();
+        let config: config::Config = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!((config.cuda_config.threads_per_block, config.cuda_config.num_blocks), (512, 4096));
+        assert_eq!((config.cuda_config.device_id, config.cpu_config.threads), (1, 3));
+        assert_eq!(config.rpc_url, "http://localhost:8899");
+    }
+
+    #[cfg(feature = "cuda")]
+    #[test]
+    fn test_cuda_grid_from_tuned_config() {
+        assert_eq!(cuda_grid(&Cli::try_parse_from(["miner"]).unwrap()).unwrap().num_blocks, 1024);
+
+        // Grille écrite par tune, relue par --config
+        let path = std::env::temp_dir().join(format!("pow-miner-grid-{}.json", std::process::id()));
+        write_tuned_config(&path, &miner::GridRun { threads_per_block: 128, num_blocks: 8192, hashrate_hs: 1e9 }).unwrap();
+        let cli = Cli::try_parse_from(["miner", "--config", path.to_str().unwrap()]).unwrap();
+        let grid = cuda_grid(&cli).unwrap();
+        assert_eq!((grid.threads_per_block, grid.num_blocks), (128, 8192));
+
+        // sub_launches du fichier, sauf --cuda-sub-launches explicite
+        std::fs::write(&path, r#"{"cuda_config": {"sub_launches": 16}}"#).unwrap();
+        assert_eq!(cuda_grid(&cli).unwrap().sub_launches, 16);
+        let cli_override = Cli::try_parse_from(["miner", "--config", path.to_str().unwrap(), "--cuda-sub-launches", "4"]).unwrap();
+        assert_eq!(cuda_grid(&cli_override).unwrap().sub_launches, 4);
+
+        // Grille invalide: erreur de configuration, pas la grille par défaut
+        std::fs::write(&path, r#"{"cuda_config": {"threads_per_block": 100}}"#).unwrap();
+        let error = cuda_grid(&cli).err().unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(exit::code(&Err(error)), 3);
+    }
+
+    #[test]
+    fn test_replay_subcommand_reports_valid_and_invalid_entries() {
+        use pow::vectors;
+
+        let cli = Cli::try_parse_from(["miner", "replay", "--solution-log", "solutions.jsonl", "--difficulty", "1000"]).unwrap();
+        assert!(matches!(cli.command, Some(Command::Replay { difficulty: Some(1000), .. })));
+
+        let job = report::BenchJob {
+            label: None,
+            challenge: vectors::CHALLENGE,
+            miner_pubkey: vectors::MINER_PUBKEY,
+            block_number: vectors::BLOCK_NUMBER,
+            difficulty: 1_000,
+            target: None,
+            hash_algo: HashAlgo::Sha256,
+            compare_mode: CompareMode::Low128,
+        };
+        let hash = pow::compute_hash(&job.challenge, &job.miner_pubkey, vectors::NONCE, job.block_number, HashAlgo::Sha256);
+        let valid = solution_log::SolutionRecord { submitted: true, accepted: true, ..solution_log::SolutionRecord::new(&job, vectors::NONCE, &hash) };
+        // Soumis puis rejeté: le nonce ne correspond pas au hash enregistré
+        let invalid = solution_log::SolutionRecord { nonce: vectors::NONCE + 1, accepted: false, ..valid.clone() };
+
+        let path = std::env::temp_dir().join(format!("pow-miner-replay-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let log = solution_log::SolutionLog::open(&path).unwrap();
+        log.append(&valid).unwrap();
+        log.append(&invalid).unwrap();
+
+        let out = describe_replay(&solution_log::read(&path).unwrap(), None);
+        assert!(out.contains(&format!("#1 block 7 nonce {} (accepted): valid\n", vectors::NONCE)));
+        assert!(out.contains(&format!("#2 block 7 nonce {} (rejected): INVALID: recorded hash", vectors::NONCE + 1)));
+        assert!(out.ends_with("2 entries, 1 valid, 1 invalid\n"));
+
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(exit::code(&run_replay(&path, None)), 3);
+    }
+
+    #[test]
+    fn test_round_job_uses_fetched_block_number() {
+        let state = chain::PowState {
+            authority: Default::default(),
+            mint: Default::default(),
+            difficulty: 1_000,
+            last_block_ts: 0,
+            blocks_mined: 42,
+            total_supply_mined: 0,
+            challenge: [7u8; 32],
+            pending_reward_tokens: 0,
+            fee_sol: 0,
+            fees: Default::default(),
+            launch_ts: 0,
+            is_initialized: true,
+            is_paused: false,
+        };
+        let miner_pubkey = [3u8; 32];
+
+        let job = round_job(&state, miner_pubkey, HashAlgo::Sha256, None, None, ChallengeDerivation::Raw);
+        assert_eq!(job.block_number, 42);
+
+        let nonce = miner::SimpleCpuMiner::default()
+            .mine(&job.challenge, &job.miner_pubkey, job.block_number, job.target(), u128::MAX)
+            .unwrap();
+        assert!(pow::verify_nonce(&state.challenge, &miner_pubkey, nonce, state.blocks_mined, job.target(), HashAlgo::Sha256));
+
+        assert_eq!(round_job(&state, miner_pubkey, HashAlgo::Sha256, None, Some(7), ChallengeDerivation::Raw).block_number, 7);
+
+        // Challenge dérivé: le préimage utilise SHA256(graine || blocks_mined)
+        let hashed = round_job(&state, miner_pubkey, HashAlgo::Sha256, None, None, ChallengeDerivation::Hashed);
+        assert_eq!(hashed.challenge, ChallengeDerivation::Hashed.derive(&state.challenge, 42));
+        assert_ne!(hashed.challenge, state.challenge);
+    }
+
+    #[test]
+    fn test_explicit_target_overrides_difficulty() {
+        use pow::vectors::*;
+
+        // Target égal à la valeur du hash: rejeté (comparaison stricte); un de plus: accepté
+        for (target, valid) in [(HASH_VALUE, false), (HASH_VALUE + 1, true)] {
+            let hex = format!("{:032x}", target);
+            let cli = Cli::try_parse_from(["miner", "--difficulty", "1", "--target", &hex]).unwrap();
+            let job = benchmark_job(&cli, HashAlgo::Sha256).unwrap();
+            assert_eq!(job.target(), target);
+            assert_eq!(pow::verify_nonce(&CHALLENGE, &MINER_PUBKEY, NONCE, BLOCK_NUMBER, job.target(), HashAlgo::Sha256), valid);
+        }
+
+        assert!(Cli::try_parse_from(["miner", "--target", "abcd"]).is_err());
+    }
+
+    #[test]
+    fn test_composite_backend_selection() {
+        let cli = Cli::try_parse_from(["miner", "-b", "cpu+cpu", "-t", "1"]).unwrap();
+        assert_eq!(composite_backend(&["cpu", "cpu"], true, &cli, HashAlgo::Sha256).unwrap().name(), "CPU+CPU");
+
+        // `all` ignore les backends non compilés, une liste explicite les refuse
+        assert!(composite_backend(&["cpu", "nope"], false, &cli, HashAlgo::Sha256).is_ok());
+        let strict = composite_backend(&["cpu", "nope"], true, &cli, HashAlgo::Sha256).map(|_| ());
+        assert_eq!(exit::code(&strict), 3);
+    }
+
+    #[test]
+    fn test_opencl_device_from_environment() {
+        std::env::set_var(OPENCL_DEVICE_ENV, "2");
+        let cli = Cli::try_parse_from(["miner", "-b", "opencl"]).unwrap();
+        assert_eq!(opencl_device(&cli).unwrap(), 2);
+        // --device l'emporte sur la variable, qui ne concerne pas CUDA
+        let cli = Cli::try_parse_from(["miner", "-b", "opencl", "--device", "1"]).unwrap();
+        assert_eq!(opencl_device(&cli).unwrap(), 1);
+        assert_eq!(gpu_device(&Cli::try_parse_from(["miner"]).unwrap()), 0);
+
+        std::env::set_var(OPENCL_DEVICE_ENV, "gpu0");
+        assert_eq!(exit::code(&opencl_device(&Cli::try_parse_from(["miner"]).unwrap()).map(|_| ())), 3);
+
+        std::env::remove_var(OPENCL_DEVICE_ENV);
+        assert_eq!(opencl_device(&Cli::try_parse_from(["miner"]).unwrap()).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_auto_backend_without_gpu_devices() {
+        // Zéro device pour chaque backend GPU: repli sur le CPU, sans panique
+        let tried = std::sync::Mutex::new(Vec::new());
+        let miner = auto_backend(
+            &["cuda", "opencl"],
+            |name| {
+                tried.lock().unwrap().push(name.to_string());
+                let kind = if name == "cuda" { "CUDA" } else { "OpenCL" };
+                miner::select_device::<usize>(kind, Vec::new(), 0).map(|_| unreachable!())
+            },
+            || Box::new(miner::CpuMiner::new(1)),
+        );
+
+        assert_eq!(miner.name(), "CPU");
+        assert_eq!(*tried.lock().unwrap(), vec!["cuda", "opencl"]);
+
+        // Le premier GPU disponible l'emporte
+        let miner = auto_backend(&["cuda"], |_| Ok(Box::new(miner::SimpleCpuMiner::default()) as Box<dyn MinerBackend>), || unreachable!());
+        assert_eq!(miner.name(), "CPU (Simple)");
+    }
+
+    #[test]
+    fn test_explicit_gpu_fails_with_no_fallback() {
+        let cli = Cli::try_parse_from(["miner", "-b", "cuda", "--no-fallback"]).unwrap();
+        assert!(cli.no_fallback);
+
+        let failed = || Err(anyhow::anyhow!("CUDA driver library not found"));
+        let error = explicit_gpu_backend("cuda", failed(), cli.no_fallback, || unreachable!()).err().unwrap();
+        assert_eq!(exit::code(&Err(error)), Failure::Config.code());
+
+        // Sans --no-fallback: repli sur le CPU
+        let miner = explicit_gpu_backend("cuda", failed(), false, || Box::new(miner::CpuMiner::new(1))).unwrap();
+        assert_eq!(miner.name(), "CPU");
+
+        // Backend non compilé dans ce binaire: même traitement qu'un driver absent
+        #[cfg(not(feature = "cuda"))]
+        assert!(explicit_gpu_backend("cuda", try_backend("cuda", &cli, HashAlgo::Sha256), true, || unreachable!()).is_err());
+    }
+
+    #[test]
+    fn test_auto_falls_back_despite_no_fallback() {
+        let cli = Cli::try_parse_from(["miner", "--no-fallback"]).unwrap();
+        assert_eq!(cli.backend, "auto");
+
+        let priority: Vec<&str> = cli.backend_priority.iter().map(String::as_str).collect();
+        let miner = auto_backend(&priority, |name| Err(anyhow::anyhow!("{} init failed", name)), || Box::new(miner::CpuMiner::new(1)));
+        assert_eq!(miner.name(), "CPU");
+    }
+
+    #[test]
+    fn test_backend_priority_picks_first_available() {
+        let cli = Cli::try_parse_from(["miner"]).unwrap();
+        assert_eq!(cli.backend_priority, ["cuda", "opencl", "cpu"]);
+        let cli = Cli::try_parse_from(["miner", "--backend-priority", "opencl,cuda,cpu"]).unwrap();
+        assert_eq!(cli.backend_priority, ["opencl", "cuda", "cpu"]);
+        assert!(Cli::try_parse_from(["miner", "--backend-priority", "opencl,metal"]).is_err());
+
+        // OpenCL en tête mais absent: CUDA, qui vient ensuite, l'emporte
+        let tried = std::sync::Mutex::new(Vec::new());
+        let priority: Vec<&str> = cli.backend_priority.iter().map(String::as_str).collect();
+        let miner = auto_backend(
+            &priority,
+            |name| {
+                tried.lock().unwrap().push(name.to_string());
+                match name {
+                    "cuda" => Ok(Box::new(miner::SimpleCpuMiner::default()) as Box<dyn MinerBackend>),
+                    _ => Err(anyhow::anyhow!("No OpenCL platform")),
+                }
+            },
+            || unreachable!(),
+        );
+        assert_eq!(miner.name(), "CPU (Simple)");
+        assert_eq!(*tried.lock().unwrap(), vec!["opencl", "cuda"]);
+
+        // CPU en tête: aucun GPU n'est essayé
+        let miner = auto_backend(&["cpu", "cuda"], |_| unreachable!(), || Box::new(miner::CpuMiner::new(1)));
+        assert_eq!(miner.name(), "CPU");
+    }
+
+    #[test]
+    fn test_full_256_compare_mode_requires_cpu_backend() {
+        let cli = Cli::try_parse_from(["miner", "--compare-mode", "full-256"]).unwrap();
+        assert_eq!(cli.compare_mode, CompareMode::Full256);
+        assert_eq!(Cli::try_parse_from(["miner"]).unwrap().compare_mode, CompareMode::Low128);
+
+        assert!(check_compare_mode(&miner::CpuMiner::new(1), CompareMode::Full256).is_ok());
+        assert!(check_compare_mode(&miner::SimpleCpuMiner::default(), CompareMode::Low128).is_ok());
+        // Backend qui ne compare que les 128 bits bas (comme les kernels GPU): erreur de config
+        let refused = check_compare_mode(&miner::SimpleCpuMiner::default(), CompareMode::Full256);
+        assert_eq!(exit::code(&refused), 3);
+    }
+
+    #[tokio::test]
+    async fn test_trivial_difficulty_benchmark_measures_throughput() {
+        for difficulty in ["0", "1"] {
+            let cli = Cli::try_parse_from(["miner", "--benchmark", "--difficulty", difficulty, "--hash-budget", "100000", "--warmup-ms", "0"]).unwrap();
+            let job = benchmark_job(&cli, HashAlgo::Sha256).unwrap();
+            // Difficulté 0 saturée à 1: tout hash est valide, sans division par zéro
+            assert_eq!(job.target(), u128::MAX);
+            assert_eq!(trivial_difficulty_hashes(&job, &cli), Some(100_000));
+
+            // Le budget arrête la mesure, pas un nonce trouvé au premier hash
+            let miner: Arc<dyn MinerBackend> = Arc::new(miner::CpuMiner::new(2));
+            let (hashes, elapsed) = counted_benchmark(miner, &job, 100_000, MiningControl::new()).await.unwrap();
+            assert!(hashes >= 100_000, "{} hashes at difficulty {}", hashes, difficulty);
+            assert!(elapsed > Duration::ZERO);
+
+            run_benchmark(Box::new(miner::CpuMiner::new(1)), &cli, HashAlgo::Sha256).await.unwrap();
+        }
+
+        // Difficulté réelle ou target explicite: mining normal
+        let cli = Cli::try_parse_from(["miner", "--benchmark", "--difficulty", "2"]).unwrap();
+        assert_eq!(trivial_difficulty_hashes(&benchmark_job(&cli, HashAlgo::Sha256).unwrap(), &cli), None);
+        let cli = Cli::try_parse_from(["miner", "--benchmark", "--difficulty", "1", "--target", &"f".repeat(32)]).unwrap();
+        assert_eq!(trivial_difficulty_hashes(&benchmark_job(&cli, HashAlgo::Sha256).unwrap(), &cli), None);
+    }
+
+    #[test]
+    fn test_verify_benchmark_reports() {
+        assert!(Cli::try_parse_from(["miner", "--cpu-only-verify"]).is_err());
+        assert!(Cli::try_parse_from(["miner", "--benchmark", "--cpu-only-verify"]).unwrap().cpu_only_verify);
+
+        let reports = verify_benchmark(2, HashAlgo::Sha256, Duration::from_millis(50));
+        let names: Vec<&str> = reports.iter().map(|r| r.backend.as_str()).collect();
+        assert_eq!(names, ["verify_nonce (1 thread)", "verify_nonce (2 threads)", "CPU miner (2 threads)"]);
+        assert!(reports.iter().all(|r| r.hashes > 0 && r.hashrate_hs > 0.0));
+    }
+
+    #[test]
+    fn test_special_thread_counts() {
+        assert_eq!(resolve_threads(None, None, 8), 8);
+        assert_eq!(resolve_threads(Some(3), None, 8), 3);
+        assert_eq!(resolve_threads(Some(0), None, 8), 7);
+        assert_eq!(resolve_threads(Some(0), None, 1), 1);
+        assert_eq!(resolve_threads(None, Some(2), 8), 6);
+        assert_eq!(resolve_threads(None, Some(8), 8), 1);
+
+        let cli = Cli::try_parse_from(["miner", "--leave-cores", "1"]).unwrap();
+        assert_eq!(cpu_threads(&cli), num_cpus::get().saturating_sub(1).max(1));
+        assert!(Cli::try_parse_from(["miner", "--threads", "4", "--leave-cores", "1"]).is_err());
+    }
+
+    #[test]
+    fn test_thread_scaling_counts() {
+        assert!(Cli::try_parse_from(["miner", "--thread-scaling"]).is_err());
+        assert!(Cli::try_parse_from(["miner", "--benchmark", "--thread-scaling", "--cpu-only-verify"]).is_err());
+
+        let points = thread_scaling(6, HashAlgo::Sha256, Duration::from_millis(30));
+        let threads: Vec<usize> = points.iter().map(|p| p.threads).collect();
+        assert_eq!(threads, [1, 2, 4, 6]);
+        assert!(points.iter().all(|p| p.hashes > 0));
+        assert_eq!(points[0].efficiency, 1.0);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_timed_benchmark_honors_duration() {
+        let cli = Cli::try_parse_from(["miner", "--benchmark", "-b", "cpu", "--time", "300ms"]).unwrap();
+        assert_eq!(cli.time, Some(Duration::from_millis(300)));
+        assert!(Cli::try_parse_from(["miner", "--time", "1"]).is_err());
+
+        let job = benchmark_job(&cli, HashAlgo::Sha256).unwrap();
+        let miner: Arc<dyn MinerBackend> = Arc::new(miner::CpuMiner::new(1));
+        let (hashes, elapsed) = timed_benchmark(miner, &job, Duration::from_millis(300), MiningControl::new()).await.unwrap();
+
+        assert!(hashes > 0);
+        assert!(elapsed >= Duration::from_millis(300), "{:?}", elapsed);
+        assert!(elapsed < Duration::from_millis(800), "{:?}", elapsed);
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("30s"), Ok(Duration::from_secs(30)));
+        assert_eq!(parse_duration("10m"), Ok(Duration::from_secs(600)));
+        assert_eq!(parse_duration("250ms"), Ok(Duration::from_millis(250)));
+        assert_eq!(parse_duration("5"), Ok(Duration::from_secs(5)));
+        assert!(parse_duration("5d").is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_soak_against_mock_chain() {
+        let cli = Cli::try_parse_from(["miner", "-b", "cpu", "-t", "2", "--mock-chain", "--soak", "300ms", "--difficulty", "1000"]).unwrap();
+        let chain = mock_chain::MockChain::new(cli.difficulty, HashAlgo::Sha256);
+        let miner: Arc<dyn MinerBackend> = Arc::new(miner::CpuMiner::new(2));
+
+        let control = MiningControl::new();
+        let timer = control.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(cli.soak.unwrap()).await;
+            timer.stop();
+        });
+
+        let stats = mine_loop(&chain, miner, &cli, HashAlgo::Sha256, control).await.unwrap();
+        assert!(stats.solutions() > 0);
+        assert!(stats.errors().is_empty(), "{:?}", stats.errors());
+        // Chaque solution a été acceptée et a fait tourner le challenge
+        assert_eq!(chain.state().blocks_mined, stats.solutions() as u64);
+        // Récompenses comptées sur les soldes relus après chaque bloc (1 token par bloc simulé)
+        let ledger = stats.summary(Duration::ZERO).ledger;
+        assert_eq!(ledger.tokens_earned, stats.solutions() as f64);
+        assert_eq!(ledger.net_profit_sol, None);
+
+        assert!(Cli::try_parse_from(["miner", "--soak", "1s"]).is_err());
+        assert!(Cli::try_parse_from(["miner", "--price-sol-per-token", "-0.1"]).is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_mock_backend_paces_the_loop() {
+        let cli = Cli::try_parse_from(["miner", "-b", "mock", "--simulate-hashrate", "10000", "--mock-chain", "--difficulty", "1000"]).unwrap();
+        let chain = mock_chain::MockChain::new(cli.difficulty, HashAlgo::Sha256);
+        let miner: Arc<dyn MinerBackend> = Arc::from(try_backend("mock", &cli, HashAlgo::Sha256).unwrap());
+        assert_eq!(miner.name(), "Mock");
+
+        let control = MiningControl::new();
+        let timer = control.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(350)).await;
+            timer.stop();
+        });
+
+        // 1000 hashes à 10 kH/s: au plus un bloc toutes les 100 ms
+        let stats = mine_loop(&chain, miner, &cli, HashAlgo::Sha256, control).await.unwrap();
+        assert!((1..=3).contains(&stats.solutions()), "{} solutions", stats.solutions());
+        assert!(stats.errors().is_empty(), "{:?}", stats.errors());
+        assert_eq!(chain.state().blocks_mined, stats.solutions() as u64);
+
+        assert!(Cli::try_parse_from(["miner", "--simulate-hashrate", "0"]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_status_against_mock_chain() {
+        let cli = Cli::try_parse_from(["miner", "--mock-chain", "--difficulty", "1000", "--output", "json", "status"]).unwrap();
+        assert!(matches!(cli.command, Some(Command::Status)));
+
+        let chain = mock_chain::MockChain::new(cli.difficulty, HashAlgo::Sha256);
+        let state = chain.state();
+        let status = protocol_status(&chain).await.unwrap();
+
+        let json = serde_json::to_value(&status).unwrap();
+        assert_eq!(json["difficulty"], 1000);
+        assert_eq!(json["blocks_mined"], state.blocks_mined);
+        assert_eq!(json["total_supply_mined"], state.total_supply_mined);
+        assert_eq!(json["challenge"], hex::encode(state.challenge));
+        assert_eq!(json["fee_sol"], state.fee_sol);
+        assert_eq!(json["total_fees_collected"], state.fees.total_fees_collected);
+        assert_eq!(json["fee_vault_balance"], 0);
+        assert_eq!(json["is_paused"], false);
+        assert!(json["seconds_since_last_block"].as_i64().unwrap() >= 0);
+
+        let text = describe_status(&status);
+        assert!(text.contains("Difficulty:           1.000e3\n"));
+        assert!(text.contains(&format!("Challenge:            {}…\n", hex::encode(&state.challenge[..8]))));
+        assert!(text.contains("Paused:               false\n"));
+        assert!(text.contains("Fee vault balance:    0.000000000 SOL\n"));
+    }
+
+    #[tokio::test]
+    async fn test_mint_mismatch_fails_startup() {
+        let wrong_mint = Pubkey::new_unique();
+        let chain = mock_chain::MockChain::new(1000, HashAlgo::Sha256).with_configured_mint(wrong_mint);
+        let miner: Arc<dyn MinerBackend> = Arc::new(miner::CpuMiner::new(1));
+
+        let cli = Cli::try_parse_from(["miner", "--mock-chain"]).unwrap();
+        let result = mine_loop(&chain, miner.clone(), &cli, HashAlgo::Sha256, MiningControl::new()).await.map(|_| ());
+        assert_eq!(exit::code(&result), 3);
+        assert!(format!("{:#}", result.unwrap_err()).contains(&wrong_mint.to_string()));
+        assert_eq!(chain.submissions(), 0);
+
+        // --force: simple avertissement, le mining démarre
+        let cli = Cli::try_parse_from(["miner", "--mock-chain", "--force"]).unwrap();
+        let control = MiningControl::new();
+        control.stop();
+        assert!(mine_loop(&chain, miner, &cli, HashAlgo::Sha256, control).await.is_ok());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_duplicate_nonce_is_submitted_once() {
+        // Le RPC renvoie toujours l'ancien challenge: le même nonce est retrouvé à chaque round
+        let chain = mock_chain::MockChain::new(1000, HashAlgo::Sha256).with_stale_reads();
+        let miner: Arc<dyn MinerBackend> = Arc::new(miner::CpuMiner::new(1));
+        let cli = Cli::try_parse_from(["miner", "--mock-chain"]).unwrap();
+
+        let control = MiningControl::new();
+        let timer = control.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(DUPLICATE_RETRY_DELAY * 3).await;
+            timer.stop();
+        });
+
+        let stats = mine_loop(&chain, miner, &cli, HashAlgo::Sha256, control).await.unwrap();
+        assert_eq!(chain.submissions(), 1);
+        assert_eq!(stats.solutions(), 1);
+        assert!(stats.errors().is_empty(), "{:?}", stats.errors());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_oneshot_mines_live_challenge() {
+        let chain = mock_chain::MockChain::new(1000, HashAlgo::Sha256);
+        let state = chain.state();
+        let miner: Arc<dyn MinerBackend> = Arc::new(miner::CpuMiner::new(1));
+        let pubkey = chain.miners()[0].pubkey().to_bytes();
+        let target = pow::difficulty_to_target(state.difficulty);
+
+        // Sans --submit: rien n'est envoyé
+        let cli = Cli::try_parse_from(["miner", "--mock-chain", "--oneshot"]).unwrap();
+        let nonce = oneshot(&chain, miner.clone(), &cli, HashAlgo::Sha256, MiningControl::new()).await.unwrap();
+        assert!(pow::verify_nonce(&state.challenge, &pubkey, nonce, state.blocks_mined, target, HashAlgo::Sha256));
+        assert_eq!(chain.submissions(), 0);
+
+        let cli = Cli::try_parse_from(["miner", "--mock-chain", "--oneshot", "--submit"]).unwrap();
+        assert_eq!(oneshot(&chain, miner, &cli, HashAlgo::Sha256, MiningControl::new()).await.unwrap(), nonce);
+        assert_eq!(chain.submissions(), 1);
+        assert_eq!(chain.state().blocks_mined, 1);
+
+        assert!(Cli::try_parse_from(["miner", "--submit"]).is_err());
+    }
+
+    #[test]
+    fn test_max_onchain_nonce_caps_search() {
+        let cli = Cli::try_parse_from(["miner"]).unwrap();
+        assert_eq!(nonce_range(&cli), 0..u128::MAX);
+
+        let cli = Cli::try_parse_from(["miner", "--max-onchain-nonce", "u64"]).unwrap();
+        assert_eq!(nonce_range(&cli), 0..u64::MAX as u128 + 1);
+        let cli = Cli::try_parse_from(["miner", "--max-onchain-nonce", "0xff"]).unwrap();
+        assert_eq!(nonce_range(&cli), 0..256);
+        assert!(Cli::try_parse_from(["miner", "--max-onchain-nonce", "-1"]).is_err());
+
+        // Target facile: chaque thread trouve tout de suite, aucun nonce rendu au-delà du plafond
+        let cpu = miner::CpuMiner::new(4);
+        let nonce = cpu.mine_with_control(&[1; 32], &[2; 32], 0, u128::MAX, nonce_range(&cli), &MiningControl::new());
+        assert!(nonce.is_some_and(|nonce| nonce <= 0xff));
+
+        // Target inatteignable: la plage est épuisée sans dépasser le plafond
+        let control = MiningControl::new();
+        assert_eq!(cpu.mine_with_control(&[1; 32], &[2; 32], 0, 0, nonce_range(&cli), &control), None);
+        assert_eq!(control.hashes(), 256);
+
+        // --machine-offset: départ propre à la machine, sous le même plafond
+        assert_eq!(search_range(&cli, &[1; 32]), vec![nonce_range(&cli)]);
+        let cli = Cli::try_parse_from(["miner", "--max-onchain-nonce", "0xff", "--machine-offset"]).unwrap();
+        let nonces = search_range(&cli, &[1; 32]);
+        assert!(nonces[0].start < 128 && nonces[0].end == 256, "{:?}", nonces);
+
+        // Nonces sous le départ: parcourus après la fin, la plage entière est couverte
+        let control = MiningControl::new();
+        for range in &nonces {
+            assert_eq!(cpu.mine_with_control(&[1; 32], &[2; 32], 0, 0, range.clone(), &control), None);
+        }
+        assert_eq!(control.hashes(), 256);
+    }
+
+    #[tokio::test]
+    async fn test_stale_difficulty_skips_submission() {
+        // Lectures: solde (fee_sol), état du round, puis relecture avant l'envoi, déjà à la nouvelle difficulté
+        let chain = mock_chain::MockChain::new(1000, HashAlgo::Sha256).with_difficulty_change(2, u128::MAX);
+        let miner: Arc<dyn MinerBackend> = Arc::new(miner::CpuMiner::new(1));
+        let cli = Cli::try_parse_from(["miner", "--mock-chain"]).unwrap();
+
+        let control = MiningControl::new();
+        let timer = control.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            timer.stop();
+        });
+
+        let stats = mine_loop(&chain, miner, &cli, HashAlgo::Sha256, control).await.unwrap();
+        assert_eq!(chain.submissions(), 0);
+        assert_eq!(stats.solutions(), 0);
+        assert!(stats.errors().is_empty(), "{:?}", stats.errors());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_submit_failures_trip_breaker() {
+        // Le protocole simulé attend du double SHA256: toutes les preuves sont rejetées
+        let chain = mock_chain::MockChain::new(1000, HashAlgo::DoubleSha256);
+        let miner: Arc<dyn MinerBackend> = Arc::new(miner::CpuMiner::new(2));
+
+        let cli = Cli::try_parse_from(["miner", "--mock-chain", "--max-submit-failures", "1"]).unwrap();
+        let err = mine_loop(&chain, miner.clone(), &cli, HashAlgo::Sha256, MiningControl::new()).await.unwrap_err();
+        assert_eq!(exit::code(&Err(err)), 6);
+
+        // Avec --submit-cooldown, le mineur reprend après chaque pause au lieu de quitter
+        let cli = Cli::try_parse_from(["miner", "--mock-chain", "--max-submit-failures", "1", "--submit-cooldown", "10ms"]).unwrap();
+        let control = MiningControl::new();
+        let timer = control.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            timer.stop();
+        });
+        let stats = mine_loop(&chain, miner, &cli, HashAlgo::Sha256, control).await.unwrap();
+        assert!(stats.errors().len() >= 2, "{:?}", stats.errors());
+
+        assert!(Cli::try_parse_from(["miner", "--max-submit-failures", "0"]).is_err());
+    }
+
+    /// Backend vu comme un GPU, qui note le challenge et le premier nonce de chaque recherche
+    #[derive(Default)]
+    struct RecordingGpu {
+        inner: miner::SimpleCpuMiner,
+        starts: std::sync::Mutex<Vec<([u8; 32], u128)>>,
+    }
+
+    impl MinerBackend for RecordingGpu {
+        fn mine_with_control(&self, challenge: &[u8; 32], miner_pubkey: &[u8; 32], block_number: u64, target: u128, nonces: std::ops::Range<u128>, control: &MiningControl) -> Option<u128> {
+            self.starts.lock().unwrap().push((*challenge, nonces.start));
+            self.inner.mine_with_control(challenge, miner_pubkey, block_number, target, nonces, control)
+        }
+
+        fn name(&self) -> &str {
+            "GPU (recording)"
+        }
+
+        fn is_gpu(&self) -> bool {
+            true
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_gpu_keeps_mining_while_submissions_fail() {
+        // Preuves rejetées (double SHA256 attendu), sauf celles qui le vérifient aussi par hasard:
+        // le challenge ne change alors qu'exceptionnellement
+        let chain = mock_chain::MockChain::new(1000, HashAlgo::DoubleSha256);
+        let gpu = Arc::new(RecordingGpu::default());
+        let cli = Cli::try_parse_from(["miner", "--mock-chain", "--max-submit-failures", "1", "--submit-cooldown", "10ms"]).unwrap();
+
+        let control = MiningControl::new();
+        let timer = control.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            timer.stop();
+        });
+        mine_loop(&chain, gpu.clone(), &cli, HashAlgo::Sha256, control).await.unwrap();
+
+        // Chaque soumission porte un nouveau nonce: sur un même challenge, le GPU a repris
+        // après chaque solution au lieu de recommencer la plage
+        let starts = gpu.starts.lock().unwrap();
+        assert!(chain.submissions() >= 2, "{} submissions", chain.submissions());
+        assert!(starts.len() >= chain.submissions());
+        assert_eq!(starts[0].1, 0);
+        assert!(starts.windows(2).all(|pair| pair[0].0 != pair[1].0 || pair[0].1 < pair[1].1), "{:?}", starts);
+    }
+
+    /// mine_loop en --speculative pendant 300ms
+    async fn mine_speculatively(chain: &mock_chain::MockChain) -> stats::StatsSummary {
+        let miner: Arc<dyn MinerBackend> = Arc::new(miner::CpuMiner::new(2));
+        let cli = Cli::try_parse_from(["miner", "--mock-chain", "--speculative"]).unwrap();
+        let control = MiningControl::new();
+        let timer = control.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            timer.stop();
+        });
+        mine_loop(chain, miner, &cli, HashAlgo::Sha256, control).await.unwrap().summary(Duration::ZERO)
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_speculative_search_is_kept_when_prediction_matches() {
+        let chain = mock_chain::MockChain::new(1000, HashAlgo::Sha256);
+        let summary = mine_speculatively(&chain).await;
+
+        assert!(chain.state().blocks_mined >= 2, "{} blocks", chain.state().blocks_mined);
+        assert!(summary.speculations_confirmed >= 1);
+        assert_eq!(summary.speculations_discarded, 0);
+        assert!(summary.errors.is_empty(), "{:?}", summary.errors);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_speculative_search_is_discarded_when_prediction_differs() {
+        let chain = mock_chain::MockChain::new(1000, HashAlgo::Sha256).with_unpredictable_rotation();
+        let summary = mine_speculatively(&chain).await;
+
+        // Aucune preuve soumise sur un challenge prévu à tort: le mining reprend sur le bon
+        assert!(chain.state().blocks_mined >= 2, "{} blocks", chain.state().blocks_mined);
+        assert_eq!(summary.speculations_confirmed, 0);
+        assert!(summary.speculations_discarded >= 1);
+        assert!(summary.errors.is_empty(), "{:?}", summary.errors);
+        assert_eq!(chain.submissions() as u64, chain.state().blocks_mined);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_batch_submit_sends_several_proofs_per_transaction() {
+        let chain = mock_chain::MockChain::new(1000, HashAlgo::Sha256);
+        let miner: Arc<dyn MinerBackend> = Arc::new(miner::CpuMiner::new(2));
+        let cli = Cli::try_parse_from(["miner", "--mock-chain", "--batch-submit", "3"]).unwrap();
+        let control = MiningControl::new();
+        let timer = control.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            timer.stop();
+        });
+        let summary = mine_loop(&chain, miner, &cli, HashAlgo::Sha256, control.clone()).await.unwrap().summary(Duration::ZERO);
+
+        // Les lots interrompus par l'arrêt partent incomplets, les autres portent 3 preuves
+        let (blocks, transactions) = (chain.state().blocks_mined, chain.submissions() as u64);
+        assert!(transactions >= 1 && blocks > transactions, "{} blocks in {} transactions", blocks, transactions);
+        assert!(blocks <= 3 * transactions);
+        assert!(summary.errors.is_empty(), "{:?}", summary.errors);
+        assert_eq!(control.recent_solutions.snapshot().len() as u64, blocks.min(solution_log::RECENT_SOLUTIONS as u64));
+
+        // Borné par la taille de la transaction; sans prévision du challenge, une preuve par transaction
+        let state = chain.state();
+        assert_eq!(batch_submit_limit(&chain, &state, 20), 8);
+        assert_eq!(batch_submit_limit(&mock_chain::MockChain::new(1000, HashAlgo::Sha256).without_prediction(), &state, 3), 1);
+        assert!(Cli::try_parse_from(["miner", "--batch-submit", "2", "--speculative"]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_all_backends_comparison_covers_compiled_backends() {
+        let cli = Cli::try_parse_from(["miner", "--benchmark", "--all-backends", "--difficulty", "1000", "--warmup-ms", "0"]).unwrap();
+        let runs = compare_backends(&cli, HashAlgo::Sha256, &report::NullReporter, &MiningControl::new()).await.unwrap();
+
+        let names: Vec<String> = runs
+            .iter()
+            .map(|run| match run {
+                BackendRun::Measured { backend, .. } => backend.to_lowercase(),
+                BackendRun::Skipped { backend, .. } => backend.to_string(),
+            })
+            .collect();
+        assert_eq!(names, COMPILED_BACKENDS);
+        assert!(matches!(runs[0], BackendRun::Measured { hashrate_hs, .. } if hashrate_hs > 0.0));
+
+        assert!(Cli::try_parse_from(["miner", "--all-backends"]).is_err());
+    }
+
+    #[test]
+    fn test_invalid_target_exits_with_config_code() {
+        assert_eq!(exit::code(&run_difficulty(None, Some("not-hex"), false)), 3);
+        assert_eq!(exit::code(&run_difficulty(Some(1_000), None, false)), 0);
+
+        // Mêmes formes que le --target global: 128 bits, ou 256 bits tel qu'affiché par --full
+        let full = hex::encode(pow::difficulty_to_target_256(1_000));
+        for target in [full.as_str(), &format!("{:032x}", pow::difficulty_to_target(1_000)), "ffff"] {
+            assert_eq!(run_difficulty(None, Some(target), false).is_ok(), parse_target(target).is_ok(), "{}", target);
+        }
+        assert!(run_difficulty(None, Some(&full), true).is_ok());
+    }
 }
@@ -1,106 +1,681 @@
 //! Backends de mining
 
-use crate::pow;
+use crate::numa::{self, Node};
+use crate::pow::{self, CompareMode, HashAlgo, ShaImpl};
+use crate::solution_log::RecentSolutions;
+use crate::tasks;
+use anyhow::{anyhow, Result};
 use rayon::prelude::*;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{Arc, Mutex};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+/// Nombre de nonces testés par un thread entre deux mises à jour du compteur
+const BATCH_SIZE: u128 = 10_000;
+
+/// Intervalle de relais de l'arrêt global vers les backends d'un `CompositeMiner`
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Device `index` parmi ceux détectés pour un backend GPU (`kind`: "CUDA", "OpenCL")
+///
+/// Aucun device ou un index hors limites donne une erreur propre, gérée par le mode auto.
+#[cfg_attr(not(any(feature = "cuda", feature = "opencl")), allow(dead_code))]
+pub fn select_device<T>(kind: &str, mut devices: Vec<T>, index: usize) -> Result<T> {
+    if devices.is_empty() {
+        return Err(anyhow!("No {} device found", kind));
+    }
+    if index >= devices.len() {
+        return Err(anyhow!("{} device index {} out of range ({} available)", kind, index, devices.len()));
+    }
+    Ok(devices.swap_remove(index))
+}
+
+/// Retire les devices énumérés plusieurs fois (même GPU exposé par l'ICD du constructeur et
+/// par un ICD générique), en gardant la plateforme qui les expose en premier: chaque index de
+/// `--device` désigne alors un GPU distinct. `identity` donne la plateforme et la clé du device.
+/// Seules des plateformes différentes se recouvrent: deux cartes identiques sur une même
+/// plateforme sont gardées, et une autre plateforme perd au plus autant de devices de même clé.
+/// Retourne les devices uniques puis les doublons écartés.
+#[cfg_attr(not(feature = "opencl"), allow(dead_code))]
+pub fn dedup_devices<T, P, K>(devices: Vec<T>, identity: impl Fn(&T) -> (P, K)) -> (Vec<T>, Vec<T>)
+where
+    P: Eq + Hash + Clone,
+    K: Eq + Hash + Clone,
+{
+    // Par clé: première plateforme qui l'expose, et nombre de devices de cette clé par plateforme
+    let mut first_platform = HashMap::new();
+    let mut counts = HashMap::new();
+    for (platform, key) in devices.iter().map(&identity) {
+        first_platform.entry(key.clone()).or_insert_with(|| platform.clone());
+        *counts.entry((platform, key)).or_insert(0usize) += 1;
+    }
+
+    let mut seen = HashMap::new();
+    devices.into_iter().partition(|device| {
+        let (platform, key) = identity(device);
+        let first = &first_platform[&key];
+        let index = seen.entry((platform.clone(), key.clone())).or_insert(0usize);
+        *index += 1;
+        platform == *first || *index > counts[&(first.clone(), key)]
+    })
+}
+
+/// Compteur de hashes et flag d'arrêt partagés entre la boucle de mining et un backend
+#[derive(Clone)]
+pub struct MiningControl {
+    /// Nombre total de hashes calculés
+    pub hash_counter: Arc<AtomicU64>,
+    /// Passe à false pour interrompre le mining
+    pub running: Arc<AtomicBool>,
+    /// Hashes au-delà desquels le mining s'arrête (`--hash-budget`)
+    pub hash_budget: Option<u64>,
+    /// Première erreur d'un backend (allocation, lancement du kernel, driver), à ne pas
+    /// confondre avec « aucune solution »
+    pub failure: Arc<Mutex<Option<String>>>,
+    /// Hashes depuis le démarrage, tous rounds confondus (sonde de liveness)
+    pub total_hashes: Arc<AtomicU64>,
+    /// Passe à true au premier état on-chain lu avec succès (sonde de readiness)
+    pub state_fetched: Arc<AtomicBool>,
+    /// Dernières solutions trouvées, servies sur `/solutions`
+    pub recent_solutions: Arc<RecentSolutions>,
+}
+
+impl MiningControl {
+    pub fn new() -> Self {
+        Self {
+            hash_counter: Arc::new(AtomicU64::new(0)),
+            running: Arc::new(AtomicBool::new(true)),
+            hash_budget: None,
+            failure: Arc::new(Mutex::new(None)),
+            total_hashes: Arc::new(AtomicU64::new(0)),
+            state_fetched: Arc::new(AtomicBool::new(false)),
+            recent_solutions: Arc::new(RecentSolutions::default()),
+        }
+    }
+
+    /// Arrête le mining une fois `budget` hashes comptés (aucune limite si None)
+    pub fn with_hash_budget(mut self, budget: Option<u64>) -> Self {
+        self.hash_budget = budget;
+        self
+    }
+
+    /// Faux après `stop` ou une fois le budget de hashes épuisé
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::Relaxed) && !self.budget_exhausted()
+    }
+
+    /// Vrai si le mining s'est arrêté sur le budget de hashes, et non sur `stop`
+    pub fn budget_exhausted(&self) -> bool {
+        self.hash_budget.is_some_and(|budget| self.hashes() >= budget)
+    }
+
+    /// Demande l'arrêt du mining en cours
+    pub fn stop(&self) {
+        self.running.store(false, Ordering::SeqCst);
+    }
+
+    /// Nouveau compteur de hashes partageant le même flag d'arrêt et le même budget
+    /// (un par round de mining)
+    pub fn round(&self) -> Self {
+        Self {
+            hash_counter: Arc::new(AtomicU64::new(0)),
+            running: self.running.clone(),
+            hash_budget: self.hash_budget,
+            failure: Arc::new(Mutex::new(None)),
+            total_hashes: self.total_hashes.clone(),
+            state_fetched: self.state_fetched.clone(),
+            recent_solutions: self.recent_solutions.clone(),
+        }
+    }
+
+    pub fn add_hashes(&self, count: u64) {
+        self.hash_counter.fetch_add(count, Ordering::Relaxed);
+        self.total_hashes.fetch_add(count, Ordering::Relaxed);
+    }
+
+    /// Enregistre une lecture réussie de l'état on-chain
+    pub fn set_state_fetched(&self) {
+        self.state_fetched.store(true, Ordering::Relaxed);
+    }
+
+    pub fn hashes(&self) -> u64 {
+        self.hash_counter.load(Ordering::Relaxed)
+    }
+
+    /// Signale l'échec d'un backend; seule la première erreur est gardée
+    #[cfg_attr(not(any(feature = "cuda", feature = "opencl")), allow(dead_code))]
+    pub fn fail(&self, error: &anyhow::Error) {
+        self.failure.lock().unwrap().get_or_insert_with(|| format!("{:#}", error));
+    }
+
+    /// Erreur signalée par `fail` pendant ce round
+    pub fn failure(&self) -> Option<String> {
+        self.failure.lock().unwrap().clone()
+    }
+}
+
+impl Default for MiningControl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Batchs GPU observés avant de juger si le kernel travaille réellement
+const IDLE_CHECK_BATCHES: u32 = 3;
+
+/// Débit au-delà duquel un batch n'a pas pu être calculé: aucun GPU n'approche 1 TH/s en SHA256
+const MAX_PLAUSIBLE_RATE: f64 = 1e12;
+
+/// Contrôle de cohérence des premiers batchs d'un backend GPU
+///
+/// Un kernel compilé mais faux peut « tourner » sans rien calculer: compteur de hashes
+/// figé, ou batchs terminés instantanément. Après `IDLE_CHECK_BATCHES` batchs, un
+/// avertissement est logué une seule fois.
+#[cfg_attr(not(any(feature = "cuda", feature = "opencl")), allow(dead_code))]
+pub struct IdleCheck {
+    backend: String,
+    batches: u32,
+    dispatched: u64,
+    counted: u64,
+    elapsed: Duration,
+}
+
+#[cfg_attr(not(any(feature = "cuda", feature = "opencl")), allow(dead_code))]
+impl IdleCheck {
+    pub fn new(backend: &str) -> Self {
+        Self { backend: backend.to_string(), batches: 0, dispatched: 0, counted: 0, elapsed: Duration::ZERO }
+    }
+
+    /// Enregistre un batch de `dispatched` nonces ayant avancé le compteur de `counted`
+    /// en `elapsed`; retourne true si le GPU semble inactif (une seule fois)
+    pub fn record_batch(&mut self, dispatched: u64, counted: u64, elapsed: Duration) -> bool {
+        if self.batches >= IDLE_CHECK_BATCHES {
+            return false;
+        }
+        self.batches += 1;
+        self.dispatched += dispatched;
+        self.counted += counted;
+        self.elapsed += elapsed;
+        if self.batches < IDLE_CHECK_BATCHES {
+            return false;
+        }
+
+        let rate = self.dispatched as f64 / self.elapsed.as_secs_f64().max(f64::MIN_POSITIVE);
+        let reason = if self.counted < self.dispatched / 2 {
+            format!("hash counter advanced by {} for {} nonces dispatched", self.counted, self.dispatched)
+        } else if rate > MAX_PLAUSIBLE_RATE {
+            format!("{} batches completed at an implausible {:.2e} H/s", self.batches, rate)
+        } else {
+            return false;
+        };
+
+        log::warn!("⚠️  {}: the GPU may not be executing the kernel ({})", self.backend, reason);
+        true
+    }
+}
+
+/// Latence maximale d'un batch retenu par `--gpu-auto-batch`: au-delà, une solution
+/// ou un arrêt attend trop longtemps la fin du lancement
+#[cfg_attr(not(feature = "cuda"), allow(dead_code))]
+pub const AUTO_BATCH_MAX_LATENCY: Duration = Duration::from_millis(100);
+
+/// Taille de batch au meilleur débit parmi `candidates` (croissants), sous `max_latency`
+///
+/// `measure` lance un batch de la taille donnée et retourne sa durée. Les tailles plus
+/// grandes que la première hors latence ne sont pas essayées; si aucune ne tient, la plus
+/// petite est retenue.
+#[cfg_attr(not(feature = "cuda"), allow(dead_code))]
+pub fn tune_batch_size(candidates: &[u64], max_latency: Duration, mut measure: impl FnMut(u64) -> Duration) -> Option<u64> {
+    let mut best: Option<(u64, f64)> = None;
+    for &size in candidates {
+        let latency = measure(size);
+        let rate = size as f64 / latency.as_secs_f64().max(f64::MIN_POSITIVE);
+        log::debug!("   Batch {}: {:?} ({:.2} MH/s)", size, latency, rate / 1e6);
+        if latency > max_latency {
+            break;
+        }
+        if best.is_none_or(|(_, best_rate)| rate > best_rate) {
+            best = Some((size, rate));
+        }
+    }
+
+    best.map(|(size, _)| size).or_else(|| candidates.first().copied())
+}
+
+/// Mesure d'une grille GPU par la sous-commande `tune`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GridRun {
+    pub threads_per_block: usize,
+    pub num_blocks: usize,
+    pub hashrate_hs: f64,
+}
+
+/// Mesure chaque grille `(threads_per_block, num_blocks)` de `grids`
+///
+/// `measure` retourne le hashrate de la grille, ou None si elle ne tourne pas sur ce
+/// device (block trop grand, mémoire): elle est alors absente du résultat.
+pub fn sweep_grids(grids: &[(usize, usize)], mut measure: impl FnMut(usize, usize) -> Option<f64>) -> Vec<GridRun> {
+    grids
+        .iter()
+        .filter_map(|&(threads_per_block, num_blocks)| {
+            let hashrate_hs = measure(threads_per_block, num_blocks)?;
+            log::debug!("   Grid {}x{}: {:.2} MH/s", threads_per_block, num_blocks, hashrate_hs / 1e6);
+            Some(GridRun { threads_per_block, num_blocks, hashrate_hs })
+        })
+        .collect()
+}
+
+/// Grille au meilleur débit
+pub fn best_grid(runs: &[GridRun]) -> Option<GridRun> {
+    runs.iter().copied().max_by(|a, b| a.hashrate_hs.total_cmp(&b.hashrate_hs))
+}
 
 /// Trait pour les différents backends de mining
 pub trait MinerBackend: Send + Sync {
     /// Mine un bloc jusqu'à trouver un nonce valide ou atteindre max_nonce
     /// miner_pubkey est inclus dans le hash pour empêcher le vol de travail
-    fn mine(&self, challenge: &[u8; 32], miner_pubkey: &[u8; 32], block_number: u64, target: u128, max_nonce: u128) -> Option<u128>;
+    #[allow(dead_code)] // Le binaire miner passe par `mine_async`
+    fn mine(&self, challenge: &[u8; 32], miner_pubkey: &[u8; 32], block_number: u64, target: u128, max_nonce: u128) -> Option<u128> {
+        self.mine_with_control(challenge, miner_pubkey, block_number, target, 0..max_nonce, &MiningControl::new())
+    }
+
+    /// Cherche dans `nonces` en comptant les hashes dans `control` et en s'arrêtant
+    /// dès que `control.running` passe à false
+    fn mine_with_control(&self, challenge: &[u8; 32], miner_pubkey: &[u8; 32], block_number: u64, target: u128, nonces: Range<u128>, control: &MiningControl) -> Option<u128>;
 
     /// Nom du backend
     fn name(&self) -> &str;
+
+    /// Matériel utilisé (modèle de CPU, nom du GPU), pour regrouper les résultats d'une flotte
+    fn device_name(&self) -> String {
+        "unknown".to_string()
+    }
+
+    /// Vrai pour les backends GPU (mesure de la consommation possible)
+    fn is_gpu(&self) -> bool {
+        false
+    }
+
+    /// Nœud NUMA de la racine PCIe du device, si la topologie est connue
+    fn numa_node(&self) -> Option<usize> {
+        None
+    }
+
+    /// Vrai si le backend sait comparer selon `mode` (les kernels GPU ne comparent que
+    /// les 128 bits bas)
+    fn supports_compare_mode(&self, mode: CompareMode) -> bool {
+        mode == CompareMode::Low128
+    }
+}
+
+/// Version async et annulable de `MinerBackend::mine_with_control`
+///
+/// Le backend tourne sur un thread bloquant de tokio pour ne pas bloquer le runtime;
+/// `control.stop()` l'interrompt à la fin du batch en cours. Un échec signalé par le
+/// backend (`MiningControl::fail`) sans solution est retourné en erreur.
+pub async fn mine_async(
+    miner: Arc<dyn MinerBackend>,
+    challenge: [u8; 32],
+    miner_pubkey: [u8; 32],
+    block_number: u64,
+    target: u128,
+    nonces: Range<u128>,
+    control: MiningControl,
+) -> Result<Option<u128>> {
+    let round = control.clone();
+    let nonce = tasks::spawn_blocking("mining", move || {
+        miner.mine_with_control(&challenge, &miner_pubkey, block_number, target, nonces, &control)
+    })
+    .await?;
+
+    // Un backend en échec ne doit pas passer pour « aucune solution »
+    match (nonce, round.failure()) {
+        (None, Some(failure)) => Err(anyhow!(failure)),
+        (Some(nonce), Some(failure)) => {
+            log::warn!("A backend failed while another found nonce {}: {}", nonce, failure);
+            Ok(Some(nonce))
+        }
+        (nonce, None) => Ok(nonce),
+    }
+}
+
+/// Recherche d'un challenge qui se poursuit après chaque solution trouvée
+///
+/// Chaque nonce trouvé part dans un canal vers la vérification et la soumission côté CPU,
+/// et le backend reprend aussitôt au nonce suivant: un GPU n'attend jamais la chaîne.
+/// La recherche s'arrête sur `cancel` (nouveau challenge), sur l'arrêt de `control`, sur
+/// le budget de hashes, à la fin de la dernière plage de `nonces` (parcourues dans l'ordre)
+/// ou quand le `NonceStream` est abandonné.
+pub struct NonceStream {
+    found: mpsc::UnboundedReceiver<u128>,
+    /// Contrôle passé au backend: compteur du round, flag d'arrêt propre à la recherche
+    inner: MiningControl,
+    task: Option<JoinHandle<()>>,
+}
+
+impl NonceStream {
+    pub fn start(
+        miner: Arc<dyn MinerBackend>,
+        challenge: [u8; 32],
+        miner_pubkey: [u8; 32],
+        block_number: u64,
+        target: u128,
+        nonces: Vec<Range<u128>>,
+        control: MiningControl,
+    ) -> Self {
+        let (sender, found) = mpsc::unbounded_channel();
+        let inner = MiningControl { running: Arc::new(AtomicBool::new(true)), ..control.clone() };
+
+        // Relaie l'arrêt global (Ctrl-C, soak) au backend, jusqu'à la fin de la recherche
+        let relay = inner.clone();
+        tasks::spawn("nonce-stream-stop", async move {
+            while relay.running.load(Ordering::Relaxed) {
+                if !control.is_running() {
+                    relay.stop();
+                }
+                tokio::time::sleep(STOP_POLL_INTERVAL).await;
+            }
+        });
+
+        let backend = inner.clone();
+        let task = tasks::spawn_blocking("mining", move || {
+            'search: for nonces in nonces {
+                let mut start = nonces.start;
+                while start < nonces.end && backend.is_running() {
+                    let Some(nonce) = miner.mine_with_control(&challenge, &miner_pubkey, block_number, target, start..nonces.end, &backend) else {
+                        // Plage épuisée: la suivante, sauf si le backend a échoué
+                        if backend.failure().is_some() {
+                            break 'search;
+                        }
+                        break;
+                    };
+                    // Récepteur abandonné: plus personne ne soumet pour ce challenge
+                    if sender.send(nonce).is_err() {
+                        break 'search;
+                    }
+                    start = nonce + 1;
+                }
+            }
+            backend.stop();
+        });
+
+        Self { found, inner, task: Some(task) }
+    }
+
+    /// Prochain nonce trouvé; None une fois la recherche terminée, erreur si le backend
+    /// a échoué (`MiningControl::fail`)
+    pub async fn next(&mut self) -> Result<Option<u128>> {
+        if let Some(nonce) = self.found.recv().await {
+            return Ok(Some(nonce));
+        }
+        if let Some(task) = self.task.take() {
+            task.await?;
+        }
+        match self.inner.failure() {
+            Some(failure) => Err(anyhow!(failure)),
+            None => Ok(None),
+        }
+    }
+
+    /// Arrête le backend à la fin de son batch en cours
+    pub fn cancel(&self) {
+        self.inner.stop();
+    }
+}
+
+impl Drop for NonceStream {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+/// Modèle du CPU (`model name` de /proc/cpuinfo), sinon l'architecture
+pub fn cpu_model() -> String {
+    std::fs::read_to_string("/proc/cpuinfo")
+        .ok()
+        .and_then(|cpuinfo| {
+            cpuinfo.lines().find_map(|line| {
+                let (key, value) = line.split_once(':')?;
+                (key.trim() == "model name").then(|| value.trim().to_string())
+            })
+        })
+        .unwrap_or_else(|| std::env::consts::ARCH.to_string())
+}
+
+/// Fait tourner `miner` pendant `duration` sur une target inatteignable
+///
+/// Absorbe les coûts de premier lancement (pool de threads, compilation du kernel,
+/// caches) avant une mesure. Retourne les hashes calculés et la durée réelle.
+pub fn warm_up(miner: &dyn MinerBackend, duration: Duration) -> (u64, Duration) {
+    let control = MiningControl::new();
+    let timer = control.clone();
+    std::thread::spawn(move || {
+        std::thread::sleep(duration);
+        timer.stop();
+    });
+
+    let start = Instant::now();
+    miner.mine_with_control(&[0u8; 32], &[0u8; 32], 0, 0, 0..u128::MAX, &control);
+
+    (control.hashes(), start.elapsed())
+}
+
+/// Appels à `pow::verify_nonce` par `threads` threads pendant `duration`
+///
+/// Débit de hash brut sur le préimage complet, sans midstate ni batchs: référence du CPU miner.
+/// Retourne les vérifications effectuées et la durée réelle.
+pub fn verify_throughput(hash_algo: HashAlgo, threads: usize, duration: Duration) -> (u64, Duration) {
+    let start = Instant::now();
+    let total: u64 = std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads.max(1) as u128)
+            .map(|thread| {
+                scope.spawn(move || {
+                    let mut nonce = thread << 64;
+                    let mut count = 0u64;
+                    // Horloge lue tous les 1024 appels seulement
+                    loop {
+                        for _ in 0..1024 {
+                            std::hint::black_box(pow::verify_nonce(&[0u8; 32], &[0u8; 32], nonce, 0, 0, hash_algo));
+                            nonce += 1;
+                        }
+                        count += 1024;
+                        if start.elapsed() >= duration {
+                            return count;
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        workers.into_iter().map(|worker| worker.join().unwrap()).sum()
+    });
+
+    (total, start.elapsed())
+}
+
+/// Mine les nonces `nonces` et retourne le premier nonce valide
+///
+/// Utilise le même préimage que `pow::compute_hash`: challenge || miner_pubkey est
+/// absorbé une seule fois par batch (midstate), seule la fin nonce || block_number est
+/// hashée à chaque nonce.
+fn mine_batch(algo: HashAlgo, sha: ShaImpl, challenge: &[u8; 32], miner_pubkey: &[u8; 32], block_number: u64, nonces: Range<u128>, is_valid: &impl Fn(&[u8; 32]) -> bool) -> Option<u128> {
+    let message = pow::preimage(challenge, miner_pubkey, nonces.start, block_number);
+    let prefix = pow::PrefixHasher::new(sha, &message[..64]);
+    let mut suffix: [u8; pow::PREIMAGE_LEN - 64] = message[64..].try_into().unwrap();
+
+    for nonce in nonces {
+        suffix[..pow::NONCE_BYTES].copy_from_slice(&nonce.to_le_bytes());
+
+        let hash = algo.hash_prefixed(sha, &prefix, &suffix);
+        if is_valid(&hash) {
+            return Some(nonce);
+        }
+    }
+
+    None
 }
 
 // ============================================================================
 // CPU MINER
 // ============================================================================
 
+/// Mineur multi-thread: chaque thread part de son propre offset, le nonce retourné est
+/// valide mais pas forcément le plus petit de la plage (voir `SimpleCpuMiner`)
 pub struct CpuMiner {
     threads: usize,
+    hash_algo: HashAlgo,
+    sha_impl: ShaImpl,
+    compare_mode: CompareMode,
+    /// Nœuds sur lesquels répartir les threads (vide: pas de placement)
+    numa_nodes: Vec<Node>,
+}
+
+/// Threads CPU effectifs: 0 laisse un cœur libre pour le bureau (`--threads 0`), au moins 1
+pub fn effective_threads(threads: usize, cpus: usize) -> usize {
+    match threads {
+        0 => cpus.saturating_sub(1).max(1),
+        n => n,
+    }
 }
 
 impl CpuMiner {
+    /// `threads` à 0: tous les cœurs sauf un
     pub fn new(threads: usize) -> Self {
-        Self { threads }
+        let threads = effective_threads(threads, num_cpus::get());
+        Self { threads, hash_algo: HashAlgo::default(), sha_impl: ShaImpl::default(), compare_mode: CompareMode::default(), numa_nodes: Vec::new() }
+    }
+
+    pub fn with_compare_mode(mut self, compare_mode: CompareMode) -> Self {
+        self.compare_mode = compare_mode;
+        self
+    }
+
+    /// Répartit les workers sur les nœuds NUMA en tourniquet; sans effet avec un seul nœud
+    ///
+    /// Seules les pages de pile touchées après l'épinglage sont locales au nœud: le
+    /// challenge et les hashers vivent sur la pile, la plage d'un worker n'a pas de nœud.
+    pub fn with_numa(mut self, enabled: bool) -> Self {
+        let nodes = if enabled { numa::nodes() } else { Vec::new() };
+        self.numa_nodes = if nodes.len() > 1 { nodes } else { Vec::new() };
+        self
+    }
+
+    pub fn with_hash_algo(mut self, hash_algo: HashAlgo) -> Self {
+        self.hash_algo = hash_algo;
+        self
+    }
+
+    pub fn with_sha_impl(mut self, sha_impl: ShaImpl) -> Self {
+        self.sha_impl = sha_impl;
+        self
     }
 }
 
 impl MinerBackend for CpuMiner {
-    fn mine(&self, challenge: &[u8; 32], miner_pubkey: &[u8; 32], block_number: u64, target: u128, max_nonce: u128) -> Option<u128> {
-        let found = Arc::new(AtomicBool::new(false));
-        let result = Arc::new(Mutex::new(0u128));
+    fn mine_with_control(&self, challenge: &[u8; 32], miner_pubkey: &[u8; 32], block_number: u64, target: u128, nonces: Range<u128>, control: &MiningControl) -> Option<u128> {
+        // Premier nonce trouvé, écrit une seule fois sans verrou: sert aussi de flag d'arrêt
+        let result = OnceLock::new();
+        let challenge = *challenge;
         let miner_pubkey = *miner_pubkey; // Copy for threads
+        let is_valid = self.compare_mode.checker(target);
 
-        // Configurer rayon pour utiliser le bon nombre de threads
+        // Configurer rayon pour utiliser le bon nombre de threads, chaque worker épinglé
+        // sur son nœud (tourniquet) dès son démarrage, avant qu'il ne touche sa pile
+        let numa_nodes = self.numa_nodes.clone();
         rayon::ThreadPoolBuilder::new()
             .num_threads(self.threads)
+            .start_handler(move |index| {
+                if let Some(node) = numa_nodes.get(index % numa_nodes.len().max(1)) {
+                    if let Err(e) = numa::bind_current_thread(node) {
+                        log::warn!("Thread {} not bound to its NUMA node: {:#}", index, e);
+                    }
+                }
+            })
             .build()
             .unwrap()
             .install(|| {
                 // Diviser le travail en chunks
-                let chunk_size = max_nonce / (self.threads as u128);
+                let chunk_size = (nonces.end.saturating_sub(nonces.start)) / (self.threads as u128);
 
                 (0..self.threads).into_par_iter().for_each(|thread_id| {
-                    let start = thread_id as u128 * chunk_size;
+                    let start = nonces.start + thread_id as u128 * chunk_size;
                     let end = if thread_id == self.threads - 1 {
-                        max_nonce
+                        nonces.end
                     } else {
-                        (thread_id as u128 + 1) * chunk_size
+                        start + chunk_size
                     };
 
-                    let mut nonce = start;
-                    while nonce < end {
-                        // Check si un autre thread a trouvé
-                        if found.load(Ordering::Relaxed) {
-                            break;
-                        }
 
-                        if pow::verify_nonce(challenge, &miner_pubkey, nonce, block_number, target) {
-                            found.store(true, Ordering::Relaxed);
-                            *result.lock().unwrap() = nonce;
+                    let mut batch_start = start;
+                    while batch_start < end {
+                        // Check si un autre thread a trouvé ou si on doit s'arrêter
+                        if result.get().is_some() || !control.is_running() {
                             break;
                         }
 
-                        // Progress update every 100k hashes
-                        if nonce % 100_000 == 0 && thread_id == 0 {
-                            // log::debug!("Thread 0: {} hashes", nonce);
+                        let batch_end = end.min(batch_start.saturating_add(BATCH_SIZE));
+
+                        if let Some(nonce) = mine_batch(self.hash_algo, self.sha_impl, &challenge, &miner_pubkey, block_number, batch_start..batch_end, &is_valid) {
+                            control.add_hashes((nonce - batch_start + 1) as u64);
+                            // Un autre thread a pu gagner entre-temps: son nonce reste
+                            let _ = result.set(nonce);
+                            break;
                         }
 
-                        nonce += 1;
+                        control.add_hashes((batch_end - batch_start) as u64);
+                        batch_start = batch_end;
                     }
                 });
             });
 
-        if found.load(Ordering::Relaxed) {
-            Some(*result.lock().unwrap())
-        } else {
-            None
-        }
+        result.into_inner()
     }
 
     fn name(&self) -> &str {
         "CPU"
     }
+
+    fn device_name(&self) -> String {
+        cpu_model()
+    }
+
+    fn supports_compare_mode(&self, _mode: CompareMode) -> bool {
+        true
+    }
 }
 
 // ============================================================================
 // CPU MINER - Version simple (single thread)
 // ============================================================================
 
-pub struct SimpleCpuMiner;
+/// Référence mono-thread: parcourt la plage strictement dans l'ordre croissant et retourne
+/// donc le plus petit nonce valide, même préimage que `pow::compute_hash`
+///
+/// Sert d'oracle déterministe aux tests des autres backends.
+#[allow(dead_code)] // Référence mono-thread, utilisée par les tests
+#[derive(Default)]
+pub struct SimpleCpuMiner {
+    pub hash_algo: HashAlgo,
+}
 
 impl MinerBackend for SimpleCpuMiner {
-    fn mine(&self, challenge: &[u8; 32], miner_pubkey: &[u8; 32], block_number: u64, target: u128, max_nonce: u128) -> Option<u128> {
-        let mut nonce = 0u128;
-        while nonce < max_nonce {
-            if pow::verify_nonce(challenge, miner_pubkey, nonce, block_number, target) {
+    fn mine_with_control(&self, challenge: &[u8; 32], miner_pubkey: &[u8; 32], block_number: u64, target: u128, nonces: Range<u128>, control: &MiningControl) -> Option<u128> {
+        let mut start = nonces.start;
+        while start < nonces.end && control.is_running() {
+            let end = nonces.end.min(start.saturating_add(BATCH_SIZE));
+
+            if let Some(nonce) = mine_batch(self.hash_algo, ShaImpl::default(), challenge, miner_pubkey, block_number, start..end, &CompareMode::Low128.checker(target)) {
+                control.add_hashes((nonce - start + 1) as u64);
                 return Some(nonce);
             }
-            nonce += 1;
+
+            control.add_hashes((end - start) as u64);
+            start = end;
         }
         None
     }
@@ -108,12 +683,319 @@ impl MinerBackend for SimpleCpuMiner {
     fn name(&self) -> &str {
         "CPU (Simple)"
     }
+
+    fn device_name(&self) -> String {
+        cpu_model()
+    }
+}
+
+// ============================================================================
+// MOCK MINER - hashrate simulé
+// ============================================================================
+
+/// Backend simulé (`--backend mock`): attend le temps que mettrait un mineur à `hashrate`
+/// H/s pour les hashes attendus de la cible (sa difficulté), comptés dans `control`, puis
+/// retourne le premier nonce réellement valide de la plage.
+///
+/// Durée déterministe pour tester la boucle de minage sans GPU. La recherche finale hashe
+/// par batchs, interrompue par `control`; ses hashes au-delà des simulés sont comptés.
+pub struct MockMiner {
+    hashrate: f64,
+    hash_algo: HashAlgo,
+    compare_mode: CompareMode,
+}
+
+impl MockMiner {
+    /// `hashrate` en H/s, strictement positif
+    pub fn new(hashrate: f64) -> Self {
+        Self { hashrate, hash_algo: HashAlgo::default(), compare_mode: CompareMode::default() }
+    }
+
+    pub fn with_hash_algo(mut self, hash_algo: HashAlgo) -> Self {
+        self.hash_algo = hash_algo;
+        self
+    }
+
+    pub fn with_compare_mode(mut self, compare_mode: CompareMode) -> Self {
+        self.compare_mode = compare_mode;
+        self
+    }
+
+    /// Durée simulée de `hashes` hashes
+    pub fn solve_time(&self, hashes: u128) -> Duration {
+        Duration::from_secs_f64(hashes as f64 / self.hashrate)
+    }
+}
+
+impl MinerBackend for MockMiner {
+    fn mine_with_control(&self, challenge: &[u8; 32], miner_pubkey: &[u8; 32], block_number: u64, target: u128, nonces: Range<u128>, control: &MiningControl) -> Option<u128> {
+        // Hashes attendus pour la cible, bornés par la plage
+        let expected = pow::target_to_difficulty(target).min(nonces.end.saturating_sub(nonces.start));
+        let start = Instant::now();
+        let mut counted = 0;
+        while counted < expected {
+            if !control.is_running() {
+                return None;
+            }
+            std::thread::sleep(STOP_POLL_INTERVAL.min(self.solve_time(expected - counted)));
+            let done = ((start.elapsed().as_secs_f64() * self.hashrate) as u128).min(expected);
+            control.add_hashes((done - counted) as u64);
+            counted = done;
+        }
+
+        let is_valid = self.compare_mode.checker(target);
+        let mut batch_start = nonces.start;
+        while batch_start < nonces.end {
+            if !control.is_running() {
+                return None;
+            }
+            let batch_end = nonces.end.min(batch_start.saturating_add(BATCH_SIZE));
+            let found = mine_batch(self.hash_algo, ShaImpl::default(), challenge, miner_pubkey, block_number, batch_start..batch_end, &is_valid);
+
+            // Hashes réels de la plage, hors ceux déjà simulés
+            let searched = found.map_or(batch_end, |nonce| nonce + 1) - nonces.start;
+            if searched > counted {
+                control.add_hashes((searched - counted) as u64);
+                counted = searched;
+            }
+            if found.is_some() {
+                return found;
+            }
+            batch_start = batch_end;
+        }
+        None
+    }
+
+    fn name(&self) -> &str {
+        "Mock"
+    }
+
+    fn device_name(&self) -> String {
+        format!("simulated {:.0} H/s", self.hashrate)
+    }
+
+    fn supports_compare_mode(&self, _mode: CompareMode) -> bool {
+        true
+    }
+}
+
+// ============================================================================
+// COMPOSITE MINER - plusieurs backends en parallèle
+// ============================================================================
+
+/// Fait tourner plusieurs backends en parallèle sur des parts disjointes des nonces
+///
+/// Les backends partagent le compteur de hashes; le premier nonce trouvé arrête les autres.
+pub struct CompositeMiner {
+    backends: Vec<Box<dyn MinerBackend>>,
+    name: String,
+    /// Nœud NUMA du thread qui alimente chaque backend (vide: pas de placement)
+    feeders: Vec<Option<Node>>,
+}
+
+impl CompositeMiner {
+    pub fn new(backends: Vec<Box<dyn MinerBackend>>) -> Self {
+        let name = backends.iter().map(|b| b.name()).collect::<Vec<_>>().join("+");
+        Self { backends, name, feeders: Vec::new() }
+    }
+
+    /// Place le thread qui alimente chaque GPU sur le nœud NUMA de sa racine PCIe
+    ///
+    /// Best-effort: seulement avec plusieurs GPU et plusieurs nœuds; un device dont la
+    /// topologie est inconnue (OpenCL, hors Linux) garde un thread libre.
+    pub fn with_gpu_numa(mut self, enabled: bool) -> Self {
+        let nodes = if enabled { numa::nodes() } else { Vec::new() };
+        let gpus = self.backends.iter().filter(|b| b.is_gpu()).count();
+        if nodes.len() < 2 || gpus < 2 {
+            self.feeders = Vec::new();
+            return self;
+        }
+
+        let device_nodes: Vec<Option<usize>> = self.backends.iter().map(|b| b.is_gpu().then(|| b.numa_node()).flatten()).collect();
+        self.feeders = numa::pair_devices(&device_nodes, &nodes);
+        for (backend, feeder) in self.backends.iter().zip(&self.feeders) {
+            if let Some(node) = feeder {
+                log::info!("   NUMA: {} fed from node {}", backend.device_name(), node.id);
+            }
+        }
+        self
+    }
+}
+
+impl MinerBackend for CompositeMiner {
+    fn mine_with_control(&self, challenge: &[u8; 32], miner_pubkey: &[u8; 32], block_number: u64, target: u128, nonces: Range<u128>, control: &MiningControl) -> Option<u128> {
+        if self.backends.is_empty() {
+            return None;
+        }
+
+        // Flag propre aux backends internes: le premier qui trouve les arrête
+        // sans toucher au flag global, qui sert aux rounds suivants
+        let inner = MiningControl { running: Arc::new(AtomicBool::new(true)), ..control.clone() };
+        let result = Mutex::new(None);
+        let finished = AtomicUsize::new(0);
+
+        // Parts bornées à u64 (sauf la dernière): les kernels GPU ne parcourent que des nonces u64
+        let count = self.backends.len() as u128;
+        let share = (nonces.end.saturating_sub(nonces.start) / count).min(u64::MAX as u128 / count);
+
+        std::thread::scope(|scope| {
+            for (i, backend) in self.backends.iter().enumerate() {
+                let start = nonces.start + i as u128 * share;
+                let end = if i == self.backends.len() - 1 { nonces.end } else { start + share };
+                let (inner, result, finished) = (&inner, &result, &finished);
+                let feeder = self.feeders.get(i).and_then(Option::as_ref);
+
+                scope.spawn(move || {
+                    if let Some(node) = feeder {
+                        if let Err(e) = numa::bind_current_thread(node) {
+                            log::debug!("{} feeder not bound to NUMA node {}: {:#}", backend.name(), node.id, e);
+                        }
+                    }
+                    if let Some(nonce) = backend.mine_with_control(challenge, miner_pubkey, block_number, target, start..end, inner) {
+                        result.lock().unwrap().get_or_insert(nonce);
+                        inner.stop();
+                    }
+                    finished.fetch_add(1, Ordering::SeqCst);
+                });
+            }
+
+            // Relaie l'arrêt global jusqu'à la fin de tous les backends
+            while finished.load(Ordering::SeqCst) < self.backends.len() {
+                if !control.is_running() {
+                    inner.stop();
+                }
+                std::thread::sleep(STOP_POLL_INTERVAL);
+            }
+        });
+
+        result.into_inner().unwrap()
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn device_name(&self) -> String {
+        self.backends.iter().map(|b| b.device_name()).collect::<Vec<_>>().join(" + ")
+    }
+
+    fn is_gpu(&self) -> bool {
+        self.backends.iter().any(|b| b.is_gpu())
+    }
+
+    fn supports_compare_mode(&self, mode: CompareMode) -> bool {
+        self.backends.iter().all(|b| b.supports_compare_mode(mode))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_idle_check_flags_noop_kernel() {
+        // Kernel no-op: le lancement rend la main sans calculer ni compter
+        let noop_kernel = |_control: &MiningControl, _nonces: u64| {};
+        let working_kernel = |control: &MiningControl, nonces: u64| {
+            std::thread::sleep(Duration::from_millis(1));
+            control.add_hashes(nonces);
+        };
+
+        let run = |kernel: &dyn Fn(&MiningControl, u64)| {
+            let control = MiningControl::new();
+            let mut check = IdleCheck::new("mock GPU");
+            (0..2 * IDLE_CHECK_BATCHES)
+                .filter(|_| {
+                    let (before, start) = (control.hashes(), Instant::now());
+                    kernel(&control, 1 << 18);
+                    check.record_batch(1 << 18, control.hashes() - before, start.elapsed())
+                })
+                .count()
+        };
+
+        // Un seul avertissement, pas un par batch
+        assert_eq!(run(&noop_kernel), 1);
+        assert_eq!(run(&working_kernel), 0);
+
+        // Compteur qui avance mais batchs instantanés: rien n'a pu être calculé
+        let mut check = IdleCheck::new("mock GPU");
+        let results: Vec<_> = (0..IDLE_CHECK_BATCHES).map(|_| check.record_batch(1 << 18, 1 << 18, Duration::from_nanos(1))).collect();
+        assert_eq!(results, [false, false, true]);
+    }
+
+    #[test]
+    fn test_tune_batch_size_within_latency() {
+        let sizes: Vec<u64> = (16..=24).step_by(2).map(|shift| 1 << shift).collect();
+        let max_latency = Duration::from_millis(100);
+
+        // Overhead fixe de 2 ms puis 100 MH/s: le débit croît avec la taille, jusqu'à
+        // dépasser la latence à 2^24 (170 ms)
+        let linear = |size: u64| Duration::from_secs_f64(0.002 + size as f64 / 1e8);
+        assert_eq!(tune_batch_size(&sizes, max_latency, linear), Some(1 << 22));
+
+        // Débit qui s'effondre au-delà de 2^18 (caches): la plus grande taille n'est pas la meilleure
+        let thrashing = |size: u64| if size > 1 << 18 { linear(size) * 4 } else { linear(size) };
+        assert_eq!(tune_batch_size(&sizes, max_latency, thrashing), Some(1 << 18));
+
+        // Rien sous la latence: la plus petite taille, sans essayer les suivantes
+        let mut tried = 0;
+        let slow = |_| {
+            tried += 1;
+            Duration::from_secs(1)
+        };
+        assert_eq!(tune_batch_size(&sizes, max_latency, slow), Some(1 << 16));
+        assert_eq!(tried, 1);
+    }
+
+    #[test]
+    fn test_verify_throughput_counts_calls() {
+        let (single, elapsed) = verify_throughput(HashAlgo::Sha256, 1, Duration::from_millis(50));
+        assert!(single > 0 && single % 1024 == 0);
+        assert!(elapsed >= Duration::from_millis(50));
+
+        // Chaque thread fait au moins un tour, même sur une machine à un cœur
+        let (multi, _) = verify_throughput(HashAlgo::Sha256, 2, Duration::from_millis(50));
+        assert!(multi >= 2 * 1024);
+    }
+
+    #[test]
+    fn test_select_device_without_devices() {
+        let err = select_device::<usize>("CUDA", Vec::new(), 0).unwrap_err();
+        assert_eq!(err.to_string(), "No CUDA device found");
+        assert!(select_device("OpenCL", vec!["gpu0"], 1).is_err());
+        assert_eq!(select_device("OpenCL", vec!["gpu0", "gpu1"], 1).unwrap(), "gpu1");
+    }
+
+    #[test]
+    fn test_dedup_devices_across_platforms() {
+        // (plateforme, nom, mémoire): la même carte vue par l'ICD NVIDIA puis par PoCL
+        let devices = vec![
+            ("NVIDIA CUDA", "GeForce RTX 3080", 10u64 << 30),
+            ("NVIDIA CUDA", "GeForce RTX 3070", 8 << 30),
+            ("Portable Computing Language", "GeForce RTX 3080", 10 << 30),
+            ("Portable Computing Language", "cpu-haswell", 32 << 30),
+        ];
+        let (unique, duplicates) = dedup_devices(devices, |&(platform, name, memory)| (platform, (name, memory)));
+        let names: Vec<_> = unique.iter().map(|&(_, name, _)| name).collect();
+        assert_eq!(names, ["GeForce RTX 3080", "GeForce RTX 3070", "cpu-haswell"]);
+        assert_eq!(duplicates, [("Portable Computing Language", "GeForce RTX 3080", 10 << 30)]);
+        assert_eq!(select_device("OpenCL", unique, 2).unwrap().1, "cpu-haswell");
+
+        // Deux cartes identiques sur une plateforme: deux GPU physiques, tous deux gardés;
+        // la seconde plateforme qui les expose aussi ne perd que ces deux-là
+        let devices = vec![
+            ("AMD Accelerated Parallel Processing", "gfx1030", 16u64 << 30),
+            ("AMD Accelerated Parallel Processing", "gfx1030", 16 << 30),
+            ("Portable Computing Language", "gfx1030", 16 << 30),
+            ("Portable Computing Language", "gfx1030", 16 << 30),
+            ("Portable Computing Language", "gfx1030", 16 << 30),
+        ];
+        let (unique, duplicates) = dedup_devices(devices, |&(platform, name, memory)| (platform, (name, memory)));
+        let platforms: Vec<_> = unique.iter().map(|&(platform, _, _)| platform).collect();
+        assert_eq!(platforms, ["AMD Accelerated Parallel Processing", "AMD Accelerated Parallel Processing", "Portable Computing Language"]);
+        assert_eq!(duplicates.len(), 2);
+    }
+
     #[test]
     fn test_cpu_miner() {
         let miner = CpuMiner::new(4);
@@ -126,12 +1008,59 @@ mod tests {
         assert!(result.is_some(), "Should find a nonce");
 
         let nonce = result.unwrap();
-        assert!(pow::verify_nonce(&challenge, &miner_pubkey, nonce, block_number, target));
+        assert!(pow::verify_nonce(&challenge, &miner_pubkey, nonce, block_number, target, HashAlgo::Sha256));
+    }
+
+    #[test]
+    fn test_cpu_miner_many_threads_easy_target() {
+        // Target facile: plusieurs threads trouvent presque en même temps, un seul nonce doit sortir
+        let miner = CpuMiner::new(16);
+        let challenge = [5u8; 32];
+        let miner_pubkey = [6u8; 32];
+        let target = u128::MAX / 2;
+
+        for block_number in 0..50 {
+            let nonce = miner.mine(&challenge, &miner_pubkey, block_number, target, 1 << 20).expect("easy target");
+            assert!(pow::verify_nonce(&challenge, &miner_pubkey, nonce, block_number, target, HashAlgo::Sha256));
+        }
+    }
+
+    #[test]
+    fn test_cpu_miner_numa_placement_still_mines() {
+        // Deux nœuds factices sur le CPU 0: le placement (ou son échec sans la feature) ne change pas le résultat
+        let mut miner = CpuMiner::new(4);
+        miner.numa_nodes = vec![Node { id: 0, cpus: vec![0] }, Node { id: 0, cpus: vec![0] }];
+        let target = u128::MAX / 1000;
+        let nonce = miner.mine(&[3u8; 32], &[4u8; 32], 7, target, 1 << 24).expect("nonce");
+        assert!(pow::verify_nonce(&[3u8; 32], &[4u8; 32], nonce, 7, target, HashAlgo::Sha256));
+
+        // Un seul nœud sur cette machine: --affinity-numa n'épingle rien
+        if numa::nodes().len() < 2 {
+            assert!(CpuMiner::new(1).with_numa(true).numa_nodes.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_hash_budget_stops_mining() {
+        // Target 0: seul le budget peut arrêter le mining
+        let control = MiningControl::new().with_hash_budget(Some(50_000));
+        assert_eq!(CpuMiner::new(2).mine_with_control(&[0u8; 32], &[0u8; 32], 0, 0, 0..u128::MAX, &control), None);
+        assert!(control.budget_exhausted());
+        assert!(!control.is_running());
+        // Dépassement borné par le batch en cours de chaque thread
+        assert!(control.hashes() < 50_000 + 2 * BATCH_SIZE as u64, "{} hashes", control.hashes());
+        assert!(control.round().is_running());
+
+        // Plage épuisée avant le budget: distinct d'un budget atteint
+        let control = MiningControl::new().with_hash_budget(Some(50_000));
+        assert_eq!(CpuMiner::new(2).mine_with_control(&[0u8; 32], &[0u8; 32], 0, 0, 0..1_000, &control), None);
+        assert!(!control.budget_exhausted());
+        assert!(control.is_running());
     }
 
     #[test]
     fn test_simple_cpu_miner() {
-        let miner = SimpleCpuMiner;
+        let miner = SimpleCpuMiner::default();
         let challenge = [0u8; 32];
         let miner_pubkey = [1u8; 32];
         let block_number = 100;
@@ -140,4 +1069,329 @@ mod tests {
         let result = miner.mine(&challenge, &miner_pubkey, block_number, target, 10_000);
         assert!(result.is_some());
     }
+
+    #[test]
+    fn test_simple_cpu_miner_returns_smallest_nonce() {
+        let (challenge, miner_pubkey, block_number) = ([3u8; 32], [5u8; 32], 11);
+        let target = u128::MAX / 300;
+        let nonces = 0..20_000u128;
+
+        // Tous les nonces valides de la plage, via compute_hash
+        let valid: Vec<u128> = nonces
+            .clone()
+            .filter(|&n| pow::verify_nonce(&challenge, &miner_pubkey, n, block_number, target, HashAlgo::Sha256))
+            .collect();
+        assert!(valid.len() > 1);
+
+        let simple = SimpleCpuMiner::default();
+        assert_eq!(simple.mine(&challenge, &miner_pubkey, block_number, target, nonces.end), valid.first().copied());
+        // Reprise juste après une solution: la suivante dans l'ordre
+        let next = simple.mine_with_control(&challenge, &miner_pubkey, block_number, target, valid[0] + 1..nonces.end, &MiningControl::new());
+        assert_eq!(next, Some(valid[1]));
+
+        // Le mineur parallèle ne garantit pas l'ordre, seulement un nonce valide de la plage
+        let parallel = CpuMiner::new(4).mine(&challenge, &miner_pubkey, block_number, target, nonces.end);
+        assert!(parallel.is_some_and(|n| valid.contains(&n)), "{:?}", parallel);
+    }
+
+    #[test]
+    fn test_sweep_picks_highest_throughput_grid() {
+        // Latence simulée: lancement fixe de 1 ms, 1 ns par nonce, blocks de 1024 threads refusés
+        // et occupation réduite au-delà de 256 threads par block
+        let latency = |threads: usize, blocks: usize| {
+            let nonces = (threads * blocks) as f64;
+            let occupancy = if threads > 256 { 0.6 } else { 1.0 };
+            Duration::from_secs_f64(1e-3 + nonces * 1e-9 / occupancy)
+        };
+        let grids: Vec<(usize, usize)> = [128, 256, 512, 1024].into_iter().flat_map(|t| [256, 1024, 4096].map(|b| (t, b))).collect();
+
+        let mut measured = Vec::new();
+        let runs = sweep_grids(&grids, |threads, blocks| {
+            measured.push((threads, blocks));
+            (threads < 1024).then(|| (threads * blocks) as f64 / latency(threads, blocks).as_secs_f64())
+        });
+        assert_eq!(measured, grids);
+        assert_eq!(runs.len(), 9);
+        assert!(runs.iter().all(|run| run.threads_per_block < 1024));
+
+        // Le plus gros lancement amortit l'overhead, sans la pénalité d'occupation
+        let best = best_grid(&runs).unwrap();
+        assert_eq!((best.threads_per_block, best.num_blocks), (256, 4096));
+        assert!(best_grid(&[]).is_none());
+    }
+
+    #[test]
+    fn test_mock_solve_time_scales_with_difficulty() {
+        let (challenge, miner_pubkey, block_number) = ([3u8; 32], [5u8; 32], 11);
+        let mock = MockMiner::new(100_000.0);
+
+        let mut elapsed = Vec::new();
+        for difficulty in [2_000u128, 20_000] {
+            let target = pow::difficulty_to_target(difficulty);
+            let control = MiningControl::new();
+            let start = Instant::now();
+            let nonce = mock.mine_with_control(&challenge, &miner_pubkey, block_number, target, 0..u128::MAX, &control).unwrap();
+            elapsed.push(start.elapsed());
+
+            assert!(pow::verify_nonce(&challenge, &miner_pubkey, nonce, block_number, target, HashAlgo::Sha256));
+            // Hashes simulés pour la difficulté, plus ceux de la recherche réelle au-delà
+            assert_eq!(control.hashes(), difficulty.max(nonce + 1) as u64);
+            assert!(elapsed[elapsed.len() - 1] >= mock.solve_time(difficulty), "{:?}", elapsed);
+        }
+        // 10x la difficulté: 20 ms puis 200 ms
+        assert!(elapsed[1] > elapsed[0] * 5, "{:?}", elapsed);
+
+        // Plage plus courte que l'attente: seuls ses nonces sont simulés
+        let control = MiningControl::new();
+        mock.mine_with_control(&challenge, &miner_pubkey, block_number, pow::difficulty_to_target(u64::MAX as u128), 0..1_000, &control);
+        assert_eq!(control.hashes(), 1_000);
+    }
+
+    #[test]
+    fn test_mock_search_stops_on_control() {
+        // Attente simulée de 1 ms, puis une recherche réelle sans fin: seul l'arrêt y met fin
+        let mock = MockMiner::new(1e12);
+        let target = pow::difficulty_to_target(1_000_000_000);
+        let control = MiningControl::new();
+        let stopper = {
+            let control = control.clone();
+            std::thread::spawn(move || {
+                std::thread::sleep(Duration::from_millis(200));
+                control.stop();
+            })
+        };
+
+        let start = Instant::now();
+        assert_eq!(mock.mine_with_control(&[0u8; 32], &[0u8; 32], 0, target, 0..u128::MAX, &control), None);
+        stopper.join().unwrap();
+        assert!(start.elapsed() < Duration::from_secs(5), "search ignored the stop: {:?}", start.elapsed());
+        // Recherche réelle arrêtée avant d'atteindre les hashes simulés: rien de plus compté
+        assert_eq!(control.hashes(), 1_000_000_000);
+    }
+
+    #[test]
+    fn test_mine_batch_matches_compute_hash() {
+        let challenge = [7u8; 32];
+        let miner_pubkey = [9u8; 32];
+        let block_number = 42;
+        let target = u128::MAX / 500;
+
+        let nonce = mine_batch(HashAlgo::Sha256, ShaImpl::default(), &challenge, &miner_pubkey, block_number, 0..50_000, &CompareMode::Low128.checker(target))
+            .expect("Should find a nonce");
+
+        // Même préimage que pow::compute_hash: premier nonce valide identique
+        let expected = (0..50_000u128)
+            .find(|&n| pow::verify_nonce(&challenge, &miner_pubkey, n, block_number, target, HashAlgo::Sha256));
+        assert_eq!(Some(nonce), expected);
+    }
+
+    #[test]
+    fn test_cpu_miner_sha_impls_find_same_nonce() {
+        let target = u128::MAX / 2_000;
+        let expected = SimpleCpuMiner::default().mine(&[7; 32], &[8; 32], 3, target, 100_000);
+        assert!(expected.is_some());
+
+        for sha in ShaImpl::available() {
+            let miner = CpuMiner::new(1).with_sha_impl(sha);
+            assert_eq!(miner.mine(&[7; 32], &[8; 32], 3, target, 100_000), expected, "{}", sha.name());
+        }
+    }
+
+    #[test]
+    fn test_stopped_control_returns_none() {
+        let control = MiningControl::new();
+        control.stop();
+
+        let miner = CpuMiner::new(2);
+        let result = miner.mine_with_control(&[0u8; 32], &[1u8; 32], 0, u128::MAX / 1_000, 0..u128::MAX, &control);
+
+        assert!(result.is_none());
+        assert_eq!(control.hashes(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_mine_async_counts_hashes() {
+        let challenge = [0u8; 32];
+        let miner_pubkey = [1u8; 32];
+        let target = u128::MAX / 10_000;
+        let control = MiningControl::new();
+
+        let miner: Arc<dyn MinerBackend> = Arc::new(CpuMiner::new(2));
+        let nonce = mine_async(miner, challenge, miner_pubkey, 5, target, 0..u128::MAX, control.clone())
+            .await
+            .unwrap()
+            .expect("Should find a nonce");
+
+        assert!(pow::verify_nonce(&challenge, &miner_pubkey, nonce, 5, target, HashAlgo::Sha256));
+        assert!(control.hashes() > 0);
+    }
+
+    /// Backend dont l'allocation échoue, comme un GPU à court de mémoire
+    struct OutOfMemoryMiner;
+
+    impl MinerBackend for OutOfMemoryMiner {
+        fn mine_with_control(&self, _: &[u8; 32], _: &[u8; 32], _: u64, _: u128, _: Range<u128>, control: &MiningControl) -> Option<u128> {
+            control.fail(&anyhow!("DriverError(CUDA_ERROR_OUT_OF_MEMORY, \"out of memory\")").context("Failed to allocate GPU memory"));
+            None
+        }
+
+        fn name(&self) -> &str {
+            "OOM"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_backend_failure_is_an_error() {
+        let control = MiningControl::new();
+        let result = mine_async(Arc::new(OutOfMemoryMiner), [0u8; 32], [0u8; 32], 0, u128::MAX, 0..u128::MAX, control.clone()).await;
+        let error = format!("{:#}", result.expect_err("allocation failure must not look like 'no solution'"));
+        assert!(error.contains("CUDA_ERROR_OUT_OF_MEMORY"), "{}", error);
+
+        // Dans un composite, l'échec d'un backend n'empêche pas les autres de trouver
+        let composite = CompositeMiner::new(vec![Box::new(OutOfMemoryMiner), Box::new(SimpleCpuMiner::default())]);
+        let nonce = mine_async(Arc::new(composite), [0u8; 32], [0u8; 32], 0, u128::MAX / 2, 0..1_000, MiningControl::new()).await;
+        assert!(nonce.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_mine_async_cancellation() {
+        let control = MiningControl::new();
+        let miner: Arc<dyn MinerBackend> = Arc::new(CpuMiner::new(2));
+
+        // Target inatteignable: seul l'arrêt peut terminer le mining
+        let handle = tokio::spawn(mine_async(miner, [0u8; 32], [1u8; 32], 0, 0, 0..u128::MAX, control.clone()));
+
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        control.stop();
+
+        let result = tokio::time::timeout(std::time::Duration::from_secs(5), handle)
+            .await
+            .expect("Mining should stop after cancellation")
+            .unwrap()
+            .unwrap();
+        assert!(result.is_none());
+        assert!(control.hashes() > 0);
+    }
+
+    #[tokio::test]
+    async fn test_nonce_stream_keeps_mining_after_a_find() {
+        let (challenge, miner_pubkey, target) = ([0u8; 32], [1u8; 32], u128::MAX / 100);
+        let control = MiningControl::new();
+        let miner: Arc<dyn MinerBackend> = Arc::new(SimpleCpuMiner::default());
+        let mut stream = NonceStream::start(miner, challenge, miner_pubkey, 5, target, std::iter::once(0..u128::MAX).collect(), control.round());
+
+        // Plusieurs solutions distinctes et croissantes pour le même challenge, sans relance
+        let mut found = Vec::new();
+        for _ in 0..5 {
+            let nonce = stream.next().await.unwrap().expect("the search should continue after a find");
+            assert!(pow::verify_nonce(&challenge, &miner_pubkey, nonce, 5, target, HashAlgo::Sha256));
+            found.push(nonce);
+        }
+        assert!(found.windows(2).all(|pair| pair[0] < pair[1]));
+
+        // Nouveau challenge: la recherche s'arrête, seuls les nonces déjà envoyés restent
+        stream.cancel();
+        let drained = tokio::time::timeout(Duration::from_secs(5), async {
+            while stream.next().await.unwrap().is_some() {}
+        });
+        drained.await.expect("the search should end once cancelled");
+        assert!(control.is_running());
+    }
+
+    #[tokio::test]
+    async fn test_nonce_stream_walks_ranges_in_order() {
+        // Target facile: chaque nonce est valide, l'ordre d'envoi est l'ordre de parcours
+        let miner: Arc<dyn MinerBackend> = Arc::new(SimpleCpuMiner::default());
+        let mut stream = NonceStream::start(miner, [0u8; 32], [1u8; 32], 0, u128::MAX, vec![5..8, 0..5], MiningControl::new());
+        let mut found = Vec::new();
+        while let Some(nonce) = stream.next().await.unwrap() {
+            found.push(nonce);
+        }
+        assert_eq!(found, [5, 6, 7, 0, 1, 2, 3, 4]);
+    }
+
+    #[tokio::test]
+    async fn test_nonce_stream_follows_global_stop_and_failures() {
+        let control = MiningControl::new();
+        let miner: Arc<dyn MinerBackend> = Arc::new(CpuMiner::new(2));
+        let mut stream = NonceStream::start(miner, [0u8; 32], [1u8; 32], 0, 0, std::iter::once(0..u128::MAX).collect(), control.round());
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        control.stop();
+        let result = tokio::time::timeout(Duration::from_secs(5), stream.next()).await.expect("Ctrl-C should reach the backend");
+        assert!(result.unwrap().is_none());
+
+        let mut failing = NonceStream::start(Arc::new(OutOfMemoryMiner), [0u8; 32], [0u8; 32], 0, u128::MAX, std::iter::once(0..u128::MAX).collect(), MiningControl::new());
+        assert!(failing.next().await.is_err());
+    }
+
+    #[test]
+    fn test_double_sha_miners_agree() {
+        let challenge = [3u8; 32];
+        let miner_pubkey = [4u8; 32];
+        let block_number = 7;
+        let target = u128::MAX / 2_000;
+        let algo = HashAlgo::DoubleSha256;
+
+        let simple = SimpleCpuMiner { hash_algo: algo };
+        let expected = simple.mine(&challenge, &miner_pubkey, block_number, target, 100_000)
+            .expect("Should find a nonce");
+        assert!(pow::verify_nonce(&challenge, &miner_pubkey, expected, block_number, target, algo));
+
+        // Un seul thread: même parcours séquentiel que la référence
+        let cpu = CpuMiner::new(1).with_hash_algo(algo);
+        assert_eq!(cpu.mine(&challenge, &miner_pubkey, block_number, target, 100_000), Some(expected));
+    }
+
+    #[test]
+    fn test_composite_returns_first_valid_nonce() {
+        let challenge = [5u8; 32];
+        let miner_pubkey = [6u8; 32];
+        let block_number = 11;
+        let target = u128::MAX / 5_000;
+
+        let composite = CompositeMiner::new(vec![Box::new(CpuMiner::new(2)), Box::new(SimpleCpuMiner::default())]);
+        assert_eq!(composite.name(), "CPU+CPU (Simple)");
+
+        let control = MiningControl::new();
+        let nonce = composite
+            .mine_with_control(&challenge, &miner_pubkey, block_number, target, 0..u128::MAX, &control)
+            .expect("Should find a nonce");
+
+        assert!(pow::verify_nonce(&challenge, &miner_pubkey, nonce, block_number, target, HashAlgo::Sha256));
+        assert!(control.hashes() > 0);
+        // Le flag global reste levé pour le round suivant
+        assert!(control.is_running());
+    }
+
+    #[test]
+    fn test_composite_stops_all_backends() {
+        let composite = CompositeMiner::new(vec![Box::new(CpuMiner::new(1)), Box::new(SimpleCpuMiner::default())]);
+        let (_, elapsed) = warm_up(&composite, Duration::from_millis(50));
+
+        assert!(elapsed < Duration::from_secs(2), "composite did not stop: {:?}", elapsed);
+    }
+
+    #[test]
+    fn test_warm_up_respects_duration() {
+        let miner = CpuMiner::new(2);
+        let (hashes, elapsed) = warm_up(&miner, Duration::from_millis(100));
+
+        assert!(elapsed >= Duration::from_millis(100));
+        assert!(elapsed < Duration::from_secs(2), "warm-up overran: {:?}", elapsed);
+        assert!(hashes > 0);
+    }
+
+    #[test]
+    fn test_cpu_miners_pass_conformance_vector() {
+        use pow::vectors::*;
+
+        let miners: [Box<dyn MinerBackend>; 2] = [Box::new(CpuMiner::new(2)), Box::new(SimpleCpuMiner::default())];
+        for miner in &miners {
+            let control = MiningControl::new();
+            let nonces = NONCE..NONCE + 1;
+            assert_eq!(miner.mine_with_control(&CHALLENGE, &MINER_PUBKEY, BLOCK_NUMBER, VALID_TARGET, nonces.clone(), &control), Some(NONCE));
+            assert_eq!(miner.mine_with_control(&CHALLENGE, &MINER_PUBKEY, BLOCK_NUMBER, INVALID_TARGET, nonces, &control), None);
+        }
+    }
 }
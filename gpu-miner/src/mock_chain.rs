@@ -0,0 +1,335 @@
+//! Protocole simulé en mémoire (`--mock-chain`), pour tester la boucle de mining sans RPC
+
+use crate::chain::{Chain, FeeCounters, MinerAccount, PowState};
+use crate::config::PdaSeeds;
+use crate::pow::{self, CompareMode, HashAlgo};
+use crate::signer::LocalSigner;
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use anchor_client::solana_sdk::signature::Keypair;
+use anyhow::{anyhow, Result};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Récompense créditée par bloc (unités de base)
+const MOCK_REWARD: u64 = 1_000_000_000;
+
+/// Décimales du mint simulé
+const MOCK_DECIMALS: u8 = 9;
+
+/// Solde SOL simulé de chaque mineur (1 SOL)
+const MOCK_BALANCE: u64 = 1_000_000_000;
+
+/// Preuves acceptées par transaction simulée
+const MOCK_MAX_PROOFS: usize = 8;
+
+/// Protocole simulé: vérifie les preuves comme le programme et fait tourner le challenge
+pub struct MockChain {
+    miners: Vec<MinerAccount>,
+    next_miner: AtomicUsize,
+    /// Mint configuré, celui de l'état sauf `with_configured_mint`
+    mint: Pubkey,
+    hash_algo: HashAlgo,
+    /// Comparaison d'une variante du programme (`--compare-mode`)
+    compare_mode: CompareMode,
+    state: Mutex<PowState>,
+    token_balances: Mutex<HashMap<Pubkey, u64>>,
+    /// Transactions submit_proof reçues, acceptées ou non
+    submissions: AtomicUsize,
+    /// État figé renvoyé par `get_pow_state`, comme un RPC en retard
+    stale_state: Option<PowState>,
+    /// Lectures de l'état via `get_pow_state`
+    reads: AtomicUsize,
+    /// (lectures, difficulté): la difficulté change après ce nombre de lectures
+    difficulty_change: Option<(usize, u128)>,
+    /// Le challenge suivant mêle aussi le numéro de bloc, que `predict_challenge` ignore
+    unpredictable_rotation: bool,
+    /// `predict_challenge` retourne None, comme sur le programme déployé
+    opaque_rotation: bool,
+}
+
+impl MockChain {
+    /// Protocole initialisé à `difficulty`, miné par un keypair local éphémère
+    pub fn new(difficulty: u128, hash_algo: HashAlgo) -> Self {
+        let program_id = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+
+        Self {
+            miners: vec![MinerAccount::new(Box::new(LocalSigner::new(Keypair::new())), &program_id, &mint, &PdaSeeds::default())],
+            next_miner: AtomicUsize::new(0),
+            mint,
+            hash_algo,
+            compare_mode: CompareMode::default(),
+            state: Mutex::new(PowState {
+                authority: Pubkey::new_unique(),
+                mint,
+                difficulty,
+                last_block_ts: unix_now(),
+                blocks_mined: 0,
+                total_supply_mined: 0,
+                challenge: Sha256::digest(b"mock-chain genesis").into(),
+                pending_reward_tokens: MOCK_REWARD,
+                fee_sol: 0,
+                fees: FeeCounters::default(),
+                launch_ts: unix_now(),
+                is_initialized: true,
+                is_paused: false,
+            }),
+            token_balances: Mutex::new(HashMap::new()),
+            submissions: AtomicUsize::new(0),
+            stale_state: None,
+            reads: AtomicUsize::new(0),
+            difficulty_change: None,
+            unpredictable_rotation: false,
+            opaque_rotation: false,
+        }
+    }
+
+    /// Simule un programme qui compare le hash selon `compare_mode`
+    pub fn with_compare_mode(mut self, compare_mode: CompareMode) -> Self {
+        self.compare_mode = compare_mode;
+        self
+    }
+
+    /// `get_pow_state` renvoie toujours l'état initial, même après un bloc accepté
+    #[cfg(test)]
+    pub fn with_stale_reads(mut self) -> Self {
+        self.stale_state = Some(self.state());
+        self
+    }
+
+    /// La difficulté passe à `difficulty` après `reads` lectures de l'état, comme un
+    /// ajustement on-chain pendant le mining
+    #[cfg(test)]
+    pub fn with_difficulty_change(mut self, reads: usize, difficulty: u128) -> Self {
+        self.difficulty_change = Some((reads, difficulty));
+        self
+    }
+
+    /// Rotation du challenge différente de celle prévue par `predict_challenge`, comme un
+    /// programme mis à jour: toute spéculation est fausse
+    #[cfg(test)]
+    pub fn with_unpredictable_rotation(mut self) -> Self {
+        self.unpredictable_rotation = true;
+        self
+    }
+
+    /// Challenge suivant imprévisible pour le mineur (ni --speculative ni --batch-submit)
+    #[cfg(test)]
+    pub fn without_prediction(mut self) -> Self {
+        self.opaque_rotation = true;
+        self
+    }
+
+    /// Mint configuré différent de celui de l'état, comme un `--mint` erroné
+    #[cfg(test)]
+    pub fn with_configured_mint(mut self, mint: Pubkey) -> Self {
+        self.mint = mint;
+        self
+    }
+
+    #[cfg(test)]
+    pub fn submissions(&self) -> usize {
+        self.submissions.load(Ordering::Relaxed)
+    }
+
+    /// Copie de l'état courant
+    pub fn state(&self) -> PowState {
+        self.state.lock().unwrap().clone()
+    }
+}
+
+impl Chain for MockChain {
+    fn miners(&self) -> &[MinerAccount] {
+        &self.miners
+    }
+
+    fn next_miner(&self) -> &MinerAccount {
+        let index = self.next_miner.fetch_add(1, Ordering::Relaxed);
+        &self.miners[index % self.miners.len()]
+    }
+
+    fn endpoint(&self) -> &str {
+        "mock"
+    }
+
+    fn mint(&self) -> Pubkey {
+        self.mint
+    }
+
+    async fn get_balance(&self, _miner: &MinerAccount) -> Result<u64> {
+        Ok(MOCK_BALANCE)
+    }
+
+    async fn get_pow_state(&self) -> Result<PowState> {
+        let reads = self.reads.fetch_add(1, Ordering::Relaxed);
+        if let Some((after, difficulty)) = self.difficulty_change {
+            if reads >= after {
+                self.state.lock().unwrap().difficulty = difficulty;
+            }
+        }
+        Ok(self.stale_state.clone().unwrap_or_else(|| self.state()))
+    }
+
+    /// Les fees ne sont jamais distribuées: le vault garde tout ce qui a été collecté
+    async fn get_fee_vault_balance(&self) -> Result<u64> {
+        Ok(self.state().fees.total_fees_collected)
+    }
+
+    async fn seconds_since_last_block(&self, state: &PowState) -> Result<i64> {
+        Ok(state.seconds_since_last_block(unix_now()))
+    }
+
+    /// Challenge suivant: SHA256(challenge || nonce LE)
+    fn predict_challenge(&self, state: &PowState, nonce: u128) -> Option<[u8; 32]> {
+        (!self.opaque_rotation).then(|| next_challenge(&state.challenge, nonce))
+    }
+
+    /// Vérifie la preuve, crédite la récompense et dérive le challenge suivant
+    async fn submit_proof(&self, miner: &MinerAccount, nonce: u128) -> Result<String> {
+        self.submit_proofs(miner, &[nonce]).await
+    }
+
+    fn max_proofs_per_transaction(&self) -> usize {
+        MOCK_MAX_PROOFS
+    }
+
+    /// Applique les preuves dans l'ordre sur une copie de l'état: une preuve invalide annule
+    /// toute la transaction, comme on-chain
+    async fn submit_proofs(&self, miner: &MinerAccount, nonces: &[u128]) -> Result<String> {
+        self.submissions.fetch_add(1, Ordering::Relaxed);
+        if nonces.len() > MOCK_MAX_PROOFS {
+            return Err(anyhow!("Transaction too large: {} proofs", nonces.len()));
+        }
+
+        let mut state = self.state.lock().unwrap();
+        let mut next = state.clone();
+        let pubkey = miner.pubkey().to_bytes();
+        for &nonce in nonces {
+            let target = pow::difficulty_to_target(next.difficulty);
+            if !self.compare_mode.verify_nonce(&next.challenge, &pubkey, nonce, next.blocks_mined, target, self.hash_algo) {
+                return Err(anyhow!("Invalid proof: nonce {} for block {}", nonce, next.blocks_mined));
+            }
+
+            next.challenge = next_challenge(&next.challenge, nonce);
+            if self.unpredictable_rotation {
+                next.challenge = next_challenge(&next.challenge, next.blocks_mined.into());
+            }
+            next.blocks_mined += 1;
+            next.total_supply_mined += MOCK_REWARD;
+            next.fees.total_fees_collected += next.fee_sol;
+            next.last_block_ts = unix_now();
+        }
+
+        let reward = MOCK_REWARD * nonces.len() as u64;
+        *self.token_balances.lock().unwrap().entry(miner.pubkey()).or_default() += reward;
+        *state = next;
+
+        Ok(format!("mock-{}", state.blocks_mined))
+    }
+
+    async fn ensure_token_account(&self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn miner_stats_rent(&self, _miner: &MinerAccount) -> Result<u64> {
+        Ok(0)
+    }
+
+    async fn get_token_balance(&self, miner: &MinerAccount) -> Result<u64> {
+        Ok(self.token_balances.lock().unwrap().get(&miner.pubkey()).copied().unwrap_or(0))
+    }
+
+    async fn mint_decimals(&self) -> Result<u8> {
+        Ok(MOCK_DECIMALS)
+    }
+}
+
+/// Rotation du challenge après un bloc accepté
+fn next_challenge(challenge: &[u8; 32], nonce: u128) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(challenge);
+    hasher.update(nonce.to_le_bytes());
+    hasher.finalize().into()
+}
+
+fn unix_now() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_submit_rotates_challenge() {
+        let chain = MockChain::new(1_000, HashAlgo::Sha256);
+        let miner = chain.next_miner();
+        let state = chain.state();
+        let target = pow::difficulty_to_target(state.difficulty);
+        let pubkey = miner.pubkey().to_bytes();
+
+        let nonce = (0..100_000u128)
+            .find(|&n| pow::verify_nonce(&state.challenge, &pubkey, n, state.blocks_mined, target, HashAlgo::Sha256))
+            .unwrap();
+        let invalid = (0..100_000u128)
+            .find(|&n| !pow::verify_nonce(&state.challenge, &pubkey, n, state.blocks_mined, target, HashAlgo::Sha256))
+            .unwrap();
+
+        assert!(chain.submit_proof(miner, invalid).await.is_err());
+        chain.submit_proof(miner, nonce).await.unwrap();
+
+        let next = chain.state();
+        assert_eq!(next.blocks_mined, 1);
+        assert_ne!(next.challenge, state.challenge);
+        assert_eq!(chain.predict_challenge(&state, nonce), Some(next.challenge));
+        assert_eq!(chain.get_token_balance(miner).await.unwrap(), MOCK_REWARD);
+    }
+
+    #[tokio::test]
+    async fn test_batched_proofs_are_all_or_nothing() {
+        let chain = MockChain::new(1_000, HashAlgo::Sha256);
+        let miner = chain.next_miner();
+        let pubkey = miner.pubkey().to_bytes();
+        let target = pow::difficulty_to_target(1_000);
+        let first_valid = |challenge: &[u8; 32], block: u64| {
+            (0..100_000u128).find(|&n| pow::verify_nonce(challenge, &pubkey, n, block, target, HashAlgo::Sha256)).unwrap()
+        };
+
+        // Second nonce miné sur le challenge prévu après le premier
+        let state = chain.state();
+        let first = first_valid(&state.challenge, 0);
+        let predicted = chain.predict_challenge(&state, first).unwrap();
+        let second = first_valid(&predicted, 1);
+
+        // Une preuve invalide annule la transaction entière
+        let invalid = (0..100_000u128).find(|&n| !pow::verify_nonce(&predicted, &pubkey, n, 1, target, HashAlgo::Sha256)).unwrap();
+        assert!(chain.submit_proofs(miner, &[first, invalid]).await.is_err());
+        assert_eq!(chain.state().challenge, state.challenge);
+        assert_eq!(chain.get_token_balance(miner).await.unwrap(), 0);
+
+        chain.submit_proofs(miner, &[first, second]).await.unwrap();
+        assert_eq!(chain.state().blocks_mined, 2);
+        assert_eq!(chain.get_token_balance(miner).await.unwrap(), 2 * MOCK_REWARD);
+    }
+
+    #[tokio::test]
+    async fn test_full_256_chain_rejects_low_128_solutions() {
+        let chain = MockChain::new(1_000, HashAlgo::Sha256).with_compare_mode(CompareMode::Full256);
+        let miner = chain.next_miner();
+        let state = chain.state();
+        let target = pow::difficulty_to_target(state.difficulty);
+        let pubkey = miner.pubkey().to_bytes();
+        let valid = |mode: CompareMode, n: u128| mode.verify_nonce(&state.challenge, &pubkey, n, state.blocks_mined, target, HashAlgo::Sha256);
+
+        // Valide pour le programme déployé, pas pour une variante qui compare le digest entier
+        let low_only = (0..1_000_000u128).find(|&n| valid(CompareMode::Low128, n) && !valid(CompareMode::Full256, n)).unwrap();
+        assert!(chain.submit_proof(miner, low_only).await.is_err());
+
+        let nonce = (0..1_000_000u128).find(|&n| valid(CompareMode::Full256, n)).unwrap();
+        chain.submit_proof(miner, nonce).await.unwrap();
+        assert_eq!(chain.state().blocks_mined, 1);
+    }
+}
@@ -0,0 +1,167 @@
+//! Topologie NUMA et placement des threads CPU sur leur nœud (--affinity-numa)
+//!
+//! Sur une machine multi-socket, un thread qui hashe depuis la mémoire d'un autre socket
+//! paie la traversée de l'interconnexion. Sans effet sur une machine à un seul nœud.
+//! Avec plusieurs GPU, le thread qui alimente chaque device est placé sur le nœud de sa
+//! racine PCIe, quand le noyau l'expose.
+
+use anyhow::{anyhow, Result};
+use std::fs;
+use std::path::Path;
+
+const SYSFS_NODES: &str = "/sys/devices/system/node";
+
+#[cfg_attr(not(feature = "cuda"), allow(dead_code))] // Adresse PCI donnée par CUDA seulement
+const SYSFS_PCI_DEVICES: &str = "/sys/bus/pci/devices";
+
+/// Nœud NUMA et ses CPUs
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    pub id: usize,
+    pub cpus: Vec<usize>,
+}
+
+/// Parse une liste de CPUs du noyau ("0-15,32-47")
+pub fn parse_cpulist(list: &str) -> Result<Vec<usize>> {
+    let mut cpus = Vec::new();
+    for part in list.trim().split(',').filter(|part| !part.is_empty()) {
+        let (first, last) = match part.split_once('-') {
+            Some((first, last)) => (first.parse::<usize>()?, last.parse::<usize>()?),
+            None => {
+                let cpu = part.parse::<usize>()?;
+                (cpu, cpu)
+            }
+        };
+        if first > last {
+            return Err(anyhow!("Invalid CPU range {}", part));
+        }
+        cpus.extend(first..=last);
+    }
+    Ok(cpus)
+}
+
+/// Nœuds NUMA ayant au moins un CPU; vide si la topologie n'est pas exposée (hors Linux)
+pub fn nodes() -> Vec<Node> {
+    let Ok(entries) = fs::read_dir(SYSFS_NODES) else {
+        return Vec::new();
+    };
+    let mut nodes: Vec<Node> = entries
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let id = entry.file_name().to_str()?.strip_prefix("node")?.parse().ok()?;
+            let cpus = parse_cpulist(&fs::read_to_string(entry.path().join("cpulist")).ok()?).ok()?;
+            (!cpus.is_empty()).then_some(Node { id, cpus })
+        })
+        .collect();
+    nodes.sort_by_key(|node| node.id);
+    nodes
+}
+
+/// Adresse sysfs d'un device PCI ("0000:65:00.0", fonction 0)
+#[cfg_attr(not(feature = "cuda"), allow(dead_code))]
+pub fn pci_address(domain: u32, bus: u32, device: u32) -> String {
+    format!("{:04x}:{:02x}:{:02x}.0", domain, bus, device)
+}
+
+/// Nœud NUMA le plus proche du device PCI `address`; None si le noyau ne le donne pas
+/// (-1 sur une machine à un seul nœud, hors Linux)
+#[cfg_attr(not(feature = "cuda"), allow(dead_code))]
+pub fn pci_numa_node(address: &str) -> Option<usize> {
+    pci_numa_node_in(Path::new(SYSFS_PCI_DEVICES), address)
+}
+
+#[cfg_attr(not(feature = "cuda"), allow(dead_code))]
+fn pci_numa_node_in(devices: &Path, address: &str) -> Option<usize> {
+    fs::read_to_string(devices.join(address).join("numa_node")).ok()?.trim().parse().ok()
+}
+
+/// Nœud de `nodes` qui alimente chaque device, d'après le nœud de sa racine PCIe
+///
+/// None pour un device sans topologie connue, ou rattaché à un nœud sans CPU.
+pub fn pair_devices(device_nodes: &[Option<usize>], nodes: &[Node]) -> Vec<Option<Node>> {
+    device_nodes.iter().map(|id| id.and_then(|id| nodes.iter().find(|node| node.id == id).cloned())).collect()
+}
+
+/// Erreur si ce binaire ne sait pas placer les threads (feature `numa` absente ou hors Linux)
+pub fn check() -> Result<()> {
+    if cfg!(all(feature = "numa", target_os = "linux")) {
+        Ok(())
+    } else {
+        Err(anyhow!("--affinity-numa: built without the numa feature (Linux only)"))
+    }
+}
+
+/// Épingle le thread courant sur les CPUs de `node` et y préfère ses allocations
+///
+/// Seules les pages touchées ensuite par le thread (pile, allocations) sont locales: la
+/// mémoire déjà allouée ne migre pas.
+#[cfg(all(feature = "numa", target_os = "linux"))]
+pub fn bind_current_thread(node: &Node) -> Result<()> {
+    use anyhow::Context;
+
+    let mask: libc::c_ulong = 1 << u32::try_from(node.id).ok().filter(|id| *id < libc::c_ulong::BITS).context("NUMA node id out of range")?;
+
+    // SAFETY: cpu_set_t est un simple bitmap, initialisé à zéro puis rempli par CPU_SET
+    let affinity = unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        for &cpu in &node.cpus {
+            libc::CPU_SET(cpu, &mut set);
+        }
+        libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set)
+    };
+    if affinity != 0 {
+        return Err(std::io::Error::last_os_error()).context(format!("sched_setaffinity on NUMA node {}", node.id));
+    }
+
+    // MPOL_PREFERRED: pages du nœud tant qu'il en reste, repli ailleurs sinon
+    const MPOL_PREFERRED: libc::c_int = 1;
+    // SAFETY: `mask` vit jusqu'au retour; maxnode couvre exactement ses bits
+    let policy = unsafe { libc::syscall(libc::SYS_set_mempolicy, MPOL_PREFERRED, &mask as *const libc::c_ulong, libc::c_ulong::BITS as libc::c_ulong + 1) };
+    if policy != 0 {
+        return Err(std::io::Error::last_os_error()).context(format!("set_mempolicy on NUMA node {}", node.id));
+    }
+    Ok(())
+}
+
+#[cfg(not(all(feature = "numa", target_os = "linux")))]
+pub fn bind_current_thread(_node: &Node) -> Result<()> {
+    check()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cpulist() {
+        assert_eq!(parse_cpulist("0-3,8,10-11\n").unwrap(), vec![0, 1, 2, 3, 8, 10, 11]);
+        assert_eq!(parse_cpulist("5").unwrap(), vec![5]);
+        assert!(parse_cpulist("").unwrap().is_empty());
+        assert!(parse_cpulist("3-1").is_err());
+        assert!(parse_cpulist("a-b").is_err());
+    }
+
+    #[test]
+    fn test_gpus_paired_with_their_pcie_node() {
+        // sysfs simulé: GPU 0 sous le socket 1, GPU 1 sous le socket 0, GPU 2 sans affinité
+        let devices = std::env::temp_dir().join(format!("pow-miner-pci-{}", std::process::id()));
+        let gpus = [pci_address(0, 0x65, 0), pci_address(0, 0x17, 0), pci_address(1, 0x03, 0)];
+        for (address, numa_node) in gpus.iter().zip(["1\n", "0\n", "-1\n"]) {
+            std::fs::create_dir_all(devices.join(address)).unwrap();
+            std::fs::write(devices.join(address).join("numa_node"), numa_node).unwrap();
+        }
+        assert_eq!(gpus[0], "0000:65:00.0");
+
+        let mut device_nodes: Vec<Option<usize>> = gpus.iter().map(|address| pci_numa_node_in(&devices, address)).collect();
+        // Device absent de sysfs (OpenCL sans adresse PCI)
+        device_nodes.push(pci_numa_node_in(&devices, "0000:99:00.0"));
+        std::fs::remove_dir_all(&devices).unwrap();
+        assert_eq!(device_nodes, [Some(1), Some(0), None, None]);
+
+        let nodes = [Node { id: 0, cpus: vec![0, 1, 2, 3] }, Node { id: 1, cpus: vec![4, 5, 6, 7] }];
+        let pairing = pair_devices(&device_nodes, &nodes);
+        assert_eq!(pairing, [Some(nodes[1].clone()), Some(nodes[0].clone()), None, None]);
+        // Nœud sans CPU (mémoire seule): pas de placement
+        assert_eq!(pair_devices(&[Some(2)], &nodes), [None]);
+    }
+}
@@ -1,22 +1,457 @@
-//! OpenCL Mining Backend (TODO)
+//! OpenCL Mining Backend
 
-use crate::miner::MinerBackend;
-use anyhow::{Result, anyhow};
+use crate::miner::{dedup_devices, select_device, IdleCheck, MinerBackend, MiningControl};
+use crate::pow::{self, HashAlgo, ShaImpl};
+use ocl::enums::{DeviceInfo, ProgramInfo, ProgramInfoResult};
+use ocl::{Buffer, Program, ProQue, Queue};
+use anyhow::{Context, Result, anyhow};
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::{Mutex, OnceLock};
+#[cfg(test)]
+use std::sync::atomic::{AtomicBool, Ordering};
 
-pub struct OpenClMiner;
+/// Work-items par lancement du kernel
+const WORK_SIZE: usize = 1024 * 256;
+
+/// Nonces testés par work-item à chaque lancement du kernel
+const NONCES_PER_THREAD: u32 = 16;
+
+const KERNEL_SOURCE: &str = include_str!("../kernels/sha256_mining.cl");
+
+/// Device (vendor, nom, driver) et SHA256 du source du kernel
+type ProgramKey = (String, [u8; 32]);
+
+/// Binaires du kernel déjà compilés dans ce processus: un second miner sur le même modèle
+/// de device (benchmark, plusieurs GPU) recharge le binaire au lieu de recompiler
+static PROGRAM_CACHE: OnceLock<Mutex<HashMap<ProgramKey, Vec<u8>>>> = OnceLock::new();
+
+fn program_cache() -> &'static Mutex<HashMap<ProgramKey, Vec<u8>>> {
+    PROGRAM_CACHE.get_or_init(Default::default)
+}
+
+/// Éléments de garde ajoutés après chaque sortie, remplis de `GUARD_BYTE`: le kernel ne
+/// doit jamais les toucher
+const GUARD_LEN: usize = 16;
+
+const GUARD_BYTE: u8 = 0xa5;
+
+/// Buffers d'entrée et de sortie du kernel, recréés quand les dims du lancement changent
+///
+/// Une seule solution par lancement (le premier work-item à lever `found` écrit seul
+/// `result_nonce`), et un compteur `tested` par work-item lancé. Chaque sortie est suivie
+/// de `GUARD_LEN` éléments de garde.
+struct KernelBuffers {
+    challenge: Buffer<u8>,
+    miner_pubkey: Buffer<u8>,
+    target: Buffer<u8>,
+    result_nonce: Buffer<u64>,
+    found: Buffer<u32>,
+    tested: Buffer<u32>,
+    /// Work-items du lancement, taille utile de `tested`
+    work_items: usize,
+}
+
+impl KernelBuffers {
+    /// Solutions écrites par lancement
+    const SLOTS: usize = 1;
+
+    fn new(pro_que: &ProQue, work_items: usize) -> Result<Self> {
+        let bytes = |len| Buffer::<u8>::builder().queue(pro_que.queue().clone()).len(len).fill_val(0u8).build();
+        let words = |len| Buffer::<u32>::builder().queue(pro_que.queue().clone()).len(len + GUARD_LEN).fill_val(u32::from_ne_bytes([GUARD_BYTE; 4])).build();
+        Ok(Self {
+            challenge: bytes(32)?,
+            miner_pubkey: bytes(32)?,
+            target: bytes(16)?,
+            result_nonce: Buffer::<u64>::builder()
+                .queue(pro_que.queue().clone())
+                .len(Self::SLOTS + GUARD_LEN)
+                .fill_val(u64::from_ne_bytes([GUARD_BYTE; 8]))
+                .build()?,
+            found: words(Self::SLOTS)?,
+            tested: words(work_items)?,
+            work_items,
+        })
+    }
+
+    /// Remet les sorties à zéro: un lancement interrompu par une erreur peut y avoir
+    /// laissé `found` levé et le nonce d'un autre challenge. La garde reste en place.
+    fn reset(&self) -> Result<()> {
+        self.found.write(&[0u32; Self::SLOTS][..]).enq()?;
+        self.result_nonce.write(&[0u64; Self::SLOTS][..]).enq()?;
+        Ok(())
+    }
+
+    /// Erreur si le kernel a écrit au-delà des sorties
+    #[cfg_attr(not(debug_assertions), allow(dead_code))]
+    fn check_guards(&self) -> Result<()> {
+        let mut nonce = vec![0u64; Self::SLOTS + GUARD_LEN];
+        let mut found = vec![0u32; Self::SLOTS + GUARD_LEN];
+        let mut tested = vec![0u32; self.work_items + GUARD_LEN];
+        self.result_nonce.read(&mut nonce).enq()?;
+        self.found.read(&mut found).enq()?;
+        self.tested.read(&mut tested).enq()?;
+
+        let intact = nonce[Self::SLOTS..].iter().all(|word| word.to_ne_bytes() == [GUARD_BYTE; 8])
+            && found[Self::SLOTS..].iter().all(|word| word.to_ne_bytes() == [GUARD_BYTE; 4])
+            && tested[self.work_items..].iter().all(|word| word.to_ne_bytes() == [GUARD_BYTE; 4]);
+        if !intact {
+            return Err(anyhow!("OpenCL kernel wrote past its output buffers"));
+        }
+        Ok(())
+    }
+
+    /// Nonces hachés au dernier lancement, tous work-items confondus
+    fn tested(&self) -> Result<u64> {
+        let mut tested = vec![0u32; self.work_items];
+        self.tested.read(&mut tested).enq()?;
+        Ok(tested.iter().map(|&count| count as u64).sum())
+    }
+}
+
+pub struct OpenClMiner {
+    pro_que: ProQue,
+    /// Un seul mining à la fois sur les buffers du miner
+    buffers: Mutex<KernelBuffers>,
+    /// Work-items par lancement, égal aux dims du `ProQue`
+    work_items: usize,
+    hash_algo: HashAlgo,
+    /// Les work-items relisent `found` avant chaque nonce et s'arrêtent dès qu'un voisin a trouvé
+    early_exit: bool,
+    device_name: String,
+    /// Programme rechargé depuis `PROGRAM_CACHE` plutôt que compilé
+    #[cfg(test)]
+    from_cache: bool,
+    /// Fait échouer le prochain batch après le lancement du kernel
+    #[cfg(test)]
+    fail_next_batch: AtomicBool,
+}
 
 impl OpenClMiner {
-    pub fn new(_device_id: usize) -> Result<Self> {
-        Err(anyhow!("OpenCL support not yet implemented"))
+    pub fn new(device_id: usize) -> Result<Self> {
+        let (platform, device) = select_device("OpenCL", list_devices()?, device_id)?;
+        let device_name = device.name().unwrap_or_default();
+
+        let context = ocl::Context::builder().platform(platform).devices(device).build()?;
+        let queue = Queue::new(&context, device, None)?;
+
+        // Binaire en cache pour ce device et ce source, sinon compilation puis mise en cache
+        let key = program_key(&device);
+        let cached = program_cache().lock().unwrap().get(&key).cloned();
+        let from_cache = cached.and_then(|binary| {
+            Program::builder()
+                .devices(device)
+                .binaries(&[&binary[..]])
+                .build(&context)
+                .map_err(|e| log::debug!("Cached OpenCL binary rejected by {}, recompiling: {}", device_name, e))
+                .ok()
+        });
+        let (program, from_cache) = match from_cache {
+            Some(program) => (program, true),
+            None => {
+                let program = Program::builder()
+                    .devices(device)
+                    .src(KERNEL_SOURCE)
+                    .build(&context)
+                    .map_err(|e| anyhow!("Failed to build the OpenCL program: {}", e))?;
+                match program.info(ProgramInfo::Binaries) {
+                    Ok(ProgramInfoResult::Binaries(mut binaries)) if !binaries.is_empty() => {
+                        program_cache().lock().unwrap().insert(key, binaries.swap_remove(0));
+                    }
+                    other => log::debug!("OpenCL program binary not cached: {:?}", other.err()),
+                }
+                (program, false)
+            }
+        };
+        if from_cache {
+            log::debug!("OpenCL program for {} loaded from the cache", device_name);
+        }
+
+        let pro_que = ProQue::new(context, queue, program, Some(WORK_SIZE));
+        let buffers = KernelBuffers::new(&pro_que, WORK_SIZE)?;
+
+        Ok(Self {
+            pro_que,
+            buffers: Mutex::new(buffers),
+            work_items: WORK_SIZE,
+            hash_algo: HashAlgo::default(),
+            early_exit: true,
+            device_name,
+            #[cfg(test)]
+            from_cache,
+            #[cfg(test)]
+            fail_next_batch: AtomicBool::new(false),
+        })
+    }
+
+    /// Fonction de hash du kernel (SHA256 simple par défaut)
+    pub fn with_hash_algo(mut self, hash_algo: HashAlgo) -> Self {
+        self.hash_algo = hash_algo;
+        self
+    }
+
+    /// Lance `work_items` work-items par batch au lieu de `WORK_SIZE` (GPU modeste, tests)
+    pub fn with_work_items(mut self, work_items: usize) -> Result<Self> {
+        if work_items == 0 || work_items > WORK_SIZE {
+            return Err(anyhow!("OpenCL work items must be between 1 and {}, got {}", WORK_SIZE, work_items));
+        }
+        self.pro_que.set_dims(work_items);
+        self.buffers = Mutex::new(KernelBuffers::new(&self.pro_que, work_items)?);
+        self.work_items = work_items;
+        Ok(self)
     }
+
+    /// Comme `mine_with_control`, mais une erreur OpenCL (buffer, lancement du kernel)
+    /// est propagée au lieu de passer pour « aucune solution »
+    fn try_mine(&self, challenge: &[u8; 32], miner_pubkey: &[u8; 32], block_number: u64, target: u128, nonces: Range<u128>, control: &MiningControl) -> Result<Option<u128>> {
+        // Le kernel ne parcourt que les 64 bits bas du nonce
+        let max_nonce_u64 = nonces.end.min(u64::MAX as u128) as u64;
+        if nonces.start >= max_nonce_u64 as u128 {
+            return Ok(None);
+        }
+
+        let buffers = self.buffers.lock().unwrap();
+        buffers.reset().context("Failed to reset OpenCL output buffers")?;
+        buffers.challenge.write(&challenge[..]).enq().context("Failed to copy the challenge to the GPU")?;
+        buffers.miner_pubkey.write(&miner_pubkey[..]).enq().context("Failed to copy the miner pubkey to the GPU")?;
+        buffers.target.write(&target.to_le_bytes()[..]).enq().context("Failed to copy the target to the GPU")?;
+
+        // Chaque lancement couvre work_items * NONCES_PER_THREAD nonces
+        debug_assert_eq!(self.pro_que.dims().to_len(), self.work_items);
+        debug_assert_eq!(buffers.work_items, self.work_items);
+        let batch_size = self.work_items as u64 * NONCES_PER_THREAD as u64;
+        let mut start_nonce = nonces.start as u64;
+        let mut idle_check = IdleCheck::new(self.name());
+
+        while start_nonce < max_nonce_u64 && control.is_running() {
+            let nonce_count = (max_nonce_u64 - start_nonce).min(batch_size);
+
+            let kernel = self.pro_que.kernel_builder("mine")
+                .arg(&buffers.challenge)
+                .arg(&buffers.miner_pubkey)
+                .arg(block_number)
+                .arg(start_nonce)
+                .arg(nonce_count)
+                .arg(&buffers.target)
+                .arg(&buffers.result_nonce)
+                .arg(&buffers.found)
+                .arg((self.hash_algo == HashAlgo::DoubleSha256) as u32)
+                .arg(NONCES_PER_THREAD)
+                .arg(self.early_exit as u32)
+                .arg(&buffers.tested)
+                .arg(self.work_items as u64)
+                .global_work_size(self.work_items)
+                .build()
+                .context("Failed to build the OpenCL kernel")?;
+
+            let (hashes_before, launched) = (control.hashes(), std::time::Instant::now());
+            unsafe {
+                kernel.enq().context("OpenCL kernel launch failed")?;
+            }
+            self.pro_que.queue().finish().context("OpenCL kernel failed")?;
+            #[cfg(debug_assertions)]
+            buffers.check_guards()?;
+
+            #[cfg(test)]
+            if self.fail_next_batch.swap(false, Ordering::Relaxed) {
+                return Err(anyhow!("simulated OpenCL failure"));
+            }
+
+            let mut found = [0u32; KernelBuffers::SLOTS];
+            buffers.found.read(&mut found[..]).enq().context("Failed to read the result from the GPU")?;
+            // Après une trouvaille, les work-items s'arrêtent avant la fin du batch
+            control.add_hashes(buffers.tested().context("Failed to read the hash count from the GPU")?);
+            idle_check.record_batch(nonce_count, control.hashes() - hashes_before, launched.elapsed());
+            if found[0] == 1 {
+                let mut nonce = [0u64; KernelBuffers::SLOTS];
+                buffers.result_nonce.read(&mut nonce[..]).enq().context("Failed to read the result from the GPU")?;
+                let nonce = nonce[0] as u128;
+                if !pow::verify_nonce(challenge, miner_pubkey, nonce, block_number, target, self.hash_algo) {
+                    return Err(anyhow!("OpenCL kernel returned nonce {} that fails the CPU {:?} check", nonce, self.hash_algo));
+                }
+                return Ok(Some(nonce));
+            }
+
+            start_nonce += nonce_count;
+        }
+
+        Ok(None)
+    }
+}
+
+/// Devices de toutes les plateformes OpenCL, sans doublons entre plateformes
+///
+/// `ocl::Platform::list` panique sans ICD installé: on passe par `ocl::core`.
+fn list_devices() -> Result<Vec<(ocl::Platform, ocl::Device)>> {
+    let platforms = ocl::core::get_platform_ids().map_err(|e| anyhow!("No OpenCL platform: {}", e))?;
+
+    let mut devices = Vec::new();
+    for platform in platforms {
+        let platform = ocl::Platform::new(platform);
+        devices.extend(ocl::Device::list_all(platform)?.into_iter().map(|device| (platform, device)));
+    }
+
+    let (devices, duplicates) = dedup_devices(devices, |(platform, device)| (*platform.as_core(), device_key(device)));
+    for (_, device) in duplicates {
+        log::debug!("OpenCL device {} is exposed by several platforms, keeping the first", device.name().unwrap_or_default());
+    }
+    Ok(devices)
+}
+
+/// Identité physique d'un device: le bus PCI n'est exposé que par des extensions propres
+/// à chaque constructeur, on compare vendor, nom, mémoire et compute units (entre plateformes
+/// seulement, voir `dedup_devices`)
+fn device_key(device: &ocl::Device) -> [String; 4] {
+    [DeviceInfo::VendorId, DeviceInfo::Name, DeviceInfo::GlobalMemSize, DeviceInfo::MaxComputeUnits]
+        .map(|info| device.info(info).map(|value| value.to_string()).unwrap_or_default())
+}
+
+/// Clé de `PROGRAM_CACHE`: un binaire n'est valable que pour un modèle de device et un driver
+fn program_key(device: &ocl::Device) -> ProgramKey {
+    let device = [DeviceInfo::Vendor, DeviceInfo::Name, DeviceInfo::DriverVersion]
+        .map(|info| device.info(info).map(|value| value.to_string()).unwrap_or_default())
+        .join("|");
+    (device, ShaImpl::default().digest(KERNEL_SOURCE.as_bytes()))
 }
 
 impl MinerBackend for OpenClMiner {
-    fn mine(&self, _challenge: &[u8; 32], _miner_pubkey: &[u8; 32], _block_number: u64, _target: u128, _max_nonce: u128) -> Option<u128> {
-        None
+    fn mine_with_control(&self, challenge: &[u8; 32], miner_pubkey: &[u8; 32], block_number: u64, target: u128, nonces: Range<u128>, control: &MiningControl) -> Option<u128> {
+        self.try_mine(challenge, miner_pubkey, block_number, target, nonces, control).unwrap_or_else(|e| {
+            // Le `ProQue` et les buffers survivent à l'erreur: sorties remises à zéro avant de
+            // rendre la main, et de nouveau au début de chaque appel
+            if let Err(e) = self.buffers.lock().unwrap().reset() {
+                log::debug!("Failed to reset OpenCL output buffers after error: {}", e);
+            }
+            control.fail(&e.context("OpenCL mining failed"));
+            None
+        })
     }
 
     fn name(&self) -> &str {
-        "OpenCL (not implemented)"
+        "OpenCL"
+    }
+
+    fn device_name(&self) -> String {
+        self.device_name.clone()
+    }
+
+    fn is_gpu(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_double_sha_matches_cpu() {
+        // Pas de device OpenCL: rien à comparer
+        let Ok(miner) = OpenClMiner::new(0) else { return };
+        let miner = miner.with_hash_algo(HashAlgo::DoubleSha256);
+
+        let challenge = [3u8; 32];
+        let miner_pubkey = [4u8; 32];
+        let target = u128::MAX / 2_000;
+
+        let nonce = miner.mine(&challenge, &miner_pubkey, 7, target, 10_000_000)
+            .expect("Should find a nonce");
+        assert!(pow::verify_nonce(&challenge, &miner_pubkey, nonce, 7, target, HashAlgo::DoubleSha256));
+    }
+
+    #[test]
+    fn test_early_exit_saves_time_at_low_difficulty() {
+        // Pas de device OpenCL: rien à mesurer
+        let Ok(miner) = OpenClMiner::new(0) else { return };
+        let without = OpenClMiner { early_exit: false, ..OpenClMiner::new(0).unwrap() };
+
+        // Difficulté 2: presque chaque nonce est valide, le batch entier est du travail perdu
+        // une fois la première solution trouvée
+        let target = u128::MAX / 2;
+        let nonces = WORK_SIZE as u64 * NONCES_PER_THREAD as u64;
+        let time_to_solution = |miner: &OpenClMiner| {
+            let start = std::time::Instant::now();
+            miner.mine(&[5u8; 32], &[6u8; 32], 0, target, nonces as u128).expect("Should find a nonce");
+            start.elapsed()
+        };
+
+        // Premier passage hors mesure (compilation paresseuse du driver, caches)
+        time_to_solution(&miner);
+        time_to_solution(&without);
+        let (early, full) = (time_to_solution(&miner), time_to_solution(&without));
+        assert!(early < full, "early exit {:?}, full batch {:?}", early, full);
+    }
+
+    #[test]
+    fn test_mine_after_mid_loop_error() {
+        // Pas de device OpenCL: rien à tester
+        let Ok(miner) = OpenClMiner::new(0) else { return };
+        let miner_pubkey = [9u8; 32];
+        let target = u128::MAX / 1_000;
+
+        // Le kernel trouve un nonce puis le batch échoue avant la lecture des sorties
+        miner.fail_next_batch.store(true, Ordering::Relaxed);
+        let control = MiningControl::new();
+        assert_eq!(miner.mine_with_control(&[1u8; 32], &miner_pubkey, 0, target, 0..u64::MAX as u128, &control), None);
+        assert!(control.failure().is_some());
+
+        // Le found et le nonce laissés par l'appel précédent ne concernent pas ce challenge
+        let challenge = [2u8; 32];
+        let control = MiningControl::new();
+        let nonce = miner.mine_with_control(&challenge, &miner_pubkey, 0, target, 0..u64::MAX as u128, &control)
+            .expect("Should find a nonce");
+        assert_eq!(control.failure(), None);
+        assert!(pow::verify_nonce(&challenge, &miner_pubkey, nonce, 0, target, HashAlgo::Sha256));
+    }
+
+    #[test]
+    fn test_second_construction_reuses_program_cache() {
+        // Pas de device OpenCL: rien à compiler
+        let Ok(_first) = OpenClMiner::new(0) else { return };
+        let second = OpenClMiner::new(0).unwrap();
+        assert!(second.from_cache);
+
+        let challenge = [7u8; 32];
+        let target = u128::MAX / 1_000;
+        let nonce = second.mine(&challenge, &[8u8; 32], 3, target, 10_000_000).expect("Should find a nonce");
+        assert!(pow::verify_nonce(&challenge, &[8u8; 32], nonce, 3, target, HashAlgo::Sha256));
+    }
+
+    #[test]
+    fn test_rejected_cached_binary_recompiles() {
+        // Pas de device OpenCL: rien à compiler
+        let Ok((_, device)) = list_devices().and_then(|devices| select_device("OpenCL", devices, 0)) else { return };
+        program_cache().lock().unwrap().insert(program_key(&device), b"not a binary".to_vec());
+
+        // Les autres tests peuvent remplir le cache en parallèle: seul le repli compte ici
+        OpenClMiner::new(0).expect("Should recompile from source");
+        assert_ne!(program_cache().lock().unwrap()[&program_key(&device)], b"not a binary");
+    }
+
+    #[test]
+    fn test_reduced_grid_stays_in_bounds() {
+        // Pas de device OpenCL: rien à lancer
+        let Ok(miner) = OpenClMiner::new(0) else { return };
+        assert!(OpenClMiner::new(0).unwrap().with_work_items(0).is_err());
+        assert!(OpenClMiner::new(0).unwrap().with_work_items(WORK_SIZE + 1).is_err());
+
+        // Grille non multiple de la taille de work-group: le runtime peut l'arrondir
+        let miner = miner.with_work_items(1000).unwrap();
+        let control = MiningControl::new();
+        let nonces = 1000 * NONCES_PER_THREAD as u128 * 3 + 7;
+        assert_eq!(miner.mine_with_control(&[1u8; 32], &[2u8; 32], 0, 0, 0..nonces, &control), None);
+        assert_eq!(control.failure(), None);
+        assert_eq!(control.hashes(), nonces as u64);
+
+        let buffers = miner.buffers.lock().unwrap();
+        buffers.check_guards().expect("Kernel wrote out of range");
+        assert_eq!(buffers.tested().unwrap(), 7);
+        drop(buffers);
+
+        let challenge = [3u8; 32];
+        let target = u128::MAX / 1_000;
+        let nonce = miner.mine(&challenge, &[2u8; 32], 0, target, 10_000_000).expect("Should find a nonce");
+        assert!(pow::verify_nonce(&challenge, &[2u8; 32], nonce, 0, target, HashAlgo::Sha256));
+        miner.buffers.lock().unwrap().check_guards().expect("Kernel wrote out of range");
     }
 }
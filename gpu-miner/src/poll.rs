@@ -0,0 +1,65 @@
+//! Délai entre deux lectures de l'état on-chain quand il n'y a rien à miner
+//!
+//! Des mineurs qui relisent le même RPC au même intervalle fixe finissent synchronisés:
+//! un jitter aléatoire étale leurs appels.
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+/// `interval` ± `jitter`, tiré à chaque attente
+pub struct PollDelay {
+    interval: Duration,
+    jitter: Duration,
+    /// État du générateur xorshift64 (jamais nul)
+    state: u64,
+}
+
+impl PollDelay {
+    pub fn new(interval: Duration, jitter: Duration) -> Self {
+        // Graine propre au processus: deux mineurs lancés ensemble tirent des délais différents
+        let seed = RandomState::new().build_hasher().finish();
+        Self { interval, jitter, state: seed | 1 }
+    }
+
+    fn next_random(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    /// Prochain délai, uniforme dans [interval - jitter, interval + jitter] (borné à 0)
+    pub fn next_delay(&mut self) -> Duration {
+        let jitter = self.jitter.as_millis() as u64;
+        if jitter == 0 {
+            return self.interval;
+        }
+        let offset = Duration::from_millis(self.next_random() % (2 * jitter + 1));
+        (self.interval + offset).saturating_sub(self.jitter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delays_vary_within_jitter_bound() {
+        let interval = Duration::from_secs(5);
+        let jitter = Duration::from_secs(1);
+        let mut poll = PollDelay::new(interval, jitter);
+
+        let delays: Vec<_> = (0..100).map(|_| poll.next_delay()).collect();
+        assert!(delays.iter().all(|d| *d >= interval - jitter && *d <= interval + jitter));
+        assert!(delays.windows(2).any(|pair| pair[0] != pair[1]));
+
+        // Sans jitter: intervalle fixe
+        let mut fixed = PollDelay::new(interval, Duration::ZERO);
+        assert!((0..10).all(|_| fixed.next_delay() == interval));
+
+        // Jitter plus large que l'intervalle: jamais de délai négatif
+        let mut wide = PollDelay::new(Duration::from_millis(100), Duration::from_secs(1));
+        assert!((0..100).all(|_| wide.next_delay() <= Duration::from_millis(1100)));
+    }
+}
@@ -1,25 +1,384 @@
 //! Logique Proof of Work (CPU)
 
+use anyhow::{anyhow, Result};
 use sha2::{Sha256, Digest};
 
+/// Fonction de hash appliquée au préimage
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HashAlgo {
+    /// SHA256(préimage) - format du protocole
+    #[default]
+    Sha256,
+    /// SHA256(SHA256(préimage)), style Bitcoin
+    DoubleSha256,
+}
+
+impl HashAlgo {
+    /// Applique les passes supplémentaires au premier digest SHA256 du préimage
+    #[inline(always)]
+    pub fn finish(self, first_digest: [u8; 32]) -> [u8; 32] {
+        match self {
+            HashAlgo::Sha256 => first_digest,
+            HashAlgo::DoubleSha256 => Sha256::digest(first_digest).into(),
+        }
+    }
+
+    /// Hash complet du préimage avec l'implémentation SHA256 `sha`, sans midstate
+    /// (référence des tests de `hash_prefixed`)
+    #[cfg(test)]
+    pub fn hash_with(self, sha: ShaImpl, message: &[u8]) -> [u8; 32] {
+        let first = sha.digest(message);
+        match self {
+            HashAlgo::Sha256 => first,
+            HashAlgo::DoubleSha256 => sha.digest(&first),
+        }
+    }
+
+    /// Hash complet du préimage dont le préfixe est déjà absorbé par `prefix`
+    #[inline(always)]
+    pub fn hash_prefixed(self, sha: ShaImpl, prefix: &PrefixHasher, suffix: &[u8]) -> [u8; 32] {
+        let first = prefix.digest_suffix(suffix);
+        match self {
+            HashAlgo::Sha256 => first,
+            HashAlgo::DoubleSha256 => sha.digest(&first),
+        }
+    }
+}
+
+/// Implémentation SHA256 du hasher CPU (`--sha-impl`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum ShaImpl {
+    /// Crate sha2: extensions SHA du CPU si présentes (détectées à l'exécution), sinon Rust portable
+    #[default]
+    Sha2,
+    /// Assembleur de ring (BoringSSL)
+    #[cfg(feature = "sha-asm")]
+    Asm,
+}
+
+impl ShaImpl {
+    pub fn name(self) -> &'static str {
+        match self {
+            ShaImpl::Sha2 => "sha2",
+            #[cfg(feature = "sha-asm")]
+            ShaImpl::Asm => "asm",
+        }
+    }
+
+    /// Code effectivement exécuté sur ce CPU: le crate sha2 choisit au premier hash
+    pub fn backend(self) -> &'static str {
+        match self {
+            ShaImpl::Sha2 => sha2_backend(),
+            #[cfg(feature = "sha-asm")]
+            ShaImpl::Asm => "ring",
+        }
+    }
+
+    /// Implémentations compilées
+    pub fn available() -> Vec<ShaImpl> {
+        <ShaImpl as clap::ValueEnum>::value_variants().to_vec()
+    }
+
+    #[inline(always)]
+    pub fn digest(self, data: &[u8]) -> [u8; 32] {
+        match self {
+            ShaImpl::Sha2 => Sha256::digest(data).into(),
+            #[cfg(feature = "sha-asm")]
+            ShaImpl::Asm => ring::digest::digest(&ring::digest::SHA256, data).as_ref().try_into().unwrap(),
+        }
+    }
+}
+
+/// État SHA256 après absorption d'un préfixe constant (midstate)
+///
+/// challenge || miner_pubkey fait exactement un bloc SHA256 de 64 bytes: compressé une
+/// seule fois par batch, chaque nonce ne coûte plus que le bloc de la fin du préimage.
+#[derive(Clone)]
+pub enum PrefixHasher {
+    Sha2(Sha256),
+    #[cfg(feature = "sha-asm")]
+    Ring(ring::digest::Context),
+}
+
+impl PrefixHasher {
+    pub fn new(sha: ShaImpl, prefix: &[u8]) -> Self {
+        match sha {
+            ShaImpl::Sha2 => PrefixHasher::Sha2(Sha256::new_with_prefix(prefix)),
+            #[cfg(feature = "sha-asm")]
+            ShaImpl::Asm => {
+                let mut context = ring::digest::Context::new(&ring::digest::SHA256);
+                context.update(prefix);
+                PrefixHasher::Ring(context)
+            }
+        }
+    }
+
+    /// SHA256(préfixe || suffix), sans toucher au midstate
+    #[inline(always)]
+    pub fn digest_suffix(&self, suffix: &[u8]) -> [u8; 32] {
+        match self {
+            PrefixHasher::Sha2(hasher) => hasher.clone().chain_update(suffix).finalize().into(),
+            #[cfg(feature = "sha-asm")]
+            PrefixHasher::Ring(context) => {
+                let mut context = context.clone();
+                context.update(suffix);
+                context.finish().as_ref().try_into().unwrap()
+            }
+        }
+    }
+}
+
+/// Backend retenu par le crate sha2 0.10, même détection que lui (cpufeatures)
+fn sha2_backend() -> &'static str {
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    {
+        let sha_ni = std::is_x86_feature_detected!("sha")
+            && std::is_x86_feature_detected!("sse2")
+            && std::is_x86_feature_detected!("ssse3")
+            && std::is_x86_feature_detected!("sse4.1");
+        if sha_ni { "SHA-NI" } else { "portable" }
+    }
+    #[cfg(target_arch = "aarch64")]
+    {
+        if std::arch::is_aarch64_feature_detected!("sha2") { "ARMv8 SHA2" } else { "portable" }
+    }
+    #[cfg(not(any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64")))]
+    {
+        "portable"
+    }
+}
+
+/// Challenge effectif du préimage, à partir de la valeur lue on-chain
+///
+/// Le programme déployé stocke le challenge brut (`Raw`, défaut). `Hashed` couvre les
+/// programmes qui stockent une graine et dérivent SHA256(graine || block_number LE).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum ChallengeDerivation {
+    #[default]
+    Raw,
+    Hashed,
+}
+
+impl ChallengeDerivation {
+    pub fn derive(self, challenge: &[u8; 32], block_number: u64) -> [u8; 32] {
+        match self {
+            ChallengeDerivation::Raw => *challenge,
+            ChallengeDerivation::Hashed => {
+                let mut hasher = Sha256::new();
+                hasher.update(challenge);
+                hasher.update(block_number.to_le_bytes());
+                hasher.finalize().into()
+            }
+        }
+    }
+}
+
+/// Largeur du nonce dans le préimage et dans l'instruction submit_proof (u128 little-endian)
+pub const NONCE_BYTES: usize = 16;
+
+/// challenge (32) + miner_pubkey (32) + nonce (16) + block_number (8)
+pub const PREIMAGE_LEN: usize = 32 + 32 + NONCE_BYTES + 8;
+
+/// Préimage hashé par le programme: challenge || miner_pubkey || nonce || block_number
+pub fn preimage(challenge: &[u8; 32], miner_pubkey: &[u8; 32], nonce: u128, block_number: u64) -> [u8; PREIMAGE_LEN] {
+    let mut message = [0u8; PREIMAGE_LEN];
+    message[..32].copy_from_slice(challenge);               // 32 bytes - Challenge actuel
+    message[32..64].copy_from_slice(miner_pubkey);          // 32 bytes - Adresse du mineur
+    message[64..64 + NONCE_BYTES].copy_from_slice(&nonce.to_le_bytes()); // 16 bytes - Nonce (u128)
+    message[64 + NONCE_BYTES..].copy_from_slice(&block_number.to_le_bytes()); // 8 bytes - Numéro de bloc
+    message
+}
+
 /// Calcule le hash PoW: SHA256(challenge || miner_pubkey || nonce || block_number)
 ///
 /// L'inclusion de miner_pubkey garantit que chaque mineur a son propre espace de recherche
-/// et empêche le vol de travail dans les pools.
-pub fn compute_hash(challenge: &[u8; 32], miner_pubkey: &[u8; 32], nonce: u128, block_number: u64) -> [u8; 32] {
-    let mut hasher = Sha256::new();
-    hasher.update(challenge);              // 32 bytes - Challenge actuel
-    hasher.update(miner_pubkey);           // 32 bytes - Adresse du mineur
-    hasher.update(&nonce.to_le_bytes());   // 16 bytes - Nonce du miner (u128)
-    hasher.update(&block_number.to_le_bytes()); // 8 bytes  - Numéro de bloc
-    hasher.finalize().into()
+/// et empêche le vol de travail dans les pools. Avec `HashAlgo::DoubleSha256`, le digest
+/// est re-hashé une seconde fois.
+pub fn compute_hash(challenge: &[u8; 32], miner_pubkey: &[u8; 32], nonce: u128, block_number: u64, algo: HashAlgo) -> [u8; 32] {
+    algo.finish(Sha256::digest(preimage(challenge, miner_pubkey, nonce, block_number)).into())
 }
 
 /// Vérifie si un nonce est valide
-pub fn verify_nonce(challenge: &[u8; 32], miner_pubkey: &[u8; 32], nonce: u128, block_number: u64, target: u128) -> bool {
-    let hash = compute_hash(challenge, miner_pubkey, nonce, block_number);
-    let hash_value = u128::from_le_bytes(hash[..16].try_into().unwrap());
-    hash_value < target
+pub fn verify_nonce(challenge: &[u8; 32], miner_pubkey: &[u8; 32], nonce: u128, block_number: u64, target: u128, algo: HashAlgo) -> bool {
+    let hash = compute_hash(challenge, miner_pubkey, nonce, block_number, algo);
+    is_valid_hash(&hash, target)
+}
+
+/// Valeur du hash comparée au target
+///
+/// Le digest est dans l'ordre standard de SHA256 (mots de l'état écrits en big-endian);
+/// ses 16 premiers bytes sont lus comme un u128 little-endian, comme le programme
+/// on-chain. Les kernels GPU doivent écrire le digest dans ce même ordre.
+#[inline(always)]
+pub fn hash_value(hash: &[u8; 32]) -> u128 {
+    u128::from_le_bytes(hash[..16].try_into().unwrap())
+}
+
+/// Vérifie si un hash est inférieur au target (voir `hash_value`)
+#[inline(always)]
+pub fn is_valid_hash(hash: &[u8; 32], target: u128) -> bool {
+    hash_value(hash) < target
+}
+
+/// Comparaison du hash au target
+///
+/// `Low128` (défaut) reproduit le programme déployé: seuls les 16 premiers bytes du digest
+/// (`hash_value`) sont comparés, les 128 bits restants sont ignorés. `Full256` lit le digest
+/// entier comme un entier 256 bits little-endian (bytes 16..32 de poids fort) et le compare
+/// au target 256 bits `target << 128`: pour les variantes du programme qui ne tronquent pas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum CompareMode {
+    #[default]
+    #[value(name = "low-128")]
+    Low128,
+    #[value(name = "full-256")]
+    Full256,
+}
+
+impl CompareMode {
+    pub fn name(self) -> &'static str {
+        match self {
+            CompareMode::Low128 => "low-128",
+            CompareMode::Full256 => "full-256",
+        }
+    }
+
+    pub fn is_valid(self, hash: &[u8; 32], target: u128) -> bool {
+        self.checker(target)(hash)
+    }
+
+    /// Test de validité pour `target`, le target 256 bits calculé une seule fois (boucles de mining)
+    pub fn checker(self, target: u128) -> impl Fn(&[u8; 32]) -> bool {
+        let target_256 = target_256(target);
+        move |hash| match self {
+            CompareMode::Low128 => is_valid_hash(hash, target),
+            CompareMode::Full256 => is_valid_hash_256(hash, &target_256),
+        }
+    }
+
+    /// `verify_nonce` selon ce mode de comparaison
+    pub fn verify_nonce(self, challenge: &[u8; 32], miner_pubkey: &[u8; 32], nonce: u128, block_number: u64, target: u128, algo: HashAlgo) -> bool {
+        self.is_valid(&compute_hash(challenge, miner_pubkey, nonce, block_number, algo), target)
+    }
+}
+
+/// Target 256 bits (big-endian) d'un target u128 en mode `Full256`: `target << 128`
+pub fn target_256(target: u128) -> [u8; 32] {
+    let mut full = [0u8; 32];
+    full[..16].copy_from_slice(&target.to_be_bytes());
+    full
+}
+
+/// Digest entier (little-endian sur 256 bits) strictement inférieur à `target` (big-endian)
+#[inline(always)]
+pub fn is_valid_hash_256(hash: &[u8; 32], target: &[u8; 32]) -> bool {
+    // Le byte de poids fort du digest est le dernier
+    hash.iter().rev().lt(target.iter())
+}
+
+/// Target correspondant à une difficulté: u128::MAX / difficulty
+///
+/// Une difficulté de 0 est saturée à 1 (tout hash est valide).
+pub fn difficulty_to_target(difficulty: u128) -> u128 {
+    u128::MAX / difficulty.max(1)
+}
+
+/// Difficulté correspondant à un target (inverse de `difficulty_to_target`)
+///
+/// Exact pour les difficultés jusqu'à u64::MAX; un target de 0 (aucun hash valide)
+/// sature à u128::MAX.
+pub fn target_to_difficulty(target: u128) -> u128 {
+    u128::MAX / target.max(1)
+}
+
+/// Hashes à calculer pour trouver une solution avec une probabilité `p`
+///
+/// Loi géométrique de paramètre 1/difficulty: n = ln(1 - p) / ln(1 - 1/difficulty),
+/// arrondi au-dessus. p ≤ 0 donne 0, p ≥ 1 sature à u128::MAX (aucun nombre fini de
+/// hashes ne garantit une solution), une difficulté ≤ 1 donne 1 hash.
+pub fn hashes_for_confidence(difficulty: u128, p: f64) -> u128 {
+    if p.is_nan() || p <= 0.0 {
+        return 0;
+    }
+    if p >= 1.0 {
+        return u128::MAX;
+    }
+    if difficulty <= 1 {
+        return 1;
+    }
+
+    // ln_1p reste précis quand 1/difficulty ou p sont minuscules
+    let n = (-p).ln_1p() / (-1.0 / difficulty as f64).ln_1p();
+    // L'arrondi flottant ne doit pas faire passer 2.0000000000000004 à 3
+    (n * (1.0 - 4.0 * f64::EPSILON)).ceil().max(1.0) as u128
+}
+
+/// Target équivalent sur 256 bits, (2^256 - 1) / difficulty, en big-endian
+///
+/// Forme usuelle quand on compare le hash complet plutôt que ses 16 premiers bytes.
+pub fn difficulty_to_target_256(difficulty: u128) -> [u8; 32] {
+    let divisor = difficulty.max(1);
+    let mut quotient = [0u8; 32];
+    let mut remainder = 0u128;
+
+    // Division bit à bit de 2^256 - 1 (tous les bits à 1) par un u128
+    for bit in 0..256 {
+        let overflow = remainder >> 127 == 1;
+        let shifted = (remainder << 1) | 1;
+        if overflow || shifted >= divisor {
+            remainder = shifted.wrapping_sub(divisor);
+            quotient[bit / 8] |= 0x80 >> (bit % 8);
+        } else {
+            remainder = shifted;
+        }
+    }
+
+    quotient
+}
+
+/// Parse une valeur de 32 bytes en hex (challenge, pubkey), avec ou sans préfixe `0x`
+///
+/// La longueur est vérifiée exactement: pas de troncature ni de padding silencieux.
+pub fn parse_hash32(s: &str) -> Result<[u8; 32]> {
+    let bytes = hex::decode(s.trim().trim_start_matches("0x"))
+        .map_err(|e| anyhow!("invalid hex: {}", e))?;
+
+    <[u8; 32]>::try_from(bytes.as_slice())
+        .map_err(|_| anyhow!("expected 32 bytes (64 hex chars), got {} bytes", bytes.len()))
+}
+
+/// Parse un target explicite en hex (big-endian), avec ou sans préfixe `0x`
+///
+/// 16 bytes: le target u128 comparé à `hash_value`. 32 bytes: target sur 256 bits (forme de
+/// `difficulty_to_target_256`), dont seuls les 16 bytes de poids fort sont comparés.
+pub fn parse_target(s: &str) -> Result<u128> {
+    let bytes = hex::decode(s.trim().trim_start_matches("0x"))
+        .map_err(|e| anyhow!("invalid hex: {}", e))?;
+
+    match bytes.len() {
+        16 | 32 => Ok(u128::from_be_bytes(bytes[..16].try_into().unwrap())),
+        n => Err(anyhow!("expected 16 or 32 bytes (32 or 64 hex chars), got {} bytes", n)),
+    }
+}
+
+/// Vecteur de conformité: préimage connu → digest connu → validité connue
+///
+/// Tout backend (CPU, CUDA, OpenCL) doit trouver `NONCE` sur `NONCE..NONCE + 1` avec
+/// `VALID_TARGET`, et rien avec `INVALID_TARGET` (égal à la valeur du hash: la
+/// comparaison est stricte).
+#[cfg(test)]
+pub mod vectors {
+    pub const CHALLENGE: [u8; 32] = [0x11; 32];
+    pub const MINER_PUBKEY: [u8; 32] = [0x22; 32];
+    pub const NONCE: u128 = 686;
+    pub const BLOCK_NUMBER: u64 = 7;
+    /// SHA256(CHALLENGE || MINER_PUBKEY || NONCE LE || BLOCK_NUMBER LE)
+    pub const DIGEST_HEX: &str = "17d51ee50ad9d27f879a87d3aada3c00515f36eaa636fb6eb09a04dcf27a14cc";
+    /// 16 premiers bytes du digest lus en little-endian
+    pub const HASH_VALUE: u128 = 0x003c_daaa_d387_9a87_7fd2_d90a_e51e_d517;
+    /// Target de la difficulté 1000
+    pub const VALID_TARGET: u128 = u128::MAX / 1_000;
+    pub const INVALID_TARGET: u128 = HASH_VALUE;
 }
 
 #[cfg(test)]
@@ -32,22 +391,55 @@ mod tests {
         let miner_pubkey = [1u8; 32];
         let nonce = 12345;
         let block_number = 100;
-        let hash = compute_hash(&challenge, &miner_pubkey, nonce, block_number);
+        let hash = compute_hash(&challenge, &miner_pubkey, nonce, block_number, HashAlgo::Sha256);
 
         // Le hash devrait être déterministe
-        let hash2 = compute_hash(&challenge, &miner_pubkey, nonce, block_number);
+        let hash2 = compute_hash(&challenge, &miner_pubkey, nonce, block_number, HashAlgo::Sha256);
         assert_eq!(hash, hash2);
 
         // Le hash devrait changer si block_number change
-        let hash3 = compute_hash(&challenge, &miner_pubkey, nonce, block_number + 1);
+        let hash3 = compute_hash(&challenge, &miner_pubkey, nonce, block_number + 1, HashAlgo::Sha256);
         assert_ne!(hash, hash3);
 
         // Le hash devrait changer si miner_pubkey change
         let other_miner = [2u8; 32];
-        let hash4 = compute_hash(&challenge, &other_miner, nonce, block_number);
+        let hash4 = compute_hash(&challenge, &other_miner, nonce, block_number, HashAlgo::Sha256);
         assert_ne!(hash, hash4);
     }
 
+    #[test]
+    fn test_preimage_layout() {
+        // Même largeur que le nonce de l'instruction submit_proof (u128) et du miner TS
+        assert_eq!(NONCE_BYTES, std::mem::size_of::<u128>());
+        assert_eq!(PREIMAGE_LEN, 88);
+
+        let nonce = (1u128 << 64) | 0x0102;
+        let message = preimage(&[1; 32], &[2; 32], nonce, 0x0a0b);
+        assert_eq!(message[..32], [1; 32]);
+        assert_eq!(message[32..64], [2; 32]);
+        assert_eq!(message[64..80], [2, 1, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(message[80..], [0x0b, 0x0a, 0, 0, 0, 0, 0, 0]);
+
+        let digest: [u8; 32] = Sha256::digest(message).into();
+        assert_eq!(compute_hash(&[1; 32], &[2; 32], nonce, 0x0a0b, HashAlgo::Sha256), digest);
+    }
+
+    #[test]
+    fn test_sha_impls_agree() {
+        let available = ShaImpl::available();
+        assert!(available.contains(&ShaImpl::Sha2));
+
+        let preimage = preimage(&[3; 32], &[4; 32], u128::MAX - 1, 9);
+        let inputs: [&[u8]; 4] = [b"", b"abc", &[0x5a; 64], &preimage];
+        for sha in available {
+            for input in inputs {
+                let expected: [u8; 32] = Sha256::digest(input).into();
+                assert_eq!(sha.digest(input), expected, "{} on {} bytes", sha.name(), input.len());
+            }
+            assert_eq!(HashAlgo::DoubleSha256.hash_with(sha, &preimage), compute_hash(&[3; 32], &[4; 32], u128::MAX - 1, 9, HashAlgo::DoubleSha256));
+        }
+    }
+
     #[test]
     fn test_verify_nonce() {
         let challenge = [0u8; 32];
@@ -57,7 +449,7 @@ mod tests {
 
         // Trouver un nonce valide
         for nonce in 0..10_000 {
-            if verify_nonce(&challenge, &miner_pubkey, nonce, block_number, target) {
+            if verify_nonce(&challenge, &miner_pubkey, nonce, block_number, target, HashAlgo::Sha256) {
                 println!("✓ Found valid nonce: {} for block {}", nonce, block_number);
                 return;
             }
@@ -65,4 +457,168 @@ mod tests {
 
         panic!("No valid nonce found in 10k attempts");
     }
+
+    #[test]
+    fn test_single_vs_double_sha_vectors() {
+        let challenge = [0u8; 32];
+        let miner_pubkey = [1u8; 32];
+
+        let single = compute_hash(&challenge, &miner_pubkey, 12345, 100, HashAlgo::Sha256);
+        let double = compute_hash(&challenge, &miner_pubkey, 12345, 100, HashAlgo::DoubleSha256);
+
+        assert_eq!(hex::encode(single), "ef717b1edcbd559a138c30cd06b0f8fbc43a0bfb4326b940f4b8f7c5af5956cb");
+        assert_eq!(hex::encode(double), "23efc1add39abc2806463d12dc4117bf2c99c76636d06cef315fd97cd6edaceb");
+
+        // Le double hash est le SHA256 du premier digest
+        assert_eq!(double, <[u8; 32]>::from(Sha256::digest(single)));
+    }
+
+    #[test]
+    fn test_challenge_derivations() {
+        let seed = [0x11u8; 32];
+        assert_eq!(ChallengeDerivation::Raw.derive(&seed, 7), seed);
+
+        // SHA256(seed || 7u64 LE)
+        let derived = ChallengeDerivation::Hashed.derive(&seed, 7);
+        assert_eq!(hex::encode(derived), "29e85a53065531daf7c7b032181ef6551f0e331ae43ae35c3bf609e87aff5878");
+        assert_ne!(ChallengeDerivation::Hashed.derive(&seed, 8), derived);
+    }
+
+    #[test]
+    fn test_hashes_for_confidence() {
+        // ln(0.05) / ln(0.999) = 2994.23
+        assert_eq!(hashes_for_confidence(1_000, 0.95), 2_995);
+        // ln(0.5) / ln(1 - 1e-6) = 693146.83
+        assert_eq!(hashes_for_confidence(1_000_000, 0.5), 693_147);
+        // 1 - (3/4)^2 = 0.4375: exactement 2 hashes
+        assert_eq!(hashes_for_confidence(4, 0.4375), 2);
+        assert_eq!(hashes_for_confidence(2, 0.75), 2);
+
+        assert_eq!(hashes_for_confidence(1_000, 0.0), 0);
+        assert_eq!(hashes_for_confidence(1_000, 1.0), u128::MAX);
+        assert_eq!(hashes_for_confidence(1, 0.99), 1);
+        assert_eq!(hashes_for_confidence(0, 0.5), 1);
+        assert_eq!(hashes_for_confidence(1_000, 1e-12), 1);
+    }
+
+    #[test]
+    fn test_parse_hash32_lengths() {
+        let hex32 = "ab".repeat(32);
+        assert_eq!(parse_hash32(&hex32).unwrap(), [0xab; 32]);
+        assert_eq!(parse_hash32(&format!("0x{}", hex32)).unwrap(), [0xab; 32]);
+
+        let err = parse_hash32(&"ab".repeat(31)).unwrap_err();
+        assert_eq!(err.to_string(), "expected 32 bytes (64 hex chars), got 31 bytes");
+
+        let err = parse_hash32(&"ab".repeat(33)).unwrap_err();
+        assert_eq!(err.to_string(), "expected 32 bytes (64 hex chars), got 33 bytes");
+
+        assert!(parse_hash32("zz").unwrap_err().to_string().starts_with("invalid hex"));
+    }
+
+    #[test]
+    fn test_prefix_hasher_matches_full_preimage() {
+        for algo in [HashAlgo::Sha256, HashAlgo::DoubleSha256] {
+            for sha in ShaImpl::available() {
+                let prefix = PrefixHasher::new(sha, &preimage(&[0x11; 32], &[0x22; 32], 0, 0)[..64]);
+                for nonce in [0, 1, 686, u64::MAX as u128 + 1, u128::MAX] {
+                    let message = preimage(&[0x11; 32], &[0x22; 32], nonce, 7);
+                    assert_eq!(algo.hash_prefixed(sha, &prefix, &message[64..]), algo.hash_with(sha, &message), "{:?}/{:?} nonce {}", algo, sha, nonce);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_target_widths() {
+        assert_eq!(parse_target("000010c6f7a0b5ed8d36b4c7f3493858").unwrap(), difficulty_to_target(1_000_000));
+        assert_eq!(parse_target("0x00000000000000000000000000000001").unwrap(), 1);
+
+        // Target 256 bits: ses 16 bytes de poids fort valent le target 128 bits
+        for difficulty in [1, 2, 1_000, 1_000_000, u64::MAX as u128] {
+            let target_256 = hex::encode(difficulty_to_target_256(difficulty));
+            assert_eq!(parse_target(&target_256).unwrap(), difficulty_to_target(difficulty));
+        }
+
+        let err = parse_target(&"ab".repeat(20)).unwrap_err();
+        assert_eq!(err.to_string(), "expected 16 or 32 bytes (32 or 64 hex chars), got 20 bytes");
+    }
+
+    #[test]
+    fn test_difficulty_target_round_trip() {
+        for difficulty in [1u128, 2, 3, 1_000, 1_000_000, 123_456_789, 1 << 40, u64::MAX as u128] {
+            let target = difficulty_to_target(difficulty);
+            assert_eq!(target_to_difficulty(target), difficulty, "difficulty {}", difficulty);
+        }
+
+        // Cas saturés
+        assert_eq!(difficulty_to_target(0), u128::MAX);
+        assert_eq!(target_to_difficulty(u128::MAX), 1);
+        assert_eq!(target_to_difficulty(0), u128::MAX);
+    }
+
+    #[test]
+    fn test_difficulty_to_target_256() {
+        assert_eq!(difficulty_to_target_256(1), [0xff; 32]);
+        assert_eq!(hex::encode(difficulty_to_target_256(2)), format!("7f{}", "ff".repeat(31)));
+        assert_eq!(hex::encode(difficulty_to_target_256(1 << 64)), format!("{}{}", "00".repeat(8), "ff".repeat(24)));
+    }
+
+    #[test]
+    fn test_compare_modes_disagree_on_crafted_hashes() {
+        let target = difficulty_to_target(1_000);
+
+        // 128 bits bas nuls, 128 bits hauts au maximum: accepté par la troncature seulement
+        let mut high_heavy = [0u8; 32];
+        high_heavy[16..].fill(0xff);
+        assert!(CompareMode::Low128.is_valid(&high_heavy, target));
+        assert!(!CompareMode::Full256.is_valid(&high_heavy, target));
+
+        // L'inverse: bits bas au maximum, bits hauts nuls
+        let mut low_heavy = [0u8; 32];
+        low_heavy[..16].fill(0xff);
+        assert!(!CompareMode::Low128.is_valid(&low_heavy, target));
+        assert!(CompareMode::Full256.is_valid(&low_heavy, target));
+
+        // Mêmes 128 bits bas, bits hauts différents: indiscernables en Low128 seulement
+        let mut a = [0u8; 32];
+        a[16..].copy_from_slice(&(target - 1).to_le_bytes());
+        let mut b = a;
+        b[16..].copy_from_slice(&target.to_le_bytes());
+        assert_eq!(CompareMode::Low128.is_valid(&a, target), CompareMode::Low128.is_valid(&b, target));
+        assert!(CompareMode::Full256.is_valid(&a, target));
+        assert!(!CompareMode::Full256.is_valid(&b, target));
+    }
+
+    #[test]
+    fn test_full_256_comparison_is_numeric() {
+        // Comparaison stricte, octet de poids fort (le dernier du digest) en premier
+        let target = [0x00, 0x01, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        let mut hash = [0u8; 32];
+        hash[30] = 0x01;
+        assert!(!is_valid_hash_256(&hash, &target));
+        hash[30] = 0x00;
+        hash[0] = 0xff;
+        assert!(is_valid_hash_256(&hash, &target));
+
+        assert_eq!(target_256(0x1234), {
+            let mut expected = [0u8; 32];
+            expected[14..16].copy_from_slice(&[0x12, 0x34]);
+            expected
+        });
+    }
+
+    #[test]
+    fn test_conformance_vector() {
+        use vectors::*;
+
+        let digest = compute_hash(&CHALLENGE, &MINER_PUBKEY, NONCE, BLOCK_NUMBER, HashAlgo::Sha256);
+        assert_eq!(hex::encode(digest), DIGEST_HEX);
+        assert_eq!(hash_value(&digest), HASH_VALUE);
+        assert!(is_valid_hash(&digest, VALID_TARGET));
+        assert!(!is_valid_hash(&digest, INVALID_TARGET));
+
+        // Lu en big-endian, le même digest serait rejeté: l'ordre des bytes compte
+        assert!(u128::from_be_bytes(digest[..16].try_into().unwrap()) >= VALID_TARGET);
+    }
 }
@@ -0,0 +1,83 @@
+//! Consommation électrique du GPU, pour rapporter l'efficacité en MH/J
+
+use anyhow::Result;
+
+/// Source de la puissance consommée par le GPU
+pub enum PowerSource {
+    /// Puissance fixe fournie par l'utilisateur (--gpu-watts)
+    Fixed(f64),
+    /// Puissance lue via NVML (GPU NVIDIA)
+    #[cfg(feature = "nvml")]
+    Nvml {
+        nvml: Box<nvml_wrapper::Nvml>,
+        device: u32,
+    },
+}
+
+impl PowerSource {
+    /// Sélectionne la source: --gpu-watts en priorité, sinon NVML si compilé et disponible
+    pub fn detect(gpu_watts: Option<f64>, device: usize) -> Option<Self> {
+        if let Some(watts) = gpu_watts {
+            return Some(Self::Fixed(watts));
+        }
+
+        #[cfg(feature = "nvml")]
+        {
+            match Self::nvml(device) {
+                Ok(source) => return Some(source),
+                Err(e) => log::debug!("NVML unavailable: {}", e),
+            }
+        }
+
+        let _ = device;
+        None
+    }
+
+    #[cfg(feature = "nvml")]
+    fn nvml(device: usize) -> Result<Self> {
+        let nvml = nvml_wrapper::Nvml::init()?;
+        let device = device as u32;
+        // Vérifie que le device existe et expose sa consommation
+        nvml.device_by_index(device)?.power_usage()?;
+        Ok(Self::Nvml { nvml: Box::new(nvml), device })
+    }
+
+    /// Puissance actuelle en watts
+    pub fn watts(&self) -> Result<f64> {
+        match self {
+            Self::Fixed(watts) => Ok(*watts),
+            #[cfg(feature = "nvml")]
+            Self::Nvml { nvml, device } => {
+                let milliwatts = nvml.device_by_index(*device)?.power_usage()?;
+                Ok(milliwatts as f64 / 1000.0)
+            }
+        }
+    }
+}
+
+/// Efficacité en MH/J (= MH/s par watt), None si la puissance n'est pas exploitable
+pub fn efficiency_mh_per_joule(hashrate_hs: f64, watts: f64) -> Option<f64> {
+    if watts > 0.0 && watts.is_finite() {
+        Some(hashrate_hs / 1_000_000.0 / watts)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_efficiency_from_hashrate_and_watts() {
+        // 500 MH/s à 250 W => 2 MH/J
+        assert_eq!(efficiency_mh_per_joule(500_000_000.0, 250.0), Some(2.0));
+        assert_eq!(efficiency_mh_per_joule(500_000_000.0, 0.0), None);
+    }
+
+    #[test]
+    fn test_fixed_source_takes_priority() {
+        let source = PowerSource::detect(Some(180.0), 0).unwrap();
+        assert_eq!(source.watts().unwrap(), 180.0);
+    }
+}
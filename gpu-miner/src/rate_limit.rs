@@ -0,0 +1,96 @@
+//! Limiteur de débit (token bucket) pour respecter les quotas des fournisseurs RPC
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Priorité d'un appel face au limiteur
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Priority {
+    /// Lecture d'état, peut attendre
+    Poll,
+    /// Soumission d'une preuve: passe immédiatement, quitte à endetter le bucket
+    Submit,
+}
+
+/// Token bucket: `rate` jetons par seconde, jusqu'à une seconde de rafale
+///
+/// Une soumission n'attend jamais: elle consomme son jeton même à découvert, et ce
+/// sont les lectures suivantes qui attendent le remboursement.
+pub struct RateLimiter {
+    rate: f64,
+    capacity: f64,
+    state: Mutex<Bucket>,
+}
+
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(per_second: f64) -> Self {
+        let rate = per_second.max(f64::MIN_POSITIVE);
+        let capacity = rate.max(1.0);
+        Self {
+            rate,
+            capacity,
+            state: Mutex::new(Bucket { tokens: capacity, last_refill: Instant::now() }),
+        }
+    }
+
+    /// Bloque jusqu'à obtenir un jeton (immédiat pour `Priority::Submit`)
+    pub fn acquire(&self, priority: Priority) {
+        loop {
+            let wait = {
+                let mut bucket = self.state.lock().unwrap();
+                let now = Instant::now();
+                let refill = now.duration_since(bucket.last_refill).as_secs_f64() * self.rate;
+                bucket.tokens = (bucket.tokens + refill).min(self.capacity);
+                bucket.last_refill = now;
+
+                if bucket.tokens >= 1.0 || priority == Priority::Submit {
+                    bucket.tokens -= 1.0;
+                    return;
+                }
+                Duration::from_secs_f64((1.0 - bucket.tokens) / self.rate)
+            };
+            std::thread::sleep(wait);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limiter_caps_call_frequency() {
+        let limiter = RateLimiter::new(100.0);
+        let start = Instant::now();
+
+        // 100 jetons de rafale, puis 50 appels à 100/s
+        for _ in 0..150 {
+            limiter.acquire(Priority::Poll);
+        }
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(450), "not throttled: {:?}", elapsed);
+        assert!(elapsed < Duration::from_secs(2), "throttled too much: {:?}", elapsed);
+    }
+
+    #[test]
+    fn test_submissions_skip_the_queue() {
+        let limiter = RateLimiter::new(10.0);
+        for _ in 0..10 {
+            limiter.acquire(Priority::Poll);
+        }
+
+        // Bucket vide: la soumission passe tout de suite...
+        let start = Instant::now();
+        limiter.acquire(Priority::Submit);
+        assert!(start.elapsed() < Duration::from_millis(50));
+
+        // ...et la lecture suivante rembourse la dette (2 jetons à 10/s)
+        limiter.acquire(Priority::Poll);
+        assert!(start.elapsed() >= Duration::from_millis(150));
+    }
+}
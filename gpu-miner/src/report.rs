@@ -0,0 +1,713 @@
+//! Sortie des résultats de mining (texte, JSON ou CSV), partagée par le benchmark et le mineur
+
+use crate::logging;
+use crate::miner::{MinerBackend, MiningControl};
+use crate::pow::{self, CompareMode, HashAlgo};
+use crate::power::{self, PowerSource};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::io::{self, Write};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Format de sortie des résultats
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Texte lisible (format parsé par continuous-gpu-miner.ts)
+    Text,
+    /// Un objet JSON par ligne
+    Json,
+    /// Une ligne CSV par mesure, précédée d'un en-tête
+    Csv,
+}
+
+/// Backend mesuré et son matériel, repris dans chaque résultat
+#[derive(Debug, Clone)]
+pub struct BackendInfo {
+    pub name: String,
+    pub device: String,
+}
+
+impl BackendInfo {
+    pub fn of(miner: &dyn MinerBackend) -> Self {
+        Self { name: miner.name().to_string(), device: miner.device_name() }
+    }
+}
+
+/// Nonce valide trouvé par un backend
+#[derive(Debug, Clone, Serialize)]
+pub struct SolutionReport {
+    pub backend: String,
+    pub device: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub difficulty_label: Option<String>,
+    pub difficulty: u128,
+    pub block_number: u64,
+    pub nonce: u128,
+    /// Hashes réellement calculés (tous threads ou batchs GPU), sans rapport avec le nonce
+    /// quand la recherche est parallèle ou part d'un nonce aléatoire
+    pub iterations: u128,
+    pub elapsed_ms: f64,
+    pub hashrate_hs: f64,
+    pub hash: String,
+    pub valid: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub power_watts: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub efficiency_mh_j: Option<f64>,
+}
+
+/// Mesure de hashrate (sans solution)
+#[derive(Debug, Clone, Serialize)]
+pub struct HashrateReport {
+    pub backend: String,
+    pub device: String,
+    pub hashes: u64,
+    pub elapsed_ms: f64,
+    pub hashrate_hs: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub power_watts: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub efficiency_mh_j: Option<f64>,
+}
+
+/// Hashrate du CPU miner à un nombre de threads donné (`--thread-scaling`)
+#[derive(Debug, Clone, Serialize)]
+pub struct ScalingPoint {
+    pub threads: usize,
+    pub hashes: u64,
+    pub elapsed_ms: f64,
+    pub hashrate_hs: f64,
+    pub per_thread_hs: f64,
+    /// Hashrate par thread rapporté à celui du premier point (1.0: passage à l'échelle linéaire)
+    pub efficiency: f64,
+}
+
+/// Points de la table de scaling, à partir des mesures (threads, hashes, durée) dans l'ordre
+pub fn scaling_points(measures: &[(usize, u64, Duration)]) -> Vec<ScalingPoint> {
+    let per_thread = |&(threads, hashes, elapsed): &(usize, u64, Duration)| hashes as f64 / elapsed.as_secs_f64() / threads.max(1) as f64;
+    let baseline = measures.first().map(per_thread);
+
+    measures
+        .iter()
+        .map(|measure| {
+            let (threads, hashes, elapsed) = *measure;
+            let per_thread_hs = per_thread(measure);
+            ScalingPoint {
+                threads,
+                hashes,
+                elapsed_ms: elapsed.as_secs_f64() * 1000.0,
+                hashrate_hs: hashes as f64 / elapsed.as_secs_f64(),
+                per_thread_hs,
+                efficiency: baseline.map_or(1.0, |base| per_thread_hs / base),
+            }
+        })
+        .collect()
+}
+
+/// Destination des résultats de mining
+pub trait Reporter: Send + Sync {
+    fn on_solution(&self, solution: &SolutionReport);
+    fn on_hashrate(&self, hashrate: &HashrateReport);
+    fn on_error(&self, backend: &str, message: &str);
+    /// Table de scaling du CPU miner sur `device`
+    fn on_scaling(&self, device: &str, points: &[ScalingPoint]);
+}
+
+/// Crée le reporter correspondant au format demandé
+pub fn reporter(format: OutputFormat) -> Box<dyn Reporter> {
+    match format {
+        OutputFormat::Text => Box::new(TextReporter),
+        OutputFormat::Json => Box::new(JsonReporter),
+        OutputFormat::Csv => Box::new(CsvReporter::new(io::stdout())),
+    }
+}
+
+// ============================================================================
+// TEXTE
+// ============================================================================
+
+pub struct TextReporter;
+
+impl TextReporter {
+    /// Bloc affiché pour une solution, backend en tête
+    fn solution_text(s: &SolutionReport) -> String {
+        let mut lines = vec![match &s.difficulty_label {
+            Some(label) => format!("✓ [{}] {} (diff: {}) - Nonce found: {}", s.backend, label, s.difficulty, s.nonce),
+            None => format!("✓ [{}] Nonce found: {}", s.backend, s.nonce),
+        }];
+        lines.push(format!("  Device: {}", s.device));
+        lines.push(format!("  Time: {:?}", Duration::from_secs_f64(s.elapsed_ms / 1000.0)));
+        lines.push(format!("  Iterations: {}", s.iterations));
+        lines.push(format!("  Hashrate: {:.2} MH/s", s.hashrate_hs / 1_000_000.0));
+        if let (Some(watts), Some(efficiency)) = (s.power_watts, s.efficiency_mh_j) {
+            lines.push(format!("  Efficiency: {:.2} MH/J ({:.0} W)", efficiency, watts));
+        }
+        lines.push(format!("  Hash: {}", s.hash));
+        lines.push(format!("  Valid: {}", s.valid));
+        lines.join("\n")
+    }
+
+    /// Ligne de hashrate: backend, puis le device entre parenthèses
+    fn hashrate_text(h: &HashrateReport) -> String {
+        let mut text = format!(
+            "  {} ({}): {} hashes in {:?} ({:.2} MH/s)",
+            h.backend,
+            h.device,
+            h.hashes,
+            Duration::from_secs_f64(h.elapsed_ms / 1000.0),
+            h.hashrate_hs / 1_000_000.0
+        );
+        if let (Some(watts), Some(efficiency)) = (h.power_watts, h.efficiency_mh_j) {
+            text.push_str(&format!("\n  Efficiency: {:.2} MH/J ({:.0} W)", efficiency, watts));
+        }
+        text
+    }
+}
+
+impl Reporter for TextReporter {
+    fn on_solution(&self, s: &SolutionReport) {
+        println!("{}", logging::decorate(&Self::solution_text(s)));
+    }
+
+    fn on_hashrate(&self, h: &HashrateReport) {
+        println!("{}", Self::hashrate_text(h));
+    }
+
+    fn on_error(&self, backend: &str, message: &str) {
+        println!("{}", logging::decorate(&format!("✗ {}: {}", backend, message)));
+    }
+
+    fn on_scaling(&self, device: &str, points: &[ScalingPoint]) {
+        println!("CPU miner scaling on {}", device);
+        println!("{:>8} {:>12} {:>14} {:>11}", "Threads", "MH/s", "MH/s/thread", "Efficiency");
+        for p in points {
+            println!(
+                "{:>8} {:>12.2} {:>14.2} {:>10.0}%",
+                p.threads,
+                p.hashrate_hs / 1_000_000.0,
+                p.per_thread_hs / 1_000_000.0,
+                p.efficiency * 100.0
+            );
+        }
+    }
+}
+
+// ============================================================================
+// JSON
+// ============================================================================
+
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+enum Event<'a> {
+    Solution(&'a SolutionReport),
+    Hashrate(&'a HashrateReport),
+    Error { backend: &'a str, message: &'a str },
+    Scaling { device: &'a str, points: &'a [ScalingPoint] },
+}
+
+pub struct JsonReporter;
+
+impl JsonReporter {
+    fn emit(event: &Event) {
+        println!("{}", serde_json::to_string(event).expect("report events always serialize"));
+    }
+}
+
+impl Reporter for JsonReporter {
+    fn on_solution(&self, solution: &SolutionReport) {
+        Self::emit(&Event::Solution(solution));
+    }
+
+    fn on_hashrate(&self, hashrate: &HashrateReport) {
+        Self::emit(&Event::Hashrate(hashrate));
+    }
+
+    fn on_error(&self, backend: &str, message: &str) {
+        Self::emit(&Event::Error { backend, message });
+    }
+
+    fn on_scaling(&self, device: &str, points: &[ScalingPoint]) {
+        Self::emit(&Event::Scaling { device, points });
+    }
+}
+
+/// Ignore tous les résultats (mode soak: seul le résumé final est écrit)
+pub struct NullReporter;
+
+impl Reporter for NullReporter {
+    fn on_solution(&self, _solution: &SolutionReport) {}
+
+    fn on_hashrate(&self, _hashrate: &HashrateReport) {}
+
+    fn on_error(&self, _backend: &str, _message: &str) {}
+
+    fn on_scaling(&self, _device: &str, _points: &[ScalingPoint]) {}
+}
+
+// ============================================================================
+// CSV
+// ============================================================================
+
+/// Ligne CSV: colonnes communes aux solutions et aux mesures de hashrate
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CsvRow {
+    pub backend: String,
+    pub device: String,
+    pub difficulty_label: Option<String>,
+    pub difficulty: Option<u128>,
+    pub nonce: Option<u128>,
+    pub elapsed_ms: f64,
+    pub hashrate_hs: f64,
+}
+
+impl From<&SolutionReport> for CsvRow {
+    fn from(s: &SolutionReport) -> Self {
+        Self {
+            backend: s.backend.clone(),
+            device: s.device.clone(),
+            difficulty_label: s.difficulty_label.clone(),
+            difficulty: Some(s.difficulty),
+            nonce: Some(s.nonce),
+            elapsed_ms: s.elapsed_ms,
+            hashrate_hs: s.hashrate_hs,
+        }
+    }
+}
+
+impl From<&HashrateReport> for CsvRow {
+    fn from(h: &HashrateReport) -> Self {
+        Self {
+            backend: h.backend.clone(),
+            device: h.device.clone(),
+            difficulty_label: None,
+            difficulty: None,
+            nonce: None,
+            elapsed_ms: h.elapsed_ms,
+            hashrate_hs: h.hashrate_hs,
+        }
+    }
+}
+
+/// Écrit une ligne par mesure; les erreurs passent par les logs pour garder un CSV valide
+pub struct CsvReporter<W: Write> {
+    writer: Mutex<csv::Writer<W>>,
+}
+
+impl<W: Write> CsvReporter<W> {
+    pub fn new(output: W) -> Self {
+        Self { writer: Mutex::new(csv::Writer::from_writer(output)) }
+    }
+
+    fn emit(&self, row: CsvRow) {
+        let mut writer = self.writer.lock().unwrap();
+        // Flush à chaque ligne: le benchmark peut être interrompu à tout moment
+        if let Err(e) = writer.serialize(row).and_then(|()| writer.flush().map_err(Into::into)) {
+            log::warn!("Failed to write CSV row: {}", e);
+        }
+    }
+}
+
+impl<W: Write + Send> Reporter for CsvReporter<W> {
+    fn on_solution(&self, solution: &SolutionReport) {
+        self.emit(solution.into());
+    }
+
+    fn on_hashrate(&self, hashrate: &HashrateReport) {
+        self.emit(hashrate.into());
+    }
+
+    fn on_error(&self, backend: &str, message: &str) {
+        log::warn!("{}: {}", backend, message);
+    }
+
+    /// Une ligne par nombre de threads
+    fn on_scaling(&self, device: &str, points: &[ScalingPoint]) {
+        for p in points {
+            self.emit(CsvRow {
+                backend: format!("CPU miner ({} threads)", p.threads),
+                device: device.to_string(),
+                difficulty_label: None,
+                difficulty: None,
+                nonce: None,
+                elapsed_ms: p.elapsed_ms,
+                hashrate_hs: p.hashrate_hs,
+            });
+        }
+    }
+}
+
+// ============================================================================
+// BENCHMARK
+// ============================================================================
+
+/// Paramètres d'un mining de benchmark
+#[derive(Clone)]
+pub struct BenchJob {
+    pub label: Option<String>,
+    pub challenge: [u8; 32],
+    pub miner_pubkey: [u8; 32],
+    pub block_number: u64,
+    pub difficulty: u128,
+    /// Target explicite (`--target`), prioritaire sur celui dérivé de la difficulté
+    pub target: Option<u128>,
+    pub hash_algo: HashAlgo,
+    pub compare_mode: CompareMode,
+}
+
+impl BenchJob {
+    pub fn target(&self) -> u128 {
+        self.target.unwrap_or_else(|| pow::difficulty_to_target(self.difficulty))
+    }
+
+    /// Vrai si `nonce` résout ce job (hash, target et mode de comparaison du job)
+    pub fn verify(&self, nonce: u128) -> bool {
+        self.compare_mode.verify_nonce(&self.challenge, &self.miner_pubkey, nonce, self.block_number, self.target(), self.hash_algo)
+    }
+}
+
+/// Rapporte le hashrate « à froid » mesuré pendant le warm-up, premiers lancements compris
+pub fn report_warmup(reporter: &dyn Reporter, backend: &BackendInfo, hashes: u64, elapsed: Duration) {
+    reporter.on_hashrate(&HashrateReport {
+        backend: format!("{} (cold)", backend.name),
+        device: backend.device.clone(),
+        hashes,
+        elapsed_ms: elapsed.as_secs_f64() * 1000.0,
+        hashrate_hs: hashes as f64 / elapsed.as_secs_f64(),
+        power_watts: None,
+        efficiency_mh_j: None,
+    });
+}
+
+/// Rapporte le hashrate d'une mesure à durée fixe (`--benchmark --time`)
+pub fn report_timed(reporter: &dyn Reporter, backend: &BackendInfo, hashes: u64, elapsed: Duration, power: Option<&PowerSource>) {
+    let hashrate_hs = hashes as f64 / elapsed.as_secs_f64();
+    let (power_watts, efficiency_mh_j) = sample_efficiency(power, hashrate_hs);
+
+    reporter.on_hashrate(&HashrateReport {
+        backend: backend.name.clone(),
+        device: backend.device.clone(),
+        hashes,
+        elapsed_ms: elapsed.as_secs_f64() * 1000.0,
+        hashrate_hs,
+        power_watts,
+        efficiency_mh_j,
+    });
+}
+
+/// Lit la puissance et calcule l'efficacité; (None, None) sans source exploitable
+fn sample_efficiency(power: Option<&PowerSource>, hashrate_hs: f64) -> (Option<f64>, Option<f64>) {
+    let Some(source) = power else {
+        return (None, None);
+    };
+
+    match source.watts() {
+        Ok(watts) => match power::efficiency_mh_per_joule(hashrate_hs, watts) {
+            Some(efficiency) => (Some(watts), Some(efficiency)),
+            None => (None, None),
+        },
+        Err(e) => {
+            log::warn!("Failed to read GPU power: {}", e);
+            (None, None)
+        }
+    }
+}
+
+/// Convertit le résultat d'un mining en événements pour `reporter`
+///
+/// Une solution déclenche `on_solution`; sinon le hashrate mesuré est rapporté
+/// si le mining a été interrompu, suivi de `on_error`. La puissance est lue
+/// juste après le mining, tant que le GPU est encore en charge.
+pub fn report_result(
+    reporter: &dyn Reporter,
+    backend: &BackendInfo,
+    job: &BenchJob,
+    nonce: Option<u128>,
+    elapsed: Duration,
+    control: &MiningControl,
+    power: Option<&PowerSource>,
+) {
+    let elapsed_ms = elapsed.as_secs_f64() * 1000.0;
+
+    // Backend en échec (erreur CUDA...): pas de hashrate, l'erreur elle-même
+    if let (None, Some(failure)) = (nonce, control.failure()) {
+        reporter.on_error(&backend.name, &failure);
+        return;
+    }
+
+    match nonce {
+        Some(nonce) => {
+            let hash = pow::compute_hash(&job.challenge, &job.miner_pubkey, nonce, job.block_number, job.hash_algo);
+            let hash_value = pow::hash_value(&hash);
+            let hashes_tried = control.hashes();
+            let hashrate_hs = hashes_tried as f64 / elapsed.as_secs_f64();
+            let (power_watts, efficiency_mh_j) = sample_efficiency(power, hashrate_hs);
+
+            reporter.on_solution(&SolutionReport {
+                backend: backend.name.clone(),
+                device: backend.device.clone(),
+                difficulty_label: job.label.clone(),
+                difficulty: job.difficulty,
+                block_number: job.block_number,
+                nonce,
+                iterations: hashes_tried.into(),
+                elapsed_ms,
+                hashrate_hs,
+                hash: format!("{:032x}", hash_value),
+                valid: job.verify(nonce),
+                power_watts,
+                efficiency_mh_j,
+            });
+        }
+        None if !control.is_running() => {
+            let hashrate_hs = control.hashes() as f64 / elapsed.as_secs_f64();
+            let (power_watts, efficiency_mh_j) = sample_efficiency(power, hashrate_hs);
+
+            reporter.on_hashrate(&HashrateReport {
+                backend: backend.name.clone(),
+                device: backend.device.clone(),
+                hashes: control.hashes(),
+                elapsed_ms,
+                hashrate_hs,
+                power_watts,
+                efficiency_mh_j,
+            });
+            let reason = if control.budget_exhausted() {
+                "Hash budget exhausted before finding a nonce"
+            } else {
+                "Interrupted before finding a nonce"
+            };
+            reporter.on_error(&backend.name, reason);
+        }
+        None => {
+            reporter.on_error(&backend.name, "No nonce found (reached limit)");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Enregistre les callbacks reçus
+    #[derive(Default)]
+    struct CapturingReporter {
+        events: Mutex<Vec<String>>,
+    }
+
+    impl Reporter for CapturingReporter {
+        fn on_solution(&self, solution: &SolutionReport) {
+            self.events.lock().unwrap().push(format!("solution:{}:{}", solution.nonce, solution.valid));
+        }
+
+        fn on_hashrate(&self, hashrate: &HashrateReport) {
+            self.events.lock().unwrap().push(format!("hashrate:{}", hashrate.hashes));
+        }
+
+        fn on_error(&self, backend: &str, _message: &str) {
+            self.events.lock().unwrap().push(format!("error:{}", backend));
+        }
+
+        fn on_scaling(&self, _device: &str, points: &[ScalingPoint]) {
+            self.events.lock().unwrap().push(format!("scaling:{}", points.len()));
+        }
+    }
+
+    fn backend(name: &str) -> BackendInfo {
+        BackendInfo { name: name.to_string(), device: "Test CPU".to_string() }
+    }
+
+    fn job() -> BenchJob {
+        BenchJob {
+            label: None,
+            challenge: [0u8; 32],
+            miner_pubkey: [1u8; 32],
+            block_number: 100,
+            difficulty: 1_000,
+            target: None,
+            hash_algo: HashAlgo::Sha256,
+            compare_mode: CompareMode::Low128,
+        }
+    }
+
+    #[test]
+    fn test_solution_fires_on_solution() {
+        let job = job();
+        let nonce = (0..10_000u128)
+            .find(|&n| pow::verify_nonce(&job.challenge, &job.miner_pubkey, n, job.block_number, job.target(), job.hash_algo))
+            .unwrap();
+
+        let reporter = CapturingReporter::default();
+        report_result(&reporter, &backend("CPU"), &job, Some(nonce), Duration::from_millis(10), &MiningControl::new(), None);
+
+        assert_eq!(*reporter.events.lock().unwrap(), vec![format!("solution:{}:true", nonce)]);
+    }
+
+    #[test]
+    fn test_hashrate_counts_hashes_tried() {
+        let job = job();
+        let control = MiningControl::new();
+        let miner = crate::miner::CpuMiner::new(1);
+        let nonce = miner.mine_with_control(&job.challenge, &job.miner_pubkey, job.block_number, job.target(), 0..100_000, &control).unwrap();
+        // Un seul thread parti de 0: nonce + 1 hashes
+        assert_eq!(control.hashes(), nonce as u64 + 1);
+
+        let reporter = TextCapture::default();
+        report_result(&reporter, &backend("CPU"), &job, Some(nonce), Duration::from_secs(1), &control, None);
+        let text = reporter.text.lock().unwrap();
+        assert!(text[0].contains(&format!("Iterations: {}\n", nonce + 1)), "{}", text[0]);
+        assert!(text[0].contains(&format!("Hashrate: {:.2} MH/s", (nonce + 1) as f64 / 1_000_000.0)), "{}", text[0]);
+
+        // Nonce élevé trouvé vite (départ aléatoire, threads parallèles): le hashrate suit les hashes
+        let control = MiningControl::new();
+        control.add_hashes(1_000);
+        let reporter = TextCapture::default();
+        report_result(&reporter, &backend("CPU"), &job, Some(nonce + (1 << 40)), Duration::from_secs(1), &control, None);
+        assert!(reporter.text.lock().unwrap()[0].contains("Iterations: 1000\n"));
+    }
+
+    #[test]
+    fn test_interrupted_fires_hashrate_then_error() {
+        let control = MiningControl::new();
+        control.add_hashes(5_000);
+        control.stop();
+
+        let reporter = CapturingReporter::default();
+        report_result(&reporter, &backend("CPU"), &job(), None, Duration::from_millis(10), &control, None);
+
+        assert_eq!(*reporter.events.lock().unwrap(), vec!["hashrate:5000", "error:CPU"]);
+    }
+
+    #[test]
+    fn test_exhausted_fires_error_only() {
+        let reporter = CapturingReporter::default();
+        report_result(&reporter, &backend("CUDA"), &job(), None, Duration::from_millis(10), &MiningControl::new(), None);
+
+        assert_eq!(*reporter.events.lock().unwrap(), vec!["error:CUDA"]);
+    }
+
+    #[test]
+    fn test_json_event_shape() {
+        let report = HashrateReport {
+            backend: "CPU".to_string(),
+            device: "Test CPU".to_string(),
+            hashes: 10,
+            elapsed_ms: 1.0,
+            hashrate_hs: 10_000.0,
+            power_watts: None,
+            efficiency_mh_j: None,
+        };
+        let json: serde_json::Value = serde_json::to_value(Event::Hashrate(&report)).unwrap();
+
+        assert_eq!(json["event"], "hashrate");
+        assert_eq!(json["backend"], "CPU");
+        assert_eq!(json["device"], "Test CPU");
+        assert_eq!(json["hashes"], 10);
+        assert!(json.get("efficiency_mh_j").is_none());
+    }
+
+    #[test]
+    fn test_scaling_points_relative_to_one_thread() {
+        let second = Duration::from_secs(1);
+        let points = scaling_points(&[(1, 1_000_000, second), (2, 2_000_000, second), (4, 2_000_000, second)]);
+
+        let efficiency: Vec<f64> = points.iter().map(|p| p.efficiency).collect();
+        assert_eq!(efficiency, [1.0, 1.0, 0.5]);
+        assert_eq!(points[2].hashrate_hs, 2_000_000.0);
+        assert_eq!(points[2].per_thread_hs, 500_000.0);
+
+        let json = serde_json::to_value(Event::Scaling { device: "Test CPU", points: &points }).unwrap();
+        assert_eq!(json["event"], "scaling");
+        assert_eq!(json["points"][2]["threads"], 4);
+    }
+
+    #[test]
+    fn test_csv_rows_parse_back() {
+        let job = BenchJob { label: Some("Facile".to_string()), ..job() };
+        let nonce = (0..10_000u128)
+            .find(|&n| pow::verify_nonce(&job.challenge, &job.miner_pubkey, n, job.block_number, job.target(), job.hash_algo))
+            .unwrap();
+
+        let reporter = CsvReporter::new(Vec::new());
+        report_result(&reporter, &backend("CPU"), &job, Some(nonce), Duration::from_millis(10), &MiningControl::new(), None);
+        report_warmup(&reporter, &backend("CPU"), 5_000, Duration::from_millis(10));
+
+        let output = reporter.writer.into_inner().unwrap().into_inner().unwrap();
+        let mut reader = csv::Reader::from_reader(output.as_slice());
+        assert_eq!(
+            reader.headers().unwrap(),
+            vec!["backend", "device", "difficulty_label", "difficulty", "nonce", "elapsed_ms", "hashrate_hs"]
+        );
+
+        let rows: Vec<CsvRow> = reader.deserialize().map(Result::unwrap).collect();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].difficulty_label.as_deref(), Some("Facile"));
+        assert_eq!(rows[0].difficulty, Some(1_000));
+        assert_eq!(rows[0].nonce, Some(nonce));
+        assert_eq!(rows[1].backend, "CPU (cold)");
+        assert_eq!(rows[1].device, "Test CPU");
+        assert_eq!(rows[1].nonce, None);
+        assert_eq!(rows[1].hashrate_hs, 500_000.0);
+    }
+
+    #[test]
+    fn test_cpu_results_name_the_device() {
+        let cpu = BackendInfo::of(&crate::miner::CpuMiner::new(1));
+        assert_eq!(cpu.name, "CPU");
+        assert!(!cpu.device.is_empty() && cpu.device != "unknown", "{:?}", cpu.device);
+
+        let reporter = CsvReporter::new(Vec::new());
+        report_warmup(&reporter, &cpu, 5_000, Duration::from_millis(10));
+        let output = reporter.writer.into_inner().unwrap().into_inner().unwrap();
+        let rows: Vec<CsvRow> = csv::Reader::from_reader(output.as_slice()).deserialize().map(Result::unwrap).collect();
+        assert_eq!(rows[0].device, cpu.device);
+    }
+
+    /// Texte que `TextReporter` afficherait
+    #[derive(Default)]
+    struct TextCapture {
+        text: Mutex<Vec<String>>,
+    }
+
+    impl Reporter for TextCapture {
+        fn on_solution(&self, solution: &SolutionReport) {
+            self.text.lock().unwrap().push(TextReporter::solution_text(solution));
+        }
+
+        fn on_hashrate(&self, hashrate: &HashrateReport) {
+            self.text.lock().unwrap().push(TextReporter::hashrate_text(hashrate));
+        }
+
+        fn on_error(&self, _backend: &str, _message: &str) {}
+
+        fn on_scaling(&self, _device: &str, _points: &[ScalingPoint]) {}
+    }
+
+    #[test]
+    fn test_text_output_names_the_backend() {
+        let job = job();
+        let nonce = (0..10_000u128)
+            .find(|&n| pow::verify_nonce(&job.challenge, &job.miner_pubkey, n, job.block_number, job.target(), job.hash_algo))
+            .unwrap();
+
+        // Repli CUDA → CPU: la sortie doit dire quel backend a réellement tourné
+        let reporter = TextCapture::default();
+        report_result(&reporter, &backend("CPU"), &job, Some(nonce), Duration::from_millis(10), &MiningControl::new(), None);
+        report_timed(&reporter, &backend("CPU"), 5_000, Duration::from_millis(10), None);
+
+        let text = reporter.text.lock().unwrap();
+        assert!(text[0].starts_with(&format!("✓ [CPU] Nonce found: {}", nonce)), "{}", text[0]);
+        assert!(text[0].contains("Device: Test CPU"));
+        assert!(text[1].starts_with("  CPU (Test CPU): 5000 hashes"), "{}", text[1]);
+    }
+
+    #[test]
+    fn test_efficiency_sampled_from_power_source() {
+        let watts = PowerSource::Fixed(100.0);
+        let (power_watts, efficiency) = sample_efficiency(Some(&watts), 200_000_000.0);
+        assert_eq!(power_watts, Some(100.0));
+        assert_eq!(efficiency, Some(2.0));
+
+        assert_eq!(sample_efficiency(None, 200_000_000.0), (None, None));
+    }
+}
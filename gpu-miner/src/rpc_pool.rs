@@ -0,0 +1,199 @@
+//! Pool d'endpoints RPC avec bascule automatique
+
+use anchor_client::solana_client::client_error::{ClientError, ClientErrorKind};
+use anchor_client::solana_client::rpc_client::RpcClient;
+use anchor_client::solana_sdk::commitment_config::CommitmentConfig;
+use crate::rate_limit::{Priority, RateLimiter};
+use anyhow::{anyhow, Result};
+use log::warn;
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Erreurs consécutives d'un endpoint avant de basculer sur le suivant
+const MAX_CONSECUTIVE_ERRORS: u32 = 3;
+
+/// Endpoints RPC utilisés un par un: après `MAX_CONSECUTIVE_ERRORS` erreurs
+/// d'affilée, l'endpoint actif passe au suivant (en boucle)
+///
+/// Le `RpcClient` est bloquant: chaque appel s'exécute sur le pool de threads
+/// bloquants de tokio, sans immobiliser les workers du runtime.
+pub struct RpcPool {
+    endpoints: Vec<(String, Arc<RpcClient>)>,
+    active: AtomicUsize,
+    consecutive_errors: AtomicU32,
+    /// Limite d'appels par seconde (`--rpc-rate-limit`)
+    limiter: Option<Arc<RateLimiter>>,
+}
+
+impl RpcPool {
+    pub fn new(urls: &[String], commitment: CommitmentConfig) -> Result<Self> {
+        let endpoints = urls
+            .iter()
+            .map(|url| (url.clone(), RpcClient::new_with_commitment(url.clone(), commitment)))
+            .collect();
+
+        Self::from_clients(endpoints)
+    }
+
+    pub(crate) fn from_clients(endpoints: Vec<(String, RpcClient)>) -> Result<Self> {
+        if endpoints.is_empty() {
+            return Err(anyhow!("No RPC URL configured"));
+        }
+
+        Ok(Self {
+            endpoints: endpoints.into_iter().map(|(url, client)| (url, Arc::new(client))).collect(),
+            active: AtomicUsize::new(0),
+            consecutive_errors: AtomicU32::new(0),
+            limiter: None,
+        })
+    }
+
+    /// Limite les appels à `per_second` (tous endpoints confondus)
+    pub fn with_rate_limit(mut self, per_second: f64) -> Self {
+        self.limiter = Some(Arc::new(RateLimiter::new(per_second)));
+        self
+    }
+
+    /// URL de l'endpoint actif
+    pub fn active_url(&self) -> &str {
+        &self.endpoints[self.active.load(Ordering::Relaxed)].0
+    }
+
+    /// Exécute `f` sur l'endpoint actif et compte ses erreurs
+    pub async fn call<T, F>(&self, f: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&RpcClient) -> Result<T, ClientError> + Send + 'static,
+    {
+        self.call_with(Priority::Poll, f).await
+    }
+
+    /// Comme `call`, pour l'envoi d'une transaction: jamais retardé par le limiteur
+    pub async fn submit<T, F>(&self, f: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&RpcClient) -> Result<T, ClientError> + Send + 'static,
+    {
+        self.call_with(Priority::Submit, f).await
+    }
+
+    async fn call_with<T, F>(&self, priority: Priority, f: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&RpcClient) -> Result<T, ClientError> + Send + 'static,
+    {
+        let index = self.active.load(Ordering::Relaxed);
+        let (url, client) = &self.endpoints[index];
+        let client = client.clone();
+        let limiter = self.limiter.clone();
+
+        // L'attente du limiteur et l'appel bloquent: hors des workers du runtime
+        let result = tokio::task::spawn_blocking(move || {
+            if let Some(limiter) = limiter {
+                limiter.acquire(priority);
+            }
+            f(&client).map_err(Box::new)
+        })
+        .await?;
+
+        match result {
+            Ok(value) => {
+                self.consecutive_errors.store(0, Ordering::Relaxed);
+                Ok(value)
+            }
+            Err(e) => {
+                if is_endpoint_error(&e) {
+                    self.record_error(index);
+                }
+                Err(anyhow::Error::new(*e).context(format!("RPC call to {} failed", url)))
+            }
+        }
+    }
+
+    fn record_error(&self, index: usize) {
+        let errors = self.consecutive_errors.fetch_add(1, Ordering::Relaxed) + 1;
+        if errors < MAX_CONSECUTIVE_ERRORS || self.endpoints.len() == 1 {
+            return;
+        }
+
+        let next = (index + 1) % self.endpoints.len();
+        // Échoue si un appel concurrent a déjà basculé
+        if self.active.compare_exchange(index, next, Ordering::Relaxed, Ordering::Relaxed).is_ok() {
+            self.consecutive_errors.store(0, Ordering::Relaxed);
+            warn!(
+                "RPC {} failed {} times in a row, switching to {}",
+                self.endpoints[index].0, errors, self.endpoints[next].0
+            );
+        }
+    }
+}
+
+/// Vrai pour une erreur d'appel (`call`) due à l'endpoint: un nouvel essai peut réussir
+pub fn is_transient(e: &anyhow::Error) -> bool {
+    e.downcast_ref::<ClientError>().is_some_and(is_endpoint_error)
+}
+
+/// Vrai si l'erreur vient de l'endpoint (réseau, réponse invalide) et non
+/// d'une transaction rejetée par le programme ou d'un problème de signature
+fn is_endpoint_error(e: &ClientError) -> bool {
+    !matches!(e.kind(), ClientErrorKind::SigningError(_)) && e.kind().get_transaction_error().is_none()
+}
+
+#[cfg(test)]
+#[allow(clippy::result_large_err)]
+mod tests {
+    use super::*;
+    use anchor_client::solana_sdk::pubkey::Pubkey;
+    use std::time::{Duration, Instant};
+
+    #[tokio::test]
+    async fn test_failover_to_second_endpoint() {
+        let pool = RpcPool::from_clients(vec![
+            ("http://primary".to_string(), RpcClient::new_mock("fails".to_string())),
+            ("http://backup".to_string(), RpcClient::new_mock("succeeds".to_string())),
+        ])
+        .unwrap();
+        let pubkey = Pubkey::new_unique();
+
+        for _ in 0..MAX_CONSECUTIVE_ERRORS {
+            assert_eq!(pool.active_url(), "http://primary");
+            assert!(pool.call(move |rpc| rpc.get_balance(&pubkey)).await.is_err());
+        }
+
+        assert_eq!(pool.active_url(), "http://backup");
+        for _ in 0..5 {
+            assert!(pool.call(move |rpc| rpc.get_balance(&pubkey)).await.is_ok());
+        }
+        assert_eq!(pool.active_url(), "http://backup");
+    }
+
+    // Runtime current_thread: un appel bloquant sur le worker arrêterait le timer
+    #[tokio::test]
+    async fn test_slow_call_does_not_block_runtime() {
+        let pool = RpcPool::from_clients(vec![("mock".to_string(), RpcClient::new_mock("succeeds".to_string()))]).unwrap();
+        let pubkey = Pubkey::new_unique();
+
+        let ticker = tokio::spawn(async {
+            let mut ticks = 0;
+            let mut interval = tokio::time::interval(Duration::from_millis(10));
+            let deadline = Instant::now() + Duration::from_millis(200);
+            while Instant::now() < deadline {
+                interval.tick().await;
+                ticks += 1;
+            }
+            ticks
+        });
+
+        let balance = pool
+            .call(move |rpc| {
+                std::thread::sleep(Duration::from_millis(300));
+                rpc.get_balance(&pubkey)
+            })
+            .await;
+        assert!(balance.is_ok());
+
+        // Le timer a continué de tourner pendant l'appel
+        let ticks = ticker.await.unwrap();
+        assert!(ticks >= 10, "timer starved: {} ticks", ticks);
+    }
+}
@@ -0,0 +1,129 @@
+//! Signature des transactions: keypair local ou signataire externe (wallet matériel, service distant)
+
+use anchor_client::solana_sdk::{
+    hash::Hash,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+    transaction::Transaction,
+};
+use anyhow::{anyhow, Result};
+
+/// Signataire des transactions d'un mineur
+///
+/// Son pubkey est le `miner_pubkey` du préimage: le mineur ne voit jamais la clé privée.
+pub trait TxSigner: Send + Sync {
+    fn pubkey(&self) -> Pubkey;
+
+    /// Signe le message sérialisé d'une transaction
+    fn sign_message(&self, message: &[u8]) -> Result<Signature>;
+}
+
+/// Keypair chargé en clair depuis un fichier
+pub struct LocalSigner {
+    keypair: Keypair,
+}
+
+impl LocalSigner {
+    pub fn new(keypair: Keypair) -> Self {
+        Self { keypair }
+    }
+}
+
+impl TxSigner for LocalSigner {
+    fn pubkey(&self) -> Pubkey {
+        self.keypair.pubkey()
+    }
+
+    fn sign_message(&self, message: &[u8]) -> Result<Signature> {
+        Ok(self.keypair.sign_message(message))
+    }
+}
+
+/// Signataire distant, joint en HTTP (protocole pas encore défini)
+#[allow(dead_code)] // Stub: pas encore exposé en CLI
+pub struct RemoteSigner {
+    url: String,
+    pubkey: Pubkey,
+}
+
+#[allow(dead_code)]
+impl RemoteSigner {
+    pub fn new(url: String, pubkey: Pubkey) -> Self {
+        Self { url, pubkey }
+    }
+}
+
+impl TxSigner for RemoteSigner {
+    fn pubkey(&self) -> Pubkey {
+        self.pubkey
+    }
+
+    fn sign_message(&self, _message: &[u8]) -> Result<Signature> {
+        Err(anyhow!("Remote signer {} is not implemented yet", self.url))
+    }
+}
+
+/// Transaction payée et signée par `signer`, seul signataire requis
+pub fn sign_transaction(instructions: &[Instruction], signer: &dyn TxSigner, recent_blockhash: Hash) -> Result<Transaction> {
+    let mut transaction = Transaction::new_with_payer(instructions, Some(&signer.pubkey()));
+    if transaction.message.header.num_required_signatures != 1 {
+        return Err(anyhow!("Transaction requires signers other than {}", signer.pubkey()));
+    }
+
+    transaction.message.recent_blockhash = recent_blockhash;
+    transaction.signatures[0] = signer.sign_message(&transaction.message_data())?;
+    Ok(transaction)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_client::solana_sdk::instruction::AccountMeta;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Signataire externe simulé: signe avec un keypair caché et compte les appels
+    struct MockSigner {
+        keypair: Keypair,
+        calls: AtomicUsize,
+    }
+
+    impl TxSigner for MockSigner {
+        fn pubkey(&self) -> Pubkey {
+            self.keypair.pubkey()
+        }
+
+        fn sign_message(&self, message: &[u8]) -> Result<Signature> {
+            self.calls.fetch_add(1, Ordering::Relaxed);
+            Ok(self.keypair.sign_message(message))
+        }
+    }
+
+    fn instruction(signer: Pubkey) -> Instruction {
+        Instruction {
+            program_id: Pubkey::new_unique(),
+            accounts: vec![AccountMeta::new(signer, true)],
+            data: vec![1, 2, 3],
+        }
+    }
+
+    #[test]
+    fn test_transaction_signed_by_external_signer() {
+        let signer = MockSigner { keypair: Keypair::new(), calls: AtomicUsize::new(0) };
+        let blockhash = Hash::new_unique();
+
+        let transaction = sign_transaction(&[instruction(signer.pubkey())], &signer, blockhash).unwrap();
+
+        assert_eq!(signer.calls.load(Ordering::Relaxed), 1);
+        assert_eq!(transaction.message.account_keys[0], signer.pubkey());
+        assert_eq!(transaction.message.recent_blockhash, blockhash);
+        assert!(transaction.verify().is_ok());
+
+        // Un second signataire requis ne peut pas être satisfait
+        let other = instruction(Pubkey::new_unique());
+        assert!(sign_transaction(&[instruction(signer.pubkey()), other], &signer, blockhash).is_err());
+
+        let remote = RemoteSigner::new("http://signer".to_string(), signer.pubkey());
+        assert!(sign_transaction(&[instruction(signer.pubkey())], &remote, blockhash).is_err());
+    }
+}
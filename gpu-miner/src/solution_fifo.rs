@@ -0,0 +1,201 @@
+//! Solutions transmises à un autre processus (`--solution-fifo`), une ligne JSON par nonce
+//!
+//! Le mineur sert alors de moteur de hash: le consommateur (continuous-gpu-miner.ts) lit les
+//! lignes et soumet lui-même les preuves. La FIFO est créée par lui (`mkfifo`); un fichier
+//! ordinaire reçoit les lignes en ajout.
+
+use crate::report::BenchJob;
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Attente entre deux essais quand aucun lecteur n'a ouvert la FIFO ou qu'elle est pleine
+const WAIT_DELAY: Duration = Duration::from_millis(100);
+
+/// Ligne écrite pour chaque nonce trouvé
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FifoSolution {
+    pub challenge: String,
+    /// Pubkey du mineur inclus dans le préimage: seul ce keypair peut soumettre le nonce
+    pub miner_pubkey: String,
+    /// En chaîne: un u128 dépasse la précision des nombres JSON côté TypeScript
+    pub nonce: String,
+    pub block_number: u64,
+    pub hash: String,
+}
+
+impl FifoSolution {
+    pub fn new(job: &BenchJob, nonce: u128, hash: &[u8; 32]) -> Self {
+        Self {
+            challenge: hex::encode(job.challenge),
+            miner_pubkey: hex::encode(job.miner_pubkey),
+            nonce: nonce.to_string(),
+            block_number: job.block_number,
+            hash: hex::encode(hash),
+        }
+    }
+}
+
+/// Écrivain de la FIFO, ouverte au premier envoi et rouverte si le lecteur se déconnecte
+pub struct SolutionFifo {
+    path: PathBuf,
+    pipe: Mutex<Option<File>>,
+}
+
+impl SolutionFifo {
+    pub fn new(path: &Path) -> Self {
+        Self { path: path.to_path_buf(), pipe: Mutex::new(None) }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Écrit une ligne; attend un lecteur tant que `is_running`, erreur une fois arrêté
+    ///
+    /// La FIFO est ouverte en O_NONBLOCK: sans lecteur, l'ouverture échoue (ENXIO) au lieu
+    /// de bloquer, et l'arrêt du mineur est vérifié entre deux essais.
+    pub fn send(&self, solution: &FifoSolution, is_running: impl Fn() -> bool) -> Result<()> {
+        let mut line = serde_json::to_vec(solution)?;
+        line.push(b'\n');
+
+        let mut pipe = self.pipe.lock().unwrap();
+        // Lecteur parti (EPIPE): une seconde tentative attend le suivant
+        for retry in [true, false] {
+            let file = match pipe.as_mut() {
+                Some(file) => file,
+                None => pipe.insert(self.open(&is_running)?),
+            };
+            match write_line(file, &line, &is_running) {
+                Ok(()) => return Ok(()),
+                Err(e) if retry && e.kind() == ErrorKind::BrokenPipe => *pipe = None,
+                Err(e) => {
+                    *pipe = None;
+                    return Err(e).with_context(|| format!("Failed to write to solution FIFO {}", self.path.display()));
+                }
+            }
+        }
+        unreachable!("the last attempt always returns")
+    }
+
+    fn open(&self, is_running: &impl Fn() -> bool) -> Result<File> {
+        let mut options = OpenOptions::new();
+        options.append(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::custom_flags(&mut options, libc::O_NONBLOCK);
+
+        loop {
+            match options.open(&self.path) {
+                Ok(file) => return Ok(file),
+                Err(e) if is_no_reader(&e) && is_running() => std::thread::sleep(WAIT_DELAY),
+                Err(e) if is_no_reader(&e) => return Err(anyhow!("No reader on solution FIFO {}, stopping", self.path.display())),
+                Err(e) => return Err(e).with_context(|| format!("Failed to open solution FIFO {}", self.path.display())),
+            }
+        }
+    }
+}
+
+/// Écrit `line` d'un bloc: sous PIPE_BUF, une écriture non bloquante est entière ou refusée
+/// (EAGAIN, FIFO pleine), réessayée tant que `is_running`
+fn write_line(file: &mut File, line: &[u8], is_running: &impl Fn() -> bool) -> std::io::Result<()> {
+    loop {
+        match file.write_all(line) {
+            Err(e) if e.kind() == ErrorKind::WouldBlock && is_running() => std::thread::sleep(WAIT_DELAY),
+            result => return result,
+        }
+    }
+}
+
+/// Ouverture en écriture d'une FIFO sans lecteur (ENXIO)
+fn is_no_reader(e: &std::io::Error) -> bool {
+    #[cfg(unix)]
+    return e.raw_os_error() == Some(libc::ENXIO);
+    #[cfg(not(unix))]
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pow::{CompareMode, HashAlgo};
+    use std::io::{BufRead, BufReader};
+
+    #[test]
+    #[cfg(unix)]
+    fn test_solutions_read_back_from_fifo() {
+        let path = std::env::temp_dir().join(format!("pow-miner-fifo-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let created = std::process::Command::new("mkfifo").arg(&path).status();
+        if !created.is_ok_and(|status| status.success()) {
+            return; // mkfifo indisponible
+        }
+
+        let reader = {
+            let path = path.clone();
+            std::thread::spawn(move || {
+                let lines = BufReader::new(File::open(path).unwrap()).lines();
+                lines.map(|line| serde_json::from_str::<FifoSolution>(&line.unwrap()).unwrap()).collect::<Vec<_>>()
+            })
+        };
+
+        let job = BenchJob {
+            label: None,
+            challenge: [3u8; 32],
+            miner_pubkey: [4u8; 32],
+            block_number: 9,
+            difficulty: 1_000,
+            target: None,
+            hash_algo: HashAlgo::Sha256,
+            compare_mode: CompareMode::Low128,
+        };
+        let solutions = [FifoSolution::new(&job, 42, &[5u8; 32]), FifoSolution::new(&job, u128::MAX, &[6u8; 32])];
+        let fifo = SolutionFifo::new(&path);
+        for solution in &solutions {
+            fifo.send(solution, || true).unwrap();
+        }
+        drop(fifo); // Fin de fichier pour le lecteur
+
+        let received = reader.join().unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(received, solutions);
+        assert_eq!(received[1].nonce, u128::MAX.to_string());
+        assert_eq!(received[0].challenge, hex::encode([3u8; 32]));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_stop_without_reader_returns() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use std::sync::Arc;
+        use std::time::Instant;
+
+        let path = std::env::temp_dir().join(format!("pow-miner-fifo-noreader-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let created = std::process::Command::new("mkfifo").arg(&path).status();
+        if !created.is_ok_and(|status| status.success()) {
+            return; // mkfifo indisponible
+        }
+
+        // Aucun lecteur: l'envoi attend jusqu'à l'arrêt, puis échoue
+        let running = Arc::new(AtomicBool::new(true));
+        let stopper = {
+            let running = running.clone();
+            std::thread::spawn(move || {
+                std::thread::sleep(Duration::from_millis(300));
+                running.store(false, Ordering::Relaxed);
+            })
+        };
+        let solution = FifoSolution { challenge: String::new(), miner_pubkey: String::new(), nonce: "1".to_string(), block_number: 0, hash: String::new() };
+        let start = Instant::now();
+        let error = SolutionFifo::new(&path).send(&solution, || running.load(Ordering::Relaxed)).unwrap_err();
+        stopper.join().unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(start.elapsed() < Duration::from_secs(5), "send blocked past the stop: {:?}", start.elapsed());
+        assert!(error.to_string().starts_with("No reader on solution FIFO"), "{:#}", error);
+    }
+}
@@ -0,0 +1,233 @@
+//! Journal d'audit des solutions trouvées (`--solution-log`), une ligne JSON par nonce
+
+use crate::pow::{self, CompareMode, HashAlgo};
+use crate::report::BenchJob;
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Solutions gardées en mémoire pour `/solutions` (--health-port)
+pub const RECENT_SOLUTIONS: usize = 32;
+
+/// Nonce trouvé et issue de sa soumission
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SolutionRecord {
+    /// Timestamp unix (secondes)
+    pub timestamp: i64,
+    pub challenge_hex: String,
+    pub nonce: u128,
+    pub block_number: u64,
+    pub hash_hex: String,
+    pub submitted: bool,
+    pub signature: Option<String>,
+    pub accepted: bool,
+    /// Champs du rejeu (`replay`), absents des journaux plus anciens
+    #[serde(default)]
+    pub miner_pubkey_hex: Option<String>,
+    #[serde(default)]
+    pub difficulty: Option<u128>,
+    /// Target effectivement visé (`--target` compris)
+    #[serde(default)]
+    pub target: Option<u128>,
+    #[serde(default)]
+    pub double_sha: bool,
+    /// Comparaison sur le digest entier (`--compare-mode full-256`)
+    #[serde(default)]
+    pub full_256: bool,
+}
+
+impl SolutionRecord {
+    /// Entrée pour `nonce`, trouvé sur `job` avec le hash `hash`
+    pub fn new(job: &BenchJob, nonce: u128, hash: &[u8; 32]) -> Self {
+        Self {
+            timestamp: SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs() as i64),
+            challenge_hex: hex::encode(job.challenge),
+            nonce,
+            block_number: job.block_number,
+            hash_hex: hex::encode(hash),
+            submitted: false,
+            signature: None,
+            accepted: false,
+            miner_pubkey_hex: Some(hex::encode(job.miner_pubkey)),
+            difficulty: Some(job.difficulty),
+            target: Some(job.target()),
+            double_sha: job.hash_algo == HashAlgo::DoubleSha256,
+            full_256: job.compare_mode == CompareMode::Full256,
+        }
+    }
+
+    /// Rejoue l'entrée: recalcule le hash et le compare au target enregistré, puis à
+    /// celui de `difficulty` si elle est donnée (difficulté actuelle)
+    ///
+    /// L'erreur explique pourquoi la solution est invalide.
+    pub fn replay(&self, difficulty: Option<u128>) -> std::result::Result<(), String> {
+        let (Some(miner_pubkey), Some(target)) = (&self.miner_pubkey_hex, self.target) else {
+            return Err("no miner pubkey or target recorded (older log format)".to_string());
+        };
+        let challenge = pow::parse_hash32(&self.challenge_hex).map_err(|e| format!("invalid challenge: {}", e))?;
+        let miner_pubkey = pow::parse_hash32(miner_pubkey).map_err(|e| format!("invalid miner pubkey: {}", e))?;
+        let algo = if self.double_sha { HashAlgo::DoubleSha256 } else { HashAlgo::Sha256 };
+        let compare = if self.full_256 { CompareMode::Full256 } else { CompareMode::Low128 };
+
+        let hash = pow::compute_hash(&challenge, &miner_pubkey, self.nonce, self.block_number, algo);
+        if hex::encode(hash) != self.hash_hex {
+            return Err(format!("recorded hash {} but the preimage hashes to {}", self.hash_hex, hex::encode(hash)));
+        }
+        if !compare.is_valid(&hash, target) {
+            return Err(format!("hash {} is above the recorded target 0x{:032x} ({} comparison)", self.hash_hex, target, compare.name()));
+        }
+        if let Some(difficulty) = difficulty {
+            if !compare.is_valid(&hash, pow::difficulty_to_target(difficulty)) {
+                return Err(format!(
+                    "valid at the recorded difficulty {} but not at difficulty {} (difficulty changed)",
+                    self.difficulty.map_or_else(|| "?".to_string(), |d| d.to_string()),
+                    difficulty
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Entrées d'un journal `--solution-log`, dans l'ordre
+pub fn read(path: &Path) -> Result<Vec<SolutionRecord>> {
+    let file = File::open(path).with_context(|| format!("Failed to open solution log {}", path.display()))?;
+    BufReader::new(file)
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| line.as_ref().map_or(true, |line| !line.trim().is_empty()))
+        .map(|(i, line)| {
+            let line = line?;
+            serde_json::from_str(&line).map_err(|e| anyhow!("{}:{}: invalid entry: {}", path.display(), i + 1, e))
+        })
+        .collect()
+}
+
+/// Fichier ouvert en ajout; chaque entrée est écrite sur disque avant de rendre la main
+pub struct SolutionLog {
+    file: Mutex<File>,
+}
+
+impl SolutionLog {
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open solution log {}", path.display()))?;
+
+        Ok(Self { file: Mutex::new(file) })
+    }
+
+    pub fn append(&self, record: &SolutionRecord) -> Result<()> {
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+
+        let mut file = self.file.lock().unwrap();
+        file.write_all(&line)?;
+        // Un crash juste après ne doit pas perdre l'entrée
+        file.sync_data()?;
+        Ok(())
+    }
+}
+
+/// Dernières solutions, les plus anciennes écartées au-delà de la capacité
+pub struct RecentSolutions {
+    capacity: usize,
+    records: Mutex<VecDeque<SolutionRecord>>,
+}
+
+impl RecentSolutions {
+    pub fn new(capacity: usize) -> Self {
+        Self { capacity, records: Mutex::new(VecDeque::with_capacity(capacity)) }
+    }
+
+    pub fn push(&self, record: SolutionRecord) {
+        let mut records = self.records.lock().unwrap();
+        if records.len() == self.capacity {
+            records.pop_front();
+        }
+        records.push_back(record);
+    }
+
+    /// Copie des entrées, de la plus ancienne à la plus récente
+    pub fn snapshot(&self) -> Vec<SolutionRecord> {
+        self.records.lock().unwrap().iter().cloned().collect()
+    }
+}
+
+impl Default for RecentSolutions {
+    fn default() -> Self {
+        Self::new(RECENT_SOLUTIONS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(challenge: [u8; 32], block_number: u64) -> BenchJob {
+        BenchJob { label: None, challenge, miner_pubkey: [9u8; 32], block_number, difficulty: 1_000, target: None, hash_algo: HashAlgo::Sha256, compare_mode: CompareMode::Low128 }
+    }
+
+    #[test]
+    fn test_entries_are_json_lines() {
+        let path = std::env::temp_dir().join(format!("pow-miner-solutions-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let log = SolutionLog::open(&path).unwrap();
+        let rejected = SolutionRecord { submitted: true, ..SolutionRecord::new(&job([1u8; 32], 7), 42, &[2u8; 32]) };
+        let accepted = SolutionRecord {
+            submitted: true,
+            signature: Some("5sig".to_string()),
+            accepted: true,
+            ..SolutionRecord::new(&job([3u8; 32], 8), u128::MAX, &[4u8; 32])
+        };
+        log.append(&rejected).unwrap();
+        log.append(&accepted).unwrap();
+
+        // Réouverture: les entrées sont ajoutées à la suite
+        drop(log);
+        SolutionLog::open(&path).unwrap().append(&rejected).unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let records: Vec<SolutionRecord> = contents.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(records, vec![rejected.clone(), accepted, rejected]);
+        assert_eq!(records[0].challenge_hex, "01".repeat(32));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_replay_explains_invalid_entries() {
+        use pow::vectors;
+
+        let job = BenchJob {
+            challenge: vectors::CHALLENGE,
+            miner_pubkey: vectors::MINER_PUBKEY,
+            block_number: vectors::BLOCK_NUMBER,
+            ..job([0u8; 32], 0)
+        };
+        let hash = pow::compute_hash(&job.challenge, &job.miner_pubkey, vectors::NONCE, job.block_number, HashAlgo::Sha256);
+        let valid = SolutionRecord::new(&job, vectors::NONCE, &hash);
+        assert_eq!(valid.replay(None), Ok(()));
+
+        // Valide à la difficulté du mining, plus après sa hausse
+        assert!(valid.replay(Some(1_000_000)).unwrap_err().contains("difficulty changed"));
+
+        let wrong_block = SolutionRecord { block_number: 8, ..valid.clone() };
+        assert!(wrong_block.replay(None).unwrap_err().contains("preimage hashes to"));
+
+        let above_target = SolutionRecord { target: Some(vectors::INVALID_TARGET), ..valid.clone() };
+        assert!(above_target.replay(None).unwrap_err().contains("above the recorded target"));
+
+        // Entrée d'un ancien journal: rien à rejouer
+        let old = SolutionRecord { miner_pubkey_hex: None, ..valid };
+        assert!(old.replay(None).unwrap_err().contains("older log format"));
+    }
+}
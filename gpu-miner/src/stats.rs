@@ -0,0 +1,255 @@
+//! Statistiques de la boucle de mining (résumé du mode `--soak`, progression des recherches)
+
+use crate::chain;
+use crate::ledger::{Ledger, LedgerSummary};
+use serde::Serialize;
+use std::time::Duration;
+
+/// Part du temps de résolution attendu entre deux lignes de progression
+const PROGRESS_FRACTION: f64 = 0.01;
+
+/// Pas de l'intervalle de progression: 250 ms x 2^k
+const PROGRESS_MIN_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Plafond de l'intervalle, aux difficultés de plusieurs heures par solution
+const PROGRESS_MAX_INTERVAL: Duration = Duration::from_secs(256);
+
+/// Intervalle tant qu'aucun hashrate n'a été mesuré
+const PROGRESS_DEFAULT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Solutions et erreurs observées par la boucle de mining
+#[derive(Debug, Default)]
+pub struct MiningStats {
+    /// Backend qui a réellement miné (`MinerBackend::name`, après un éventuel repli)
+    backend: String,
+    solve_times: Vec<Duration>,
+    errors: Vec<String>,
+    ledger: Ledger,
+    /// Recherches spéculatives (`--speculative`) confirmées et abandonnées
+    speculations: (usize, usize),
+    /// Endpoint RPC actif (`--json-rpc-url-list`), et bascules vers un autre endpoint
+    rpc_endpoint: Option<String>,
+    rpc_failovers: usize,
+}
+
+/// Résumé JSON d'une exécution
+#[derive(Debug, Serialize)]
+pub struct StatsSummary {
+    pub backend: String,
+    pub duration_s: f64,
+    pub solutions: usize,
+    pub avg_solve_ms: Option<f64>,
+    pub p50_solve_ms: Option<f64>,
+    pub p90_solve_ms: Option<f64>,
+    pub p99_solve_ms: Option<f64>,
+    /// Pic de mémoire résidente (VmHWM, Linux uniquement)
+    pub max_rss_kb: Option<u64>,
+    pub errors: Vec<String>,
+    pub ledger: LedgerSummary,
+    pub speculations_confirmed: usize,
+    pub speculations_discarded: usize,
+    pub rpc_endpoint: Option<String>,
+    pub rpc_failovers: usize,
+}
+
+impl MiningStats {
+    pub fn new(backend: &str) -> Self {
+        Self { backend: backend.to_string(), ..Self::default() }
+    }
+
+    pub fn backend(&self) -> &str {
+        &self.backend
+    }
+
+    pub fn record_solution(&mut self, solve_time: Duration) {
+        self.solve_times.push(solve_time);
+    }
+
+    pub fn record_error(&mut self, error: String) {
+        self.errors.push(error);
+    }
+
+    /// Issue d'une recherche spéculative: le challenge prévu était-il le bon
+    pub fn record_speculation(&mut self, confirmed: bool) {
+        if confirmed {
+            self.speculations.0 += 1;
+        } else {
+            self.speculations.1 += 1;
+        }
+    }
+
+    /// Enregistre l'endpoint RPC actif; vrai s'il diffère du précédent (bascule du pool)
+    pub fn observe_rpc_endpoint(&mut self, endpoint: &str) -> bool {
+        if self.rpc_endpoint.as_deref() == Some(endpoint) {
+            return false;
+        }
+        let failover = self.rpc_endpoint.replace(endpoint.to_string()).is_some();
+        self.rpc_failovers += failover as usize;
+        failover
+    }
+
+    pub fn solutions(&self) -> usize {
+        self.solve_times.len()
+    }
+
+    pub fn errors(&self) -> &[String] {
+        &self.errors
+    }
+
+    pub fn ledger(&self) -> &Ledger {
+        &self.ledger
+    }
+
+    pub fn ledger_mut(&mut self) -> &mut Ledger {
+        &mut self.ledger
+    }
+
+    pub fn summary(&self, duration: Duration) -> StatsSummary {
+        let mut times: Vec<f64> = self.solve_times.iter().map(|t| t.as_secs_f64() * 1000.0).collect();
+        times.sort_by(f64::total_cmp);
+
+        let avg = (!times.is_empty()).then(|| times.iter().sum::<f64>() / times.len() as f64);
+
+        StatsSummary {
+            backend: self.backend.clone(),
+            duration_s: duration.as_secs_f64(),
+            solutions: times.len(),
+            avg_solve_ms: avg,
+            p50_solve_ms: percentile(&times, 50.0),
+            p90_solve_ms: percentile(&times, 90.0),
+            p99_solve_ms: percentile(&times, 99.0),
+            max_rss_kb: max_rss_kb(),
+            errors: self.errors.clone(),
+            ledger: self.ledger.summary(),
+            speculations_confirmed: self.speculations.0,
+            speculations_discarded: self.speculations.1,
+            rpc_endpoint: self.rpc_endpoint.clone(),
+            rpc_failovers: self.rpc_failovers,
+        }
+    }
+}
+
+/// Intervalle entre deux lignes de progression: ~1% du temps de résolution attendu
+/// (`difficulty` hashes à `hashrate_hs`), arrondi à la puissance de 2 inférieure pour
+/// rester stable quand le hashrate mesuré fluctue
+pub fn progress_interval(difficulty: u128, hashrate_hs: f64) -> Duration {
+    if hashrate_hs.is_nan() || hashrate_hs <= 0.0 {
+        return PROGRESS_DEFAULT_INTERVAL;
+    }
+    let wanted = difficulty as f64 / hashrate_hs * PROGRESS_FRACTION;
+    let steps = (wanted / PROGRESS_MIN_INTERVAL.as_secs_f64()).max(1.0).log2().floor();
+    PROGRESS_MIN_INTERVAL.mul_f64(steps.exp2().min(u32::MAX as f64)).min(PROGRESS_MAX_INTERVAL)
+}
+
+/// Progression des recherches: dernier hashrate mesuré, repris d'un round à l'autre
+#[derive(Debug, Default)]
+pub struct Progress {
+    hashrate_hs: Option<f64>,
+}
+
+impl Progress {
+    /// Délai avant la prochaine ligne pour une recherche à `difficulty`
+    pub fn interval(&self, difficulty: u128) -> Duration {
+        progress_interval(difficulty, self.hashrate_hs.unwrap_or(0.0))
+    }
+
+    /// Mesure le hashrate de la recherche en cours et retourne la ligne à logger
+    ///
+    /// Le temps restant ne décroît pas: chaque hash a la même chance, quel que soit le
+    /// travail déjà fait, et une solution reste attendue en `difficulty` hashes.
+    pub fn observe(&mut self, difficulty: u128, hashes: u64, elapsed: Duration) -> String {
+        let hashrate_hs = hashes as f64 / elapsed.as_secs_f64().max(f64::EPSILON);
+        if hashes > 0 {
+            self.hashrate_hs = Some(hashrate_hs);
+        }
+        let expected = match hashrate_hs {
+            rate if rate > 0.0 => format!("{:.1}s", difficulty as f64 / rate),
+            _ => "unknown".to_string(),
+        };
+        format!(
+            "   ⏳ {} hashes ({:.0}% of expected) - {:.2} MH/s - one solution every ~{}",
+            chain::format_thousands(hashes),
+            hashes as f64 / difficulty.max(1) as f64 * 100.0,
+            hashrate_hs / 1e6,
+            expected
+        )
+    }
+}
+
+/// Percentile par rang le plus proche sur des valeurs triées
+fn percentile(sorted: &[f64], p: f64) -> Option<f64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+    Some(sorted[rank.clamp(1, sorted.len()) - 1])
+}
+
+/// Pic de mémoire résidente du processus, lu dans /proc/self/status
+fn max_rss_kb() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    line.split_whitespace().nth(1)?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary_percentiles() {
+        let mut stats = MiningStats::new("CPU");
+        for ms in 1..=100 {
+            stats.record_solution(Duration::from_millis(ms));
+        }
+        stats.record_error("boom".to_string());
+
+        let summary = stats.summary(Duration::from_secs(10));
+        assert_eq!(summary.backend, "CPU");
+        assert_eq!(summary.solutions, 100);
+        assert_eq!(summary.avg_solve_ms, Some(50.5));
+        assert_eq!(summary.p50_solve_ms, Some(50.0));
+        assert_eq!(summary.p90_solve_ms, Some(90.0));
+        assert_eq!(summary.p99_solve_ms, Some(99.0));
+        assert_eq!(summary.errors, vec!["boom"]);
+
+        assert_eq!(MiningStats::default().summary(Duration::ZERO).p50_solve_ms, None);
+    }
+
+    #[test]
+    fn test_summary_reports_active_rpc_endpoint() {
+        let mut stats = MiningStats::new("CPU");
+        assert!(!stats.observe_rpc_endpoint("http://primary"));
+        assert!(!stats.observe_rpc_endpoint("http://primary"));
+        assert!(stats.observe_rpc_endpoint("http://backup"));
+
+        let summary = stats.summary(Duration::ZERO);
+        assert_eq!(summary.rpc_endpoint.as_deref(), Some("http://backup"));
+        assert_eq!(summary.rpc_failovers, 1);
+        let json = serde_json::to_value(&summary).unwrap();
+        assert_eq!(json["rpc_endpoint"], "http://backup");
+    }
+
+    #[test]
+    fn test_progress_interval_scales_with_expected_solve_time() {
+        // 1e9 hashes à 10 MH/s: 100 s par solution, une ligne par seconde
+        assert_eq!(progress_interval(1_000_000_000, 1e7), Duration::from_secs(1));
+        // 10x la difficulté: intervalle ~10x (arrondi à la puissance de 2 inférieure)
+        assert_eq!(progress_interval(10_000_000_000, 1e7), Duration::from_secs(8));
+        // 10x le hashrate: l'inverse
+        assert_eq!(progress_interval(10_000_000_000, 1e8), Duration::from_secs(1));
+
+        // Solutions en quelques ms: plancher; des heures par solution: plafond
+        assert_eq!(progress_interval(1_000, 1e7), PROGRESS_MIN_INTERVAL);
+        assert_eq!(progress_interval(u64::MAX as u128, 1e7), PROGRESS_MAX_INTERVAL);
+        // Pas encore de mesure
+        assert_eq!(progress_interval(1_000_000, 0.0), PROGRESS_DEFAULT_INTERVAL);
+
+        // Le hashrate mesuré sert aux intervalles suivants
+        let mut progress = Progress::default();
+        assert_eq!(progress.interval(1_000_000_000), PROGRESS_DEFAULT_INTERVAL);
+        let line = progress.observe(1_000_000_000, 250_000_000, Duration::from_secs(25));
+        assert!(line.contains("250,000,000 hashes (25% of expected) - 10.00 MH/s - one solution every ~100.0s"), "{}", line);
+        assert_eq!(progress.interval(1_000_000_000), Duration::from_secs(1));
+    }
+}
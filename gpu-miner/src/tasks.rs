@@ -0,0 +1,55 @@
+//! Lancement de tâches tokio nommées, visibles dans tokio-console (feature `console`)
+
+use std::future::Future;
+use tokio::task::JoinHandle;
+
+#[cfg(all(feature = "console", not(tokio_unstable)))]
+compile_error!("the `console` feature requires RUSTFLAGS=\"--cfg tokio_unstable\"");
+
+/// Démarre le serveur tokio-console (port 6669 par défaut)
+#[cfg(feature = "console")]
+pub fn init_console() {
+    console_subscriber::init();
+}
+
+/// `tokio::spawn` avec un nom de tâche
+pub fn spawn<F>(name: &str, future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    #[cfg(all(feature = "console", tokio_unstable))]
+    {
+        tokio::task::Builder::new()
+            .name(name)
+            .spawn(future)
+            .expect("failed to spawn task")
+    }
+
+    #[cfg(not(all(feature = "console", tokio_unstable)))]
+    {
+        let _ = name;
+        tokio::spawn(future)
+    }
+}
+
+/// `tokio::task::spawn_blocking` avec un nom de tâche
+pub fn spawn_blocking<F, R>(name: &str, f: F) -> JoinHandle<R>
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    #[cfg(all(feature = "console", tokio_unstable))]
+    {
+        tokio::task::Builder::new()
+            .name(name)
+            .spawn_blocking(f)
+            .expect("failed to spawn blocking task")
+    }
+
+    #[cfg(not(all(feature = "console", tokio_unstable)))]
+    {
+        let _ = name;
+        tokio::task::spawn_blocking(f)
+    }
+}
@@ -0,0 +1,98 @@
+//! Vecteurs de référence de `compute_hash`
+//!
+//! Digests calculés une fois hors du mineur (hashlib) et figés: tout changement du format
+//! du préimage (ordre des champs, endianness du nonce ou du block number) casse ces tests.
+//! Ils servent aussi de référence pour valider les kernels GPU.
+
+// Le module est partagé avec les binaires, dont ces tests n'utilisent qu'une partie
+#[path = "../src/pow.rs"]
+#[allow(dead_code)]
+mod pow;
+
+use pow::{HashAlgo, PrefixHasher, ShaImpl};
+
+struct Vector {
+    challenge: [u8; 32],
+    miner_pubkey: [u8; 32],
+    nonce: u128,
+    block_number: u64,
+    algo: HashAlgo,
+    hash: &'static str,
+}
+
+/// 0x01, 0x02, ..., 0x20
+fn ascending() -> [u8; 32] {
+    std::array::from_fn(|i| i as u8 + 1)
+}
+
+/// 0x20, 0x1f, ..., 0x01
+fn descending() -> [u8; 32] {
+    std::array::from_fn(|i| 32 - i as u8)
+}
+
+fn vectors() -> Vec<Vector> {
+    vec![
+        Vector {
+            challenge: [0; 32],
+            miner_pubkey: [0; 32],
+            nonce: 0,
+            block_number: 0,
+            algo: HashAlgo::Sha256,
+            hash: "10eef285deef7a4b7c82b22aa53589b7833df29de3814649c772bbd5c832f365",
+        },
+        Vector {
+            challenge: ascending(),
+            miner_pubkey: descending(),
+            nonce: 42,
+            block_number: 1,
+            algo: HashAlgo::Sha256,
+            hash: "25c46a5d2fb94166da734e2136e0d0ef1e77b3ca41fb2e8ceb17c2eefce621fa",
+        },
+        // Nonce au-delà de 2^64: la moitié haute du u128 compte dans le préimage
+        Vector {
+            challenge: ascending(),
+            miner_pubkey: descending(),
+            nonce: (1 << 64) + 1,
+            block_number: 123_456_789,
+            algo: HashAlgo::Sha256,
+            hash: "c40298d32b545393d5f15103421ebc3f3319b8118cdb1acdab43f3087a83122f",
+        },
+        Vector {
+            challenge: [0xab; 32],
+            miner_pubkey: [0xcd; 32],
+            nonce: u128::MAX,
+            block_number: u64::MAX,
+            algo: HashAlgo::Sha256,
+            hash: "8cd06222f56f6ed0785ac2b12b950b6d971081c3c35ff93f7a6945c1bbd812bf",
+        },
+        Vector {
+            challenge: ascending(),
+            miner_pubkey: descending(),
+            nonce: 42,
+            block_number: 1,
+            algo: HashAlgo::DoubleSha256,
+            hash: "d0717f10ae71871dd24b24cf3236218347a9a0a7b7b204015570eaa932bfce80",
+        },
+    ]
+}
+
+#[test]
+fn test_compute_hash_matches_reference_vectors() {
+    for v in vectors() {
+        let hash = pow::compute_hash(&v.challenge, &v.miner_pubkey, v.nonce, v.block_number, v.algo);
+        assert_eq!(hex::encode(hash), v.hash, "nonce {} block {} ({:?})", v.nonce, v.block_number, v.algo);
+    }
+}
+
+#[test]
+fn test_midstate_hash_matches_reference_vectors() {
+    // Chemin du mineur CPU: challenge || miner_pubkey absorbé une fois, puis nonce || block_number
+    for v in vectors() {
+        let preimage = pow::preimage(&v.challenge, &v.miner_pubkey, v.nonce, v.block_number);
+        for sha in ShaImpl::available() {
+            let prefix = PrefixHasher::new(sha, &preimage[..64]);
+            let hash = v.algo.hash_prefixed(sha, &prefix, &preimage[64..]);
+            assert_eq!(hex::encode(hash), v.hash, "nonce {} with {}", v.nonce, sha.name());
+        }
+    }
+}